@@ -0,0 +1,3377 @@
+use chrono::NaiveDateTime;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+use crate::{
+    diff::{diff_count, diff_profiles, take_changes, DiffCount, DiffKind, DiffRow},
+    history::{Edit, History},
+    input::{TextArea, TextInput},
+    io::{parse_url_lines, Settings},
+    launcher::LaunchMode,
+    renderer::*,
+    query::Query,
+    search::{search_profiles, SearchHit},
+    urls::{normalize_url, validate_url},
+    group_is_in,
+    is_reserved_section,
+    normalize_group,
+    parse_tags,
+    PairRow,
+    profile_order,
+    profile_tree,
+    sort_profiles,
+    tag_counts,
+    MergeCount,
+    SplitBy,
+    Backend,
+    ARCHIVE_SECTION,
+    Errors,
+    Profile,
+    ProfileSortMode,
+    SortMode,
+    TabKind,
+    TabMove,
+    TreeRow,
+    URLTitlePair,
+};
+
+//
+//
+//The state of the TUI and how it changes:
+//`update` turns (state, event) into the next state, `view` draws a state.
+//
+//Anything that has to touch the outside world (clipboard, files)
+//is queued as an [Effect] for the caller to run;
+//its outcome comes back in as another [AppEvent].
+//
+//
+
+
+
+/// A scrollable list: the highlighted row, and which rows are on screen
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ListState {
+    cursor: usize,
+    viewport: Viewport,
+}
+
+impl ListState {
+    fn at(cursor: usize) -> Self {
+        Self { cursor, ..Self::default() }
+    }
+
+    pub fn get_cursor(&self) -> usize {
+        self.cursor
+    }
+    pub fn get_viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    fn move_cursor(&mut self, mv: CursorMove, len: usize) {
+        self.cursor = move_cursor(self.cursor, len, mv, &self.viewport);
+        self.viewport.scroll_to(self.cursor, len);
+    }
+
+    /// Keeps the cursor on a row that exists after the list changed to `len` rows
+    fn clamp(&mut self, len: usize) {
+        self.cursor = self.cursor.min(len.saturating_sub(1));
+        self.viewport.scroll_to(self.cursor, len);
+    }
+
+    fn resize(&mut self, (cols, rows): (u16, u16), header_rows: u16, len: usize) {
+        self.viewport.resize(cols, rows, header_rows);
+        self.viewport.scroll_to(self.cursor, len);
+    }
+}
+
+
+///
+///`/` pressed in a list: while the query is typed only the matching rows are shown,
+///afterwards all rows are, with the matches marked and reachable with n / N.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Search {
+    input: TextInput,
+    typing: bool,
+    /// where the cursor was before, to go back there if the search is cancelled
+    cursor_before: usize,
+}
+
+impl Search {
+    fn new(cursor_before: usize) -> Self {
+        Self { input: TextInput::default(), typing: true, cursor_before }
+    }
+
+    pub fn get_query(&self) -> &str {
+        self.input.get_text()
+    }
+    pub fn get_input(&self) -> &TextInput {
+        &self.input
+    }
+    pub fn is_typing(&self) -> bool {
+        self.typing
+    }
+
+    fn is_empty(&self) -> bool {
+        self.get_query().trim().is_empty()
+    }
+
+    /// What was typed, as a [Query]
+    pub fn parse(&self) -> Result<Query, Errors> {
+        Query::parse(self.get_query())
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Screen {
+    /// the main menu: the tree of groups and profiles, without the insides of the `collapsed` groups
+    ProfileList { list: ListState, search: Option<Search>, collapsed: Vec<String> },
+    ///
+    ///the tabs of one profile, in its sections (see [Profile::rows]) without the tabs of the `collapsed` ones;
+    ///`selected` are the indices of the tabs picked with SPACE or S, ascending
+    ///
+    Profile {
+        prfl_idx: usize,
+        list: ListState,
+        url_elide: Elide,
+        search: Option<Search>,
+        selected: Vec<usize>,
+        collapsed: Vec<String>,
+    },
+    /// one tab of a profile
+    BrowserTab { prfl_idx: usize, tab_idx: usize },
+    /// the main menu again, to choose where something goes
+    PickProfile { list: ListState, action: PickAction },
+    /// the tabs of all profiles matching what is typed
+    GlobalSearch { input: TextInput, list: ListState },
+    /// the orders to choose from for the list below
+    SortMenu { list: ListState, target: SortTarget },
+    /// every tag of the tabs and profiles, see [tag_counts]
+    TagList { list: ListState },
+    /// the tabs of all profiles that have `tag`, to be opened together
+    TagView { tag: String, list: ListState },
+    /// the note on a profile or tab being written
+    Note { target: NoteTarget, editor: TextArea },
+    /// the tabs of all profiles that were stale `at` that time, see [Profile::stale_pairs]
+    Stale { at: NaiveDateTime, list: ListState },
+    /// the tabs of a profile next to those of `with`, see [diff_profiles]; only those that differ if `only_changes`
+    Diff { prfl_idx: usize, with: DiffWith, list: ListState, only_changes: bool },
+    /// every key of the screen called `what` (such as [HELP_MAINMENU]), which shows only a few
+    Help { what: &'static str, keys: &'static [&'static str], list: ListState },
+}
+
+impl Screen {
+    fn list_mut(&mut self) -> Option<&mut ListState> {
+        match self {
+            Screen::ProfileList { list, .. }
+            | Screen::Profile { list, .. }
+            | Screen::PickProfile { list, .. }
+            | Screen::GlobalSearch { list, .. }
+            | Screen::SortMenu { list, .. }
+            | Screen::TagList { list }
+            | Screen::TagView { list, .. }
+            | Screen::Stale { list, .. }
+            | Screen::Diff { list, .. }
+            | Screen::Help { list, .. } => Some(list),
+            Screen::BrowserTab { .. } | Screen::Note { .. } => None,
+        }
+    }
+
+    /// For the screens that can be searched with `/`
+    fn list_and_search_mut(&mut self) -> Option<(&mut ListState, &mut Option<Search>)> {
+        match self {
+            Screen::ProfileList { list, search, .. }
+            | Screen::Profile { list, search, .. } => Some((list, search)),
+            _ => None,
+        }
+    }
+
+    pub fn get_search(&self) -> Option<&Search> {
+        match self {
+            Screen::ProfileList { search, .. }
+            | Screen::Profile { search, .. } => search.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The rows above the list
+    fn header_rows(&self) -> u16 {
+        match self {
+            Screen::ProfileList { .. } => STATIC_INFO_MAINMENU_LEN,
+            Screen::Profile { .. } => STATIC_INFO_PROFILE_LEN,
+            Screen::PickProfile { .. } => STATIC_INFO_PICK_LEN,
+            Screen::GlobalSearch { .. } => STATIC_INFO_SEARCH_LEN,
+            Screen::BrowserTab { .. } => STATIC_INFO_TAB_LEN,
+            Screen::SortMenu { .. } => STATIC_INFO_SORT_LEN,
+            Screen::TagList { .. } => STATIC_INFO_TAGS_LEN,
+            Screen::TagView { .. } => STATIC_INFO_TAG_VIEW_LEN,
+            Screen::Note { .. } => STATIC_INFO_NOTE_LEN,
+            Screen::Stale { .. } => STATIC_INFO_STALE_LEN,
+            Screen::Diff { .. } => STATIC_INFO_DIFF_LEN,
+            Screen::Help { .. } => STATIC_INFO_HELP_LEN,
+        }
+    }
+}
+
+
+/// The rows of the list on `screen` matching its search, best first, if it has a non-empty search
+fn search_hits(prfls: &[Profile], screen: &Screen) -> Option<Vec<usize>> {
+    let search = screen.get_search().filter(|search| !search.is_empty())?;
+    // a query that does not parse matches nothing, the prompt tells why
+    let Ok(query) = search.parse() else {
+        return Some(Vec::new());
+    };
+
+    let mut scored: Vec<(usize, i64)> = match screen {
+        Screen::ProfileList { collapsed, .. } => profile_tree(prfls, collapsed)
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, row)| match row {
+                TreeRow::Profile { prfl_idx, .. } => Some((idx, query.score_profile(&prfls[*prfl_idx])?)),
+                TreeRow::Group { .. } => None,
+            })
+            .collect(),
+        Screen::Profile { prfl_idx, collapsed, .. } => prfls
+            .get(*prfl_idx)
+            .map(|prfl| {
+                prfl.rows(collapsed)
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(idx, row)| match row {
+                        PairRow::Pair { tab_idx } => Some((idx, query.score(prfl, &prfl.get_pairs()[*tab_idx])?.score)),
+                        PairRow::Section { .. } => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+
+    Some(scored.into_iter().map(|(idx, _)| idx).collect())
+}
+
+/// The tabs of all profiles matching what was typed in [Screen::GlobalSearch]
+fn global_hits(prfls: &[Profile], input: &TextInput) -> Vec<SearchHit> {
+    Query::parse(input.get_text())
+        .map(|query| search_profiles(prfls, &query))
+        .unwrap_or_default()
+}
+
+/// The tabs of all profiles tagged `tag` (themselves or through their profile), in the order of the profiles
+fn tag_hits(prfls: &[Profile], tag: &str) -> Vec<SearchHit> {
+    search_profiles(prfls, &Query::tag(tag))
+}
+
+/// The rows the list on `screen` shows, if not all of them: only the hits while a search is typed
+fn shown_rows(prfls: &[Profile], screen: &Screen) -> Option<Vec<usize>> {
+    match screen.get_search() {
+        Some(search) if search.is_typing() => search_hits(prfls, screen),
+        _ => None,
+    }
+}
+
+/// How many rows the list on `screen` shows
+fn row_count(prfls: &[Profile], screen: &Screen) -> usize {
+    if let Some(shown) = shown_rows(prfls, screen) {
+        return shown.len();
+    }
+
+    match screen {
+        Screen::ProfileList { collapsed, .. } => profile_tree(prfls, collapsed).len(),
+        Screen::PickProfile { .. } => prfls.len(),
+        Screen::Profile { prfl_idx, collapsed, .. } => prfls.get(*prfl_idx).map(|prfl| prfl.rows(collapsed).len()).unwrap_or(0),
+        Screen::GlobalSearch { input, .. } => global_hits(prfls, input).len(),
+        Screen::BrowserTab { .. } | Screen::Note { .. } => 0,
+        Screen::SortMenu { target: SortTarget::Profiles, .. } => ProfileSortMode::ALL.len(),
+        Screen::SortMenu { target: SortTarget::Tabs { .. }, .. } => SortMode::ALL.len(),
+        Screen::TagList { .. } => tag_counts(prfls).len(),
+        Screen::TagView { tag, .. } => tag_hits(prfls, tag).len(),
+        Screen::Stale { at, .. } => stale_tabs(prfls, *at).len(),
+        Screen::Diff { prfl_idx, with, only_changes, .. } => diff_rows(prfls, *prfl_idx, with, *only_changes).len(),
+        Screen::Help { keys, .. } => keys.len(),
+    }
+}
+
+/// The stale tabs of all profiles `at` that time as `(prfl_idx, tab_idx)`, the longest unopened first
+fn stale_tabs(prfls: &[Profile], at: NaiveDateTime) -> Vec<(usize, usize)> {
+    let mut tabs: Vec<(usize, usize)> = prfls
+        .iter()
+        .enumerate()
+        .flat_map(|(prfl_idx, prfl)| prfl.stale_pairs(at).into_iter().map(move |tab_idx| (prfl_idx, tab_idx)))
+        .collect();
+    tabs.sort_by_key(|&(prfl_idx, tab_idx)| prfls[prfl_idx].get_pairs()[tab_idx].get_time_last_used());
+
+    tabs
+}
+
+/// The rows of [Screen::Diff]
+fn diff_rows(prfls: &[Profile], prfl_idx: usize, with: &DiffWith, only_changes: bool) -> Vec<DiffRow> {
+    let (Some(prfl), Some(other)) = (prfls.get(prfl_idx), with.profile(prfls)) else {
+        return Vec::new();
+    };
+
+    let mut rows = diff_profiles(prfl, other);
+    if only_changes {
+        rows.retain(|row| row.kind != DiffKind::Same);
+    }
+
+    rows
+}
+
+/// What happens to the profile chosen in [Screen::PickProfile]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PickAction {
+    /// `tab_idxs` ascending
+    CopyTabs { prfl_idx: usize, tab_idxs: Vec<usize> },
+    /// `tab_idxs` ascending
+    MoveTabs { prfl_idx: usize, tab_idxs: Vec<usize> },
+    /// the profile chosen takes in the one at `prfl_idx`, which is deleted
+    MergeInto { prfl_idx: usize },
+    /// the profile at `prfl_idx` is compared with the one chosen, see [Screen::Diff]
+    DiffWith { prfl_idx: usize },
+}
+
+
+/// What the profile of [Screen::Diff] is compared with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffWith {
+    Profile { prfl_idx: usize },
+    /// read from the file at `path`, see [crate::io::read_profile_to_compare]
+    File { path: String, prfl: Box<Profile> },
+}
+
+impl DiffWith {
+    pub fn profile<'a>(&'a self, prfls: &'a [Profile]) -> Option<&'a Profile> {
+        match self {
+            DiffWith::Profile { prfl_idx } => prfls.get(*prfl_idx),
+            DiffWith::File { prfl, .. } => Some(prfl),
+        }
+    }
+
+    /// The name of the profile, or the path of the file
+    pub fn label(&self, prfls: &[Profile]) -> String {
+        match self {
+            DiffWith::Profile { prfl_idx } => prfls.get(*prfl_idx).map(|prfl| prfl.get_name().clone()).unwrap_or_default(),
+            DiffWith::File { path, .. } => path.clone(),
+        }
+    }
+}
+
+
+/// What the note of [Screen::Note] is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteTarget {
+    Profile { prfl_idx: usize },
+    Tab { prfl_idx: usize, tab_idx: usize },
+}
+
+impl NoteTarget {
+    /// The note as it is now, before the editing
+    fn note_in(&self, prfls: &[Profile]) -> Option<String> {
+        let note = match *self {
+            NoteTarget::Profile { prfl_idx } => prfls.get(prfl_idx)?.get_note(),
+            NoteTarget::Tab { prfl_idx, tab_idx } => prfls.get(prfl_idx)?.get_pairs().get(tab_idx)?.get_note(),
+        };
+
+        Some(note.to_string())
+    }
+}
+
+
+/// What [Screen::SortMenu] sorts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortTarget {
+    Profiles,
+    Tabs { prfl_idx: usize },
+}
+
+
+/// Asks something in the last row, on top of the screen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Dialog {
+    /// a line of text to type, ENTER accepts it
+    Text { label: String, input: TextInput, action: TextAction },
+    /// answered with Y or N
+    Confirm { question: String, action: ConfirmAction },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextAction {
+    /// first step of editing a tab
+    EditTitle { prfl_idx: usize, tab_idx: usize },
+    /// second step, with the title typed in the first
+    EditURL { prfl_idx: usize, tab_idx: usize, title: String },
+    /// a [Query], adding the matching tabs of the profile to the selection
+    SelectMatching { prfl_idx: usize },
+    /// the tags of one tab, replacing the ones it has
+    EditTabTags { prfl_idx: usize, tab_idx: usize },
+    /// tags to add to the selected tabs
+    AddTabTags { prfl_idx: usize, tab_idxs: Vec<usize> },
+    /// the tags of the profile, which all its tabs have too
+    EditProfileTags { prfl_idx: usize },
+    /// the group to put the profile in, see [normalize_group]
+    MoveToGroup { prfl_idx: usize },
+    /// the new path of the group, which every group and profile inside it moves along to
+    RenameGroup { path: String },
+    /// the section to put the tabs in, see [Profile::set_pair_section]
+    MoveToSection { prfl_idx: usize, tab_idxs: Vec<usize> },
+    /// the new name of the section, see [Profile::rename_section]
+    RenameSection { prfl_idx: usize, name: String },
+    /// the name of a new profile for the selected tabs, see [Profile::split_off]
+    SplitSelection { prfl_idx: usize, tab_idxs: Vec<usize> },
+    /// `host` or `tag`, see [Profile::split_plan]
+    SplitBy { prfl_idx: usize },
+    /// the path of a file to compare the profile with, see [Screen::Diff]
+    DiffWithFile { prfl_idx: usize },
+    /// after how many days unopened the tabs of the profile are stale, see [Profile::set_expire_after]
+    ExpireAfter { prfl_idx: usize },
+    /// the name of a new profile from the template at `prfl_idx`, see [Profile::from_template]
+    NameFromTemplate { prfl_idx: usize },
+    /// the value of the placeholder `placeholder` of the template, after the `values` of the ones before it
+    TemplateValue { prfl_idx: usize, name: String, placeholder: String, values: Vec<(String, String)> },
+    /// the name of a copy of the profile, see [Profile::duplicate]
+    DuplicateProfile { prfl_idx: usize },
+    ///
+    ///the value of the placeholder `placeholder` of the template tabs among `tabs` (see [TabKind::Template]),
+    ///after the `values` of the ones before it; UP / DOWN go through the `recent` values of it
+    ///
+    LaunchValue {
+        tabs: Vec<(usize, usize)>,
+        mode: LaunchMode,
+        placeholder: String,
+        recent: Vec<String>,
+        values: Vec<(String, String)>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfirmAction {
+    DeleteTab { prfl_idx: usize, tab_idx: usize },
+    /// `tab_idxs` ascending
+    DeleteTabs { prfl_idx: usize, tab_idxs: Vec<usize> },
+    /// every change shown in [Screen::Diff], into the profile on the left
+    TakeAllChanges,
+    /// the tabs of all profiles that were stale `at` that time, see [Profile::archive_pairs]
+    ArchiveStale { at: NaiveDateTime },
+}
+
+
+/// Work `update` wants done outside of it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Effect {
+    /// read the clipboard, then send [AppEvent::Pasted]
+    ReadClipboard { prfl_idx: usize },
+    /// write the profile to its file, then send [AppEvent::Saved]
+    Save { prfl_idx: usize },
+    ///
+    ///open the `tabs`, as `(prfl_idx, tab_idx)`, one window per section (see [crate::launcher::launch_tabs]),
+    ///with the placeholders of template tabs filled with `values`, then send [AppEvent::Launched]
+    ///
+    Launch { tabs: Vec<(usize, usize)>, values: Vec<(String, String)>, mode: LaunchMode },
+    /// write the settings file, then send [AppEvent::SettingsSaved]
+    SaveSettings { settings: Settings },
+    /// remove the file of a profile that is gone, then send [AppEvent::Deleted]
+    Delete { file_name: String },
+    /// read a profile from the file at `path` to compare the one at `prfl_idx` with, then send [AppEvent::ReadToCompare]
+    ReadToCompare { prfl_idx: usize, path: String },
+}
+
+#[derive(Debug)]
+pub enum AppEvent {
+    Terminal(Event),
+
+    /// the outcome of [Effect::ReadClipboard]
+    Pasted { prfl_idx: usize, text: Result<String, Errors> },
+    /// the outcome of [Effect::Save]
+    Saved { prfl_idx: usize, result: Result<(), Errors> },
+    /// the outcome of [Effect::Launch], which happened `at` that time
+    Launched { tabs: Vec<(usize, usize)>, values: Vec<(String, String)>, at: NaiveDateTime, result: Result<(), Errors> },
+    /// the outcome of [Effect::SaveSettings]
+    SettingsSaved { result: Result<(), Errors> },
+    /// the outcome of [Effect::Delete]
+    Deleted { file_name: String, result: Result<(), Errors> },
+    /// the outcome of [Effect::ReadToCompare]
+    ReadToCompare { prfl_idx: usize, path: String, result: Result<Profile, Errors> },
+}
+
+
+#[derive(Debug)]
+pub struct App {
+    /// in the order of `profile_sort`
+    prfls: Vec<Profile>,
+    profile_sort: ProfileSortMode,
+
+    /// never empty, the last screen is the one shown
+    screens: Vec<Screen>,
+
+    /// shown in the last row until replaced
+    status: Option<String>,
+    /// shown in the last row instead of the status while open
+    dialog: Option<Dialog>,
+    /// `(columns, rows)` of the terminal
+    size: (u16, u16),
+
+    effects: Vec<Effect>,
+    running: bool,
+
+    /// the edits to the profiles made so far, see [App::record_edit]
+    history: History,
+    ///
+    ///the profiles changed by the event being handled, by id, as they were before it
+    ///([None] for the ones it created), see [App::touch]
+    ///
+    touched: Vec<(usize, Option<Profile>)>,
+}
+
+impl App {
+    pub fn new(prfls: Vec<Profile>, size: (u16, u16)) -> Self {
+        let mut app = Self {
+            prfls,
+            profile_sort: ProfileSortMode::default(),
+            screens: vec![Screen::ProfileList { list: ListState::default(), search: None, collapsed: Vec::new() }],
+            status: None,
+            dialog: None,
+            size,
+            effects: Vec::new(),
+            running: true,
+            history: History::default(),
+            touched: Vec::new(),
+        };
+        app.fit_screens();
+
+        app
+    }
+
+    pub fn with_status(mut self, status: Option<String>) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Sorts the profiles by `mode`, e.g. the one remembered in the [Settings]
+    pub fn with_profile_sort(mut self, mode: ProfileSortMode) -> Self {
+        self.profile_sort = mode;
+        sort_profiles(&mut self.prfls, mode);
+        self
+    }
+
+    pub fn get_profile_sort(&self) -> ProfileSortMode {
+        self.profile_sort
+    }
+
+    pub fn get_profiles(&self) -> &[Profile] {
+        &self.prfls
+    }
+    pub fn get_mut_profile(&mut self, prfl_idx: usize) -> Option<&mut Profile> {
+        self.prfls.get_mut(prfl_idx)
+    }
+
+    pub fn get_screen(&self) -> &Screen {
+        self.screens.last().expect("there is always a screen")
+    }
+    pub fn get_screens(&self) -> &[Screen] {
+        &self.screens
+    }
+
+    pub fn get_status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+    pub fn get_dialog(&self) -> Option<&Dialog> {
+        self.dialog.as_ref()
+    }
+
+    pub fn get_size(&self) -> (u16, u16) {
+        self.size
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Hands the queued effects to the caller, who has to run them
+    pub fn take_effects(&mut self) -> Vec<Effect> {
+        std::mem::take(&mut self.effects)
+    }
+
+
+    fn push(&mut self, screen: Screen) {
+        self.screens.push(screen);
+        self.fit_screens();
+    }
+
+    /// Replaces the screen shown, e.g. with one whose cursor moved
+    fn set_screen(&mut self, screen: Screen) {
+        *self.screens.last_mut().expect("there is always a screen") = screen;
+    }
+
+    /// Goes back one screen, or quits from the main menu
+    fn pop(&mut self) {
+        match self.screens.len() {
+            1 => self.running = false,
+            _ => { self.screens.pop(); },
+        }
+    }
+
+    fn top_mut(&mut self) -> &mut Screen {
+        self.screens.last_mut().expect("there is always a screen")
+    }
+
+    /// Moves the cursor of the list shown, if there is one
+    fn move_cursor(&mut self, mv: CursorMove) {
+        let len = row_count(&self.prfls, self.get_screen());
+
+        if let Some(list) = self.top_mut().list_mut() {
+            list.move_cursor(mv, len);
+        }
+    }
+
+    /// Sizes (and scrolls) every list on the stack to the terminal and to its current length
+    fn fit_screens(&mut self) {
+        let size = self.size;
+
+        for idx in 0..self.screens.len() {
+            let len = row_count(&self.prfls, &self.screens[idx]);
+            let header_rows = self.screens[idx].header_rows();
+
+            if let Some(list) = self.screens[idx].list_mut() {
+                list.clamp(len);
+                list.resize(size, header_rows, len);
+            }
+        }
+    }
+
+    /// Drops the screens showing the tab at `tab_idx` of the profile at `prfl_idx`, which is gone
+    fn forget_tab(&mut self, prfl_idx: usize, tab_idx: usize) {
+        let shows_tab = |screen: &Screen| match screen {
+            Screen::BrowserTab { prfl_idx: p, tab_idx: t }
+            | Screen::Note { target: NoteTarget::Tab { prfl_idx: p, tab_idx: t }, .. } => *p == prfl_idx && *t == tab_idx,
+            Screen::PickProfile { action: PickAction::CopyTabs { prfl_idx: p, tab_idxs }, .. }
+            | Screen::PickProfile { action: PickAction::MoveTabs { prfl_idx: p, tab_idxs }, .. } => {
+                *p == prfl_idx && tab_idxs.contains(&tab_idx)
+            },
+            _ => false,
+        };
+
+        if let Some(first) = self.screens.iter().position(shows_tab) {
+            self.screens.truncate(first.max(1));
+        }
+
+        // the tabs behind it moved up by one
+        for screen in &mut self.screens {
+            if let Screen::Profile { prfl_idx: p, selected, .. } = screen {
+                if *p == prfl_idx {
+                    selected.retain(|&idx| idx != tab_idx);
+                    selected.iter_mut().filter(|idx| **idx > tab_idx).for_each(|idx| *idx -= 1);
+                }
+            }
+        }
+        self.fit_screens();
+    }
+
+    ///
+    ///Keeps every screen showing tabs of the profile at `prfl_idx` on the same tabs (and sections)
+    ///after they were reordered; `before` is the profile as it was.
+    ///
+    fn reordered_tabs(&mut self, prfl_idx: usize, before: &Profile) {
+        let Some(prfl) = self.prfls.get(prfl_idx) else {
+            return;
+        };
+        let moved = |idx: usize| before.get_pairs().get(idx).and_then(|pair| prfl.find_url(&pair.url)).unwrap_or(idx);
+
+        for screen in &mut self.screens {
+            match screen {
+                Screen::Profile { prfl_idx: p, list, selected, collapsed, .. } if *p == prfl_idx => {
+                    let row = match before.rows(collapsed).get(list.cursor).cloned() {
+                        Some(PairRow::Pair { tab_idx }) => Some(PairRow::Pair { tab_idx: moved(tab_idx) }),
+                        row => row,
+                    };
+                    if let Some(idx) = row.and_then(|row| pair_row_position(&prfl.rows(collapsed), &row)) {
+                        list.cursor = idx;
+                    }
+                    selected.iter_mut().for_each(|idx| *idx = moved(*idx));
+                    selected.sort_unstable();
+                },
+                Screen::BrowserTab { prfl_idx: p, tab_idx }
+                | Screen::Note { target: NoteTarget::Tab { prfl_idx: p, tab_idx }, .. } if *p == prfl_idx => {
+                    *tab_idx = moved(*tab_idx)
+                },
+                _ => {},
+            }
+        }
+        self.fit_screens();
+    }
+
+    ///
+    ///Remembers the profile at `prfl_idx` as it is, to be able to undo what the event being handled
+    ///is about to do to it; every change to a profile (but opening its tabs) comes after this.
+    ///
+    fn touch(&mut self, prfl_idx: usize) {
+        let Some(prfl) = self.prfls.get(prfl_idx) else {
+            return;
+        };
+
+        if self.touched.iter().all(|(id, _)| *id != prfl.get_id()) {
+            self.touched.push((prfl.get_id(), Some(prfl.clone())));
+        }
+    }
+
+    /// Remembers what the event just handled did to the profiles it touched, if anything, to be undone
+    fn record_edit(&mut self) {
+        let touched = std::mem::take(&mut self.touched);
+        let before: Vec<Profile> = touched
+            .iter()
+            .filter_map(|(_, prfl)| prfl.clone())
+            .collect();
+        let after: Vec<Profile> = touched
+            .iter()
+            .filter_map(|(id, _)| position_of(&self.prfls, *id))
+            .map(|prfl_idx| self.prfls[prfl_idx].clone())
+            .collect();
+
+        if let Some(edit) = Edit::between(&before, &after) {
+            self.history.record(edit);
+        }
+    }
+
+    ///
+    ///Makes the profiles like in `edit.after` (see [History]), saving every profile that changes
+    ///and deleting the files of the ones that go away;
+    ///only the lists stay on the stack, and only those whose profile is still there.
+    ///
+    fn apply_edit(&mut self, edit: Edit) {
+        // the indices are about to change
+        let shown = self.shown_profiles();
+
+        let gone = edit.before
+            .iter()
+            .filter(|prfl| edit.after.iter().all(|other| other.get_id() != prfl.get_id()));
+        for prfl in gone {
+            if let Some(idx) = position_of(&self.prfls, prfl.get_id()) {
+                if let Some(file_name) = self.prfls.remove(idx).get_file_name() {
+                    self.effects.push(Effect::Delete { file_name: file_name.to_string() });
+                }
+            }
+        }
+
+        let ids: Vec<usize> = edit.after.iter().map(Profile::get_id).collect();
+        for mut prfl in edit.after {
+            match position_of(&self.prfls, prfl.get_id()) {
+                Some(idx) => {
+                    // the edit may have been recorded before the profile was first saved
+                    if let (None, Some(file_name)) = (prfl.get_file_name(), self.prfls[idx].get_file_name()) {
+                        prfl.set_file_name(file_name);
+                    }
+                    // opening tabs is no edit, what it changed stays
+                    prfl.keep_launches(&self.prfls[idx]);
+                    self.prfls[idx] = prfl;
+                },
+                None => self.prfls.push(prfl),
+            }
+        }
+        sort_profiles(&mut self.prfls, self.profile_sort);
+        for id in ids {
+            if let Some(prfl_idx) = position_of(&self.prfls, id) {
+                self.effects.push(Effect::Save { prfl_idx });
+            }
+        }
+
+        self.follow_profiles(shown);
+    }
+
+    ///
+    ///Adds `prfl` where [sort_profiles] puts it and saves it; every screen and every save
+    ///still to be done stays on its profile. Returns where it went.
+    ///
+    fn insert_profile(&mut self, prfl: Profile) -> usize {
+        let id = prfl.get_id();
+        let shown = self.shown_profiles();
+        let highlighted = highlighted_tree_row(self);
+        let saving: Vec<Option<usize>> = self.effects
+            .iter()
+            .map(|effect| match effect {
+                Effect::Save { prfl_idx } => self.prfls.get(*prfl_idx).map(Profile::get_id),
+                _ => None,
+            })
+            .collect();
+
+        self.touched.push((id, None));
+        self.prfls.push(prfl);
+        sort_profiles(&mut self.prfls, self.profile_sort);
+
+        for (effect, id) in self.effects.iter_mut().zip(saving) {
+            if let (Effect::Save { prfl_idx }, Some(id)) = (effect, id) {
+                *prfl_idx = position_of(&self.prfls, id).unwrap_or_default();
+            }
+        }
+        self.follow_profiles(shown);
+        keep_highlighted_tree_row(self, highlighted);
+
+        let prfl_idx = position_of(&self.prfls, id).unwrap_or_default();
+        self.effects.push(Effect::Save { prfl_idx });
+
+        prfl_idx
+    }
+
+    /// The id of the profile each screen shows, for [App::follow_profiles]
+    fn shown_profiles(&self) -> Vec<Option<usize>> {
+        self.screens
+            .iter()
+            .map(|screen| match screen {
+                Screen::Profile { prfl_idx, .. } => self.prfls.get(*prfl_idx).map(Profile::get_id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    ///
+    ///Puts every screen back on the profile it showed (`shown`, see [App::shown_profiles])
+    ///after the profiles moved; only the lists stay on the stack, and only those whose profile is still there.
+    ///
+    fn follow_profiles(&mut self, shown: Vec<Option<usize>>) {
+        let first_gone = self.screens
+            .iter()
+            .zip(&shown)
+            .position(|(screen, id)| match screen {
+                Screen::ProfileList { .. }
+                | Screen::GlobalSearch { .. }
+                | Screen::TagList { .. }
+                | Screen::TagView { .. }
+                | Screen::Stale { .. }
+                | Screen::Help { .. } => false,
+                Screen::Profile { .. } => id.and_then(|id| position_of(&self.prfls, id)).is_none(),
+                _ => true,
+            });
+        if let Some(first) = first_gone {
+            self.screens.truncate(first.max(1));
+        }
+        for (screen, id) in self.screens.iter_mut().zip(shown) {
+            if let (Screen::Profile { prfl_idx, selected, .. }, Some(id)) = (screen, id) {
+                *prfl_idx = position_of(&self.prfls, id).unwrap_or_default();
+                selected.clear();
+            }
+        }
+        self.fit_screens();
+    }
+
+    ///
+    ///Takes the profile at `prfl_idx` out of the list, going back to the main menu;
+    ///the profiles behind it move up by one. Its file is left to the caller.
+    ///
+    fn remove_profile(&mut self, prfl_idx: usize) -> Option<Profile> {
+        if prfl_idx >= self.prfls.len() {
+            return None;
+        }
+
+        self.screens.truncate(1);
+        self.touch(prfl_idx);
+        let prfl = self.prfls.remove(prfl_idx);
+        self.fit_screens();
+
+        Some(prfl)
+    }
+}
+
+
+
+
+/// Index of the profile with the id `id`, see [Profile::get_id]
+fn position_of(prfls: &[Profile], id: usize) -> Option<usize> {
+    prfls.iter().position(|prfl| prfl.get_id() == id)
+}
+
+
+/// The key of a key press, with the modifiers, if `event` is one
+fn key_press_of(event: &Event) -> Option<KeyEvent> {
+    match event {
+        Event::Key(key) if key.kind == KeyEventKind::Press => Some(*key),
+        _ => None,
+    }
+}
+
+fn cursor_move_of(key: &KeyEvent) -> Option<CursorMove> {
+    match key.code {
+        KeyCode::Up => Some(CursorMove::Up),
+        KeyCode::Down => Some(CursorMove::Down),
+        KeyCode::PageUp => Some(CursorMove::PageUp),
+        KeyCode::PageDown => Some(CursorMove::PageDown),
+        KeyCode::Home => Some(CursorMove::Home),
+        KeyCode::End => Some(CursorMove::End),
+        _ => None,
+    }
+}
+
+/// SHIFT + UP / DOWN / HOME / END, or < / > to the top / bottom for terminals that do not tell SHIFT apart
+fn tab_move_of(key: &KeyEvent) -> Option<TabMove> {
+    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+
+    match key.code {
+        KeyCode::Up if shift => Some(TabMove::Up),
+        KeyCode::Down if shift => Some(TabMove::Down),
+        KeyCode::Home if shift => Some(TabMove::Top),
+        KeyCode::End if shift => Some(TabMove::Bottom),
+        KeyCode::Char('<') => Some(TabMove::Top),
+        KeyCode::Char('>') => Some(TabMove::Bottom),
+        _ => None,
+    }
+}
+
+/// `c` pressed, with or without SHIFT
+fn is_char(key: &KeyEvent, c: char) -> bool {
+    matches!(key.code, KeyCode::Char(k) if k.eq_ignore_ascii_case(&c))
+}
+
+
+
+
+///
+///Returns the state after `event` happened in `app`.
+///
+///Never does any IO itself; see [App::take_effects].
+///
+pub fn update(mut app: App, event: AppEvent) -> App {
+    match event {
+        AppEvent::Terminal(Event::Resize(cols, rows)) => {
+            app.size = (cols, rows);
+            app.fit_screens();
+        },
+        AppEvent::Terminal(event) => {
+            if let Some(key) = key_press_of(&event) {
+                if !update_on_history_key(&mut app, &key) {
+                    update_on_key(&mut app, &key);
+                    app.record_edit();
+                }
+            }
+        },
+
+        AppEvent::Pasted { prfl_idx, text } => {
+            update_on_paste(&mut app, prfl_idx, text);
+            app.record_edit();
+        },
+
+        AppEvent::Saved { prfl_idx, result: Err(e) } => {
+            let name = app.prfls.get(prfl_idx).map(|prfl| prfl.get_name().to_string()).unwrap_or_default();
+            app.status = Some(format!(" == Saving \"{}\" failed: {}", name, e));
+        },
+        AppEvent::Saved { result: Ok(()), .. } => {},
+
+        AppEvent::SettingsSaved { result: Err(e) } => app.status = Some(format!(" == Saving the settings failed: {}", e)),
+        AppEvent::SettingsSaved { result: Ok(()) } => {},
+
+        AppEvent::Deleted { file_name, result: Err(e) } => app.status = Some(format!(" == Deleting {} failed: {}", file_name, e)),
+        AppEvent::Deleted { result: Ok(()), .. } => {},
+
+        AppEvent::ReadToCompare { path, result: Err(e), .. } => app.status = Some(format!(" == Reading {} failed: {}", path, e)),
+        AppEvent::ReadToCompare { prfl_idx, path, result: Ok(prfl) } => {
+            let with = DiffWith::File { path, prfl: Box::new(prfl) };
+            app.push(Screen::Diff { prfl_idx, with, list: ListState::default(), only_changes: false });
+        },
+
+        AppEvent::Launched { tabs, values, at, result: Ok(()) } => {
+            let mut prfl_idxs: Vec<usize> = tabs.iter().map(|&(prfl_idx, _)| prfl_idx).collect();
+            prfl_idxs.sort_unstable();
+            prfl_idxs.dedup();
+
+            for &prfl_idx in &prfl_idxs {
+                let tab_idxs: Vec<usize> = tabs
+                    .iter()
+                    .filter(|&&(p, _)| p == prfl_idx)
+                    .map(|&(_, tab_idx)| tab_idx)
+                    .collect();
+
+                // not touched: opening tabs is no edit to undo, see [Profile::keep_launches]
+                if let Some(prfl) = app.prfls.get_mut(prfl_idx) {
+                    prfl.record_opened(&tab_idxs, at);
+                    prfl.remember_values(&tab_idxs, &values);
+                    app.effects.push(Effect::Save { prfl_idx });
+                }
+            }
+
+            app.status = match prfl_idxs.as_slice() {
+                [prfl_idx] => app.prfls
+                    .get(*prfl_idx)
+                    .map(|prfl| format!(" == Opened {} tab(s) of \"{}\"", tabs.len(), prfl.get_name())),
+                _ => Some(format!(" == Opened {} tab(s) of {} profiles", tabs.len(), prfl_idxs.len())),
+            };
+        },
+        AppEvent::Launched { result: Err(e), .. } => app.status = Some(format!(" == {}", e)),
+    }
+
+    app
+}
+
+
+///
+///U undoes the last edit and CTRL+R redoes it, on every screen but those where they are typed;
+///returns whether `key` was one of them.
+///
+fn update_on_history_key(app: &mut App, key: &KeyEvent) -> bool {
+    let is_typing = app.dialog.is_some()
+        || matches!(app.get_screen(), Screen::Note { .. } | Screen::GlobalSearch { .. })
+        || app.get_screen().get_search().is_some_and(Search::is_typing);
+    if is_typing {
+        return false;
+    }
+
+    let is_redo = key.code == KeyCode::Char('r') && key.modifiers.contains(KeyModifiers::CONTROL);
+    if key.code != KeyCode::Char('u') && !is_redo {
+        return false;
+    }
+
+    let (edit, done) = match is_redo {
+        false => (app.history.undo(), "Undid"),
+        true => (app.history.redo(), "Redid"),
+    };
+    app.status = Some(match edit {
+        Some(edit) => {
+            let status = format!(
+                " == {} the change to {} ({} to undo, {} to redo)",
+                done, edit.describe(), app.history.undo_len(), app.history.redo_len()
+            );
+            app.apply_edit(edit);
+            status
+        },
+        None if is_redo => String::from(" == Nothing to redo"),
+        None => String::from(" == Nothing to undo"),
+    });
+
+    true
+}
+
+fn update_on_key(app: &mut App, key: &KeyEvent) {
+    if app.dialog.is_some() {
+        return update_on_dialog_key(app, key);
+    }
+    // a search goes through every profile, so it opens all groups
+    if let Screen::ProfileList { list, search: None, collapsed } = app.get_screen() {
+        if key.code == KeyCode::Char('/') && !collapsed.is_empty() {
+            let row = profile_tree(&app.prfls, collapsed).get(list.cursor).cloned();
+            set_collapsed(app, Vec::new(), row.as_ref());
+        }
+    }
+    // and a search of a profile all its sections
+    if let Screen::Profile { prfl_idx, list, search: None, collapsed, .. } = app.get_screen() {
+        if key.code == KeyCode::Char('/') && !collapsed.is_empty() {
+            let row = app.prfls[*prfl_idx].rows(collapsed).get(list.cursor).cloned();
+            set_sections_collapsed(app, Vec::new(), row.as_ref());
+        }
+    }
+    if update_on_search_key(app, key) {
+        return app.fit_screens();
+    }
+
+    match app.get_screen().clone() {
+        Screen::ProfileList { list, collapsed, .. } => {
+            let row = profile_tree(&app.prfls, &collapsed).get(list.cursor).cloned();
+
+            match (key.code, row) {
+                (KeyCode::Esc, _) => app.pop(),
+
+                (KeyCode::Enter, Some(TreeRow::Profile { prfl_idx, .. })) => app.push(profile_screen(&app.prfls, prfl_idx, None)),
+
+                // ENTER toggles, LEFT collapses, RIGHT expands
+                (KeyCode::Enter | KeyCode::Left | KeyCode::Right, Some(TreeRow::Group { path, collapsed: is_collapsed, depth })) => {
+                    let collapse = match key.code {
+                        KeyCode::Enter => !is_collapsed,
+                        code => code == KeyCode::Left,
+                    };
+                    let mut collapsed = collapsed;
+                    collapsed.retain(|other| *other != path);
+                    if collapse {
+                        collapsed.push(path.clone());
+                    }
+                    set_collapsed(app, collapsed, Some(&TreeRow::Group { path, depth, collapsed: collapse }));
+                },
+                // from a profile in a group to that group
+                (KeyCode::Left, Some(TreeRow::Profile { prfl_idx, .. })) => {
+                    let group = app.prfls[prfl_idx].get_group().to_string();
+                    if !group.is_empty() {
+                        set_collapsed(app, collapsed, Some(&TreeRow::Group { path: group, depth: 0, collapsed: false }));
+                    }
+                },
+
+                (_, Some(TreeRow::Profile { prfl_idx, .. })) if is_char(key, 'i') => {
+                    let tab_idxs = app.prfls[prfl_idx].active_pairs();
+
+                    match tab_idxs.is_empty() {
+                        true => app.status = Some(String::from(" == This profile has no tabs to open")),
+                        false => open_tabs(app, tabs_of(prfl_idx, &tab_idxs), LaunchMode::Private),
+                    }
+                },
+                (_, Some(TreeRow::Group { path, .. })) if is_char(key, 'i') => {
+                    // in the order they are listed
+                    let tabs: Vec<(usize, usize)> = profile_tree(&app.prfls, &[])
+                        .into_iter()
+                        .filter_map(|row| match row {
+                            TreeRow::Profile { prfl_idx, .. } => Some(prfl_idx),
+                            TreeRow::Group { .. } => None,
+                        })
+                        .filter(|&prfl_idx| group_is_in(app.prfls[prfl_idx].get_group(), &path))
+                        .flat_map(|prfl_idx| app.prfls[prfl_idx].active_pairs().into_iter().map(move |tab_idx| (prfl_idx, tab_idx)))
+                        .collect();
+
+                    match tabs.is_empty() {
+                        true => app.status = Some(String::from(" == The profiles of this group have no tabs to open")),
+                        false => open_tabs(app, tabs, LaunchMode::Private),
+                    }
+                },
+
+                (_, Some(TreeRow::Profile { prfl_idx, .. })) if is_char(key, 'g') => {
+                    app.dialog = Some(Dialog::Text {
+                        label: String::from("Move to group: "),
+                        input: TextInput::new(app.prfls[prfl_idx].get_group()),
+                        action: TextAction::MoveToGroup { prfl_idx },
+                    });
+                },
+                (_, Some(TreeRow::Group { path, .. })) if is_char(key, 'g') => {
+                    app.dialog = Some(Dialog::Text {
+                        label: String::from("Rename group: "),
+                        input: TextInput::new(&path),
+                        action: TextAction::RenameGroup { path },
+                    });
+                },
+
+                (_, Some(TreeRow::Profile { prfl_idx, .. })) if is_char(key, 't') => {
+                    app.dialog = Some(Dialog::Text {
+                        label: String::from("Profile tags: "),
+                        input: TextInput::new(&app.prfls[prfl_idx].get_tags().join(" ")),
+                        action: TextAction::EditProfileTags { prfl_idx },
+                    });
+                },
+                (KeyCode::Char('#'), _) => app.push(Screen::TagList { list: ListState::default() }),
+                (KeyCode::Char('?'), _) => app.push(Screen::Help { what: "main menu", keys: &HELP_MAINMENU, list: ListState::default() }),
+
+                (_, Some(TreeRow::Profile { prfl_idx, .. })) if is_char(key, 'w') => {
+                    write_note(app, NoteTarget::Profile { prfl_idx });
+                },
+
+                (_, Some(TreeRow::Profile { prfl_idx, .. })) if is_char(key, 'm') => {
+                    app.push(Screen::PickProfile { list: ListState::default(), action: PickAction::MergeInto { prfl_idx } });
+                },
+                (KeyCode::Char('c'), Some(TreeRow::Profile { prfl_idx, .. })) => {
+                    app.push(Screen::PickProfile { list: ListState::default(), action: PickAction::DiffWith { prfl_idx } });
+                },
+                (KeyCode::Char('C'), Some(TreeRow::Profile { prfl_idx, .. })) => {
+                    app.dialog = Some(Dialog::Text {
+                        label: String::from("Compare with the file: "),
+                        input: TextInput::default(),
+                        action: TextAction::DiffWithFile { prfl_idx },
+                    });
+                },
+
+                (_, Some(TreeRow::Profile { prfl_idx, .. })) if is_char(key, 'a') => {
+                    app.touch(prfl_idx);
+                    let prfl = &mut app.prfls[prfl_idx];
+                    prfl.set_template(!prfl.is_template());
+                    app.status = Some(match prfl.is_template() {
+                        true => format!(" == \"{}\" is a template now, press P to create a profile from it", prfl.get_name()),
+                        false => format!(" == \"{}\" is no template any more", prfl.get_name()),
+                    });
+                    app.effects.push(Effect::Save { prfl_idx });
+                },
+                (_, Some(TreeRow::Profile { prfl_idx, .. })) if is_char(key, 'p') => {
+                    let prfl = &app.prfls[prfl_idx];
+                    match prfl.is_template() {
+                        true => app.dialog = Some(Dialog::Text {
+                            label: String::from("Name of the new profile: "),
+                            input: TextInput::new(prfl.get_name()),
+                            action: TextAction::NameFromTemplate { prfl_idx },
+                        }),
+                        false => app.status = Some(format!(" == \"{}\" is no template, press A to make it one", prfl.get_name())),
+                    }
+                },
+                (_, Some(TreeRow::Profile { prfl_idx, .. })) if is_char(key, 'y') => {
+                    app.dialog = Some(Dialog::Text {
+                        label: String::from("Name of the copy: "),
+                        input: TextInput::new(&format!("{} (copy)", app.prfls[prfl_idx].get_name())),
+                        action: TextAction::DuplicateProfile { prfl_idx },
+                    });
+                },
+
+                _ if is_char(key, 's') => {
+                    app.push(Screen::Stale { at: chrono::Utc::now().naive_utc(), list: ListState::default() });
+                },
+
+                _ if is_char(key, 'f') => {
+                    app.push(Screen::GlobalSearch { input: TextInput::default(), list: ListState::default() });
+                },
+
+                (KeyCode::Char('r'), _) => sort_profiles_by(app, app.profile_sort.next()),
+                (KeyCode::Char('R'), _) => {
+                    let current = ProfileSortMode::ALL.iter().position(|&mode| mode == app.profile_sort).unwrap_or(0);
+                    app.push(Screen::SortMenu { list: ListState::at(current), target: SortTarget::Profiles });
+                },
+
+                _ => if let Some(mv) = cursor_move_of(key) {
+                    app.move_cursor(mv);
+                },
+            }
+        },
+
+        Screen::Profile { prfl_idx, list, url_elide, search, mut selected, collapsed } => {
+            let row = app.prfls[prfl_idx].rows(&collapsed).get(list.cursor).cloned();
+
+            if let Some(mv) = tab_move_of(key) {
+                match row {
+                    Some(PairRow::Section { name, .. }) => move_section(app, prfl_idx, &name, mv),
+                    // the selection, or else the highlighted tab
+                    Some(PairRow::Pair { tab_idx }) => {
+                        let tab_idxs = match selected.is_empty() {
+                            true => vec![tab_idx],
+                            false => selected,
+                        };
+                        move_tabs(app, prfl_idx, &tab_idxs, mv);
+                    },
+                    None => {},
+                }
+                return;
+            }
+
+            match (key.code, row) {
+                (KeyCode::Esc, _) if !selected.is_empty() => {
+                    app.set_screen(Screen::Profile { prfl_idx, list, url_elide, search, selected: Vec::new(), collapsed });
+                },
+                (KeyCode::Esc, _) => app.pop(),
+
+                (KeyCode::Enter, Some(PairRow::Pair { tab_idx })) => app.push(Screen::BrowserTab { prfl_idx, tab_idx }),
+
+                // ENTER toggles, LEFT collapses, RIGHT expands
+                (KeyCode::Enter | KeyCode::Left | KeyCode::Right, Some(PairRow::Section { name, collapsed: is_collapsed, count })) => {
+                    let collapse = match key.code {
+                        KeyCode::Enter => !is_collapsed,
+                        code => code == KeyCode::Left,
+                    };
+                    let mut collapsed = collapsed;
+                    collapsed.retain(|other| *other != name);
+                    if collapse {
+                        collapsed.push(name.clone());
+                    }
+                    set_sections_collapsed(app, collapsed, Some(&PairRow::Section { name, collapsed: collapse, count }));
+                },
+                // from a tab in a section to that section
+                (KeyCode::Left, Some(PairRow::Pair { tab_idx })) => {
+                    let name = app.prfls[prfl_idx].get_pairs()[tab_idx].get_section().to_string();
+                    if !name.is_empty() {
+                        set_sections_collapsed(app, collapsed, Some(&PairRow::Section { name, collapsed: false, count: 0 }));
+                    }
+                },
+
+                (KeyCode::Char(' '), Some(PairRow::Pair { tab_idx })) => {
+                    match selected.binary_search(&tab_idx) {
+                        Ok(pos) => { selected.remove(pos); },
+                        Err(pos) => selected.insert(pos, tab_idx),
+                    }
+                    app.set_screen(Screen::Profile { prfl_idx, list, url_elide, search, selected, collapsed });
+                    app.move_cursor(CursorMove::Down);
+                },
+                _ if is_char(key, 's') => {
+                    app.dialog = Some(Dialog::Text {
+                        label: String::from("Select: "),
+                        input: TextInput::default(),
+                        action: TextAction::SelectMatching { prfl_idx },
+                    });
+                },
+
+                // the selection, or else the highlighted tab, or all tabs of the highlighted section
+                (_, Some(row)) if is_char(key, 'i') || is_char(key, 'o') => {
+                    let mode = match is_char(key, 'i') {
+                        true => LaunchMode::Private,
+                        false => LaunchMode::Normal,
+                    };
+                    let tab_idxs = match (selected.is_empty(), row) {
+                        (false, _) => selected,
+                        (true, PairRow::Pair { tab_idx }) => vec![tab_idx],
+                        (true, PairRow::Section { name, .. }) => section_tabs(&app.prfls[prfl_idx], &name),
+                    };
+                    open_tabs(app, tabs_of(prfl_idx, &tab_idxs), mode);
+                },
+                (_, Some(PairRow::Pair { tab_idx })) if is_char(key, 'd') => {
+                    let question = match selected.len() {
+                        0 => format!("Delete \"{}\"? (y/n)", app.prfls[prfl_idx].get_pairs()[tab_idx].title),
+                        n => format!("Delete the {} selected tab(s)? (y/n)", n),
+                    };
+                    let action = match selected.is_empty() {
+                        true => ConfirmAction::DeleteTab { prfl_idx, tab_idx },
+                        false => ConfirmAction::DeleteTabs { prfl_idx, tab_idxs: selected },
+                    };
+                    app.dialog = Some(Dialog::Confirm { question, action });
+                },
+
+                _ if is_char(key, 'p') => app.effects.push(Effect::ReadClipboard { prfl_idx }),
+                (KeyCode::Char('?'), _) => app.push(Screen::Help { what: "profile", keys: &HELP_PROFILE, list: ListState::default() }),
+
+                (_, Some(PairRow::Pair { tab_idx })) if is_char(key, 'w') => {
+                    write_note(app, NoteTarget::Tab { prfl_idx, tab_idx });
+                },
+
+                (_, Some(PairRow::Pair { tab_idx })) if is_char(key, 't') => {
+                    app.dialog = Some(match selected.is_empty() {
+                        true => tab_tags_dialog(&app.prfls[prfl_idx], prfl_idx, tab_idx),
+                        false => Dialog::Text {
+                            label: format!("Add tags to {} tab(s): ", selected.len()),
+                            input: TextInput::default(),
+                            action: TextAction::AddTabTags { prfl_idx, tab_idxs: selected },
+                        },
+                    });
+                },
+
+                (_, Some(PairRow::Pair { tab_idx })) if is_char(key, 'q') => {
+                    app.touch(prfl_idx);
+                    let prfl = &mut app.prfls[prfl_idx];
+                    let kind = match prfl.get_pairs()[tab_idx].get_kind() {
+                        TabKind::Page => TabKind::Template,
+                        TabKind::Template => TabKind::Page,
+                    };
+                    app.status = Some(match prfl.set_pair_kind(tab_idx, kind) {
+                        Ok(()) => {
+                            app.effects.push(Effect::Save { prfl_idx });
+                            match kind {
+                                TabKind::Template => String::from(" == The tab is a URL template now, its placeholders are asked for when it is opened"),
+                                TabKind::Page => String::from(" == The tab is an ordinary page again"),
+                            }
+                        },
+                        Err(e) => format!(" == {}", e),
+                    });
+                },
+
+                // the selection, or else the highlighted tab
+                (_, Some(PairRow::Pair { tab_idx })) if is_char(key, 'c') || is_char(key, 'm') => {
+                    let tab_idxs = match selected.is_empty() {
+                        true => vec![tab_idx],
+                        false => selected,
+                    };
+                    let action = match is_char(key, 'c') {
+                        true => PickAction::CopyTabs { prfl_idx, tab_idxs },
+                        false => PickAction::MoveTabs { prfl_idx, tab_idxs },
+                    };
+                    app.push(Screen::PickProfile { list: ListState::default(), action });
+                },
+
+                _ if is_char(key, 'x') && !app.prfls[prfl_idx].get_pairs().is_empty() => {
+                    app.dialog = Some(match selected.is_empty() {
+                        true => Dialog::Text {
+                            label: String::from("Split by (host or tag): "),
+                            input: TextInput::new("host"),
+                            action: TextAction::SplitBy { prfl_idx },
+                        },
+                        false => Dialog::Text {
+                            label: format!("New profile for the {} selected tab(s): ", selected.len()),
+                            input: TextInput::default(),
+                            action: TextAction::SplitSelection { prfl_idx, tab_idxs: selected },
+                        },
+                    });
+                },
+
+                (_, Some(PairRow::Pair { tab_idx })) if is_char(key, 'g') => {
+                    let (label, section, tab_idxs) = match selected.is_empty() {
+                        true => (
+                            String::from("Section: "),
+                            app.prfls[prfl_idx].get_pairs()[tab_idx].get_section().to_string(),
+                            vec![tab_idx],
+                        ),
+                        false => (format!("Section of {} tab(s): ", selected.len()), String::new(), selected),
+                    };
+                    app.dialog = Some(Dialog::Text {
+                        label,
+                        input: TextInput::new(&section),
+                        action: TextAction::MoveToSection { prfl_idx, tab_idxs },
+                    });
+                },
+                (_, Some(PairRow::Section { name, .. })) if is_char(key, 'g') => {
+                    app.dialog = Some(Dialog::Text {
+                        label: String::from("Rename section: "),
+                        input: TextInput::new(&name),
+                        action: TextAction::RenameSection { prfl_idx, name },
+                    });
+                },
+
+                (KeyCode::Char('r'), _) => {
+                    let next = app.prfls[prfl_idx].get_sort_mode().next();
+                    sort_tabs_by(app, prfl_idx, next);
+                },
+                (KeyCode::Char('R'), _) => {
+                    let sort = *app.prfls[prfl_idx].get_sort_mode();
+                    let current = SortMode::ALL.iter().position(|&mode| mode == sort).unwrap_or(0);
+                    app.push(Screen::SortMenu { list: ListState::at(current), target: SortTarget::Tabs { prfl_idx } });
+                },
+
+                _ if is_char(key, 'l') => {
+                    let days = app.prfls[prfl_idx].get_expire_after().map(|days| days.to_string()).unwrap_or_default();
+                    app.dialog = Some(expire_dialog(&days, prfl_idx));
+                },
+                // the selection, or else the highlighted tab, or the whole archive
+                (_, Some(row)) if is_char(key, 'b') => {
+                    let prfl = &app.prfls[prfl_idx];
+                    let tab_idxs = match (selected.is_empty(), row) {
+                        (false, _) => selected,
+                        (true, PairRow::Pair { tab_idx }) => vec![tab_idx],
+                        (true, PairRow::Section { name, .. }) if is_reserved_section(&name) => {
+                            (0..prfl.get_pairs().len()).filter(|&idx| prfl.get_pairs()[idx].is_archived()).collect()
+                        },
+                        (true, PairRow::Section { .. }) => Vec::new(),
+                    };
+                    unarchive_tabs(app, prfl_idx, &tab_idxs);
+                },
+
+                (KeyCode::Char('U'), _) => {
+                    let url_elide = match url_elide {
+                        Elide::End => Elide::Middle,
+                        Elide::Middle => Elide::End,
+                    };
+                    app.set_screen(Screen::Profile { prfl_idx, list, url_elide, search, selected, collapsed });
+                },
+
+                _ => if let Some(mv) = cursor_move_of(key) {
+                    app.move_cursor(mv);
+                },
+            }
+        },
+
+        Screen::BrowserTab { prfl_idx, tab_idx } => {
+            let Some(pair) = app.prfls.get(prfl_idx).and_then(|prfl| prfl.get_pairs().get(tab_idx)) else {
+                return app.pop();
+            };
+
+            match key.code {
+                KeyCode::Esc => app.pop(),
+
+                _ if is_char(key, 'i') => open_tabs(app, vec![(prfl_idx, tab_idx)], LaunchMode::Private),
+                _ if is_char(key, 'o') => open_tabs(app, vec![(prfl_idx, tab_idx)], LaunchMode::Normal),
+
+                _ if is_char(key, 't') => app.dialog = Some(tab_tags_dialog(&app.prfls[prfl_idx], prfl_idx, tab_idx)),
+                _ if is_char(key, 'w') => write_note(app, NoteTarget::Tab { prfl_idx, tab_idx }),
+
+                _ if is_char(key, 'e') => {
+                    app.dialog = Some(Dialog::Text {
+                        label: String::from("Title: "),
+                        input: TextInput::new(&pair.title),
+                        action: TextAction::EditTitle { prfl_idx, tab_idx },
+                    });
+                },
+                _ if is_char(key, 'd') => {
+                    app.dialog = Some(Dialog::Confirm {
+                        question: format!("Delete \"{}\"? (y/n)", pair.title),
+                        action: ConfirmAction::DeleteTab { prfl_idx, tab_idx },
+                    });
+                },
+
+                _ if is_char(key, 'c') => {
+                    app.push(Screen::PickProfile {
+                        list: ListState::default(),
+                        action: PickAction::CopyTabs { prfl_idx, tab_idxs: vec![tab_idx] },
+                    });
+                },
+                _ if is_char(key, 'm') => {
+                    app.push(Screen::PickProfile {
+                        list: ListState::default(),
+                        action: PickAction::MoveTabs { prfl_idx, tab_idxs: vec![tab_idx] },
+                    });
+                },
+
+                _ => {},
+            }
+        },
+
+        Screen::PickProfile { list, action } => {
+            match key.code {
+                KeyCode::Esc => app.pop(),
+
+                // listed like in the main menu
+                KeyCode::Enter => if let Some(&prfl_idx) = profile_order(&app.prfls).get(list.cursor) {
+                    pick_profile(app, prfl_idx, action);
+                },
+
+                _ => if let Some(mv) = cursor_move_of(key) {
+                    app.move_cursor(mv);
+                },
+            }
+        },
+
+        Screen::SortMenu { list, target } => {
+            match key.code {
+                KeyCode::Esc => app.pop(),
+
+                KeyCode::Enter => {
+                    app.pop();
+                    match target {
+                        SortTarget::Profiles => if let Some(&mode) = ProfileSortMode::ALL.get(list.cursor) {
+                            sort_profiles_by(app, mode);
+                        },
+                        SortTarget::Tabs { prfl_idx } => if let Some(&mode) = SortMode::ALL.get(list.cursor) {
+                            sort_tabs_by(app, prfl_idx, mode);
+                        },
+                    }
+                },
+
+                _ => if let Some(mv) = cursor_move_of(key) {
+                    app.move_cursor(mv);
+                },
+            }
+        },
+
+        Screen::TagList { list } => {
+            match key.code {
+                KeyCode::Esc => app.pop(),
+
+                KeyCode::Enter => if let Some((tag, _)) = tag_counts(&app.prfls).get(list.cursor) {
+                    app.push(Screen::TagView { tag: tag.clone(), list: ListState::default() });
+                },
+
+                _ => if let Some(mv) = cursor_move_of(key) {
+                    app.move_cursor(mv);
+                },
+            }
+        },
+
+        Screen::TagView { tag, list } => {
+            let hits = tag_hits(&app.prfls, &tag);
+
+            match key.code {
+                KeyCode::Esc => app.pop(),
+
+                KeyCode::Enter => if let Some(hit) = hits.get(list.cursor) {
+                    app.push(profile_screen(&app.prfls, hit.prfl_idx, Some(hit.tab_idx)));
+                },
+
+                // all of them, together
+                _ if (is_char(key, 'i') || is_char(key, 'o')) && !hits.is_empty() => {
+                    let mode = match is_char(key, 'i') {
+                        true => LaunchMode::Private,
+                        false => LaunchMode::Normal,
+                    };
+                    let tabs = hits.iter().map(|hit| (hit.prfl_idx, hit.tab_idx)).collect();
+                    open_tabs(app, tabs, mode);
+                },
+
+                _ => if let Some(mv) = cursor_move_of(key) {
+                    app.move_cursor(mv);
+                },
+            }
+        },
+
+        Screen::Note { target, mut editor } => {
+            match key.code {
+                KeyCode::Esc => {
+                    app.pop();
+                    save_note(app, target, editor.get_text());
+                },
+                KeyCode::Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    app.pop();
+                    app.status = Some(String::from(" == The note was left as it was"));
+                },
+
+                _ => {
+                    editor.handle_key(key);
+                    app.set_screen(Screen::Note { target, editor });
+                },
+            }
+        },
+
+        Screen::Stale { at, list } => {
+            let tabs = stale_tabs(&app.prfls, at);
+
+            match key.code {
+                KeyCode::Esc => app.pop(),
+
+                KeyCode::Enter => if let Some(&(prfl_idx, tab_idx)) = tabs.get(list.cursor) {
+                    app.push(profile_screen(&app.prfls, prfl_idx, Some(tab_idx)));
+                },
+
+                _ if is_char(key, 'a') && !tabs.is_empty() => {
+                    app.dialog = Some(Dialog::Confirm {
+                        question: format!("Move these {} tab(s) to the archive of their profiles? (y/n)", tabs.len()),
+                        action: ConfirmAction::ArchiveStale { at },
+                    });
+                },
+
+                _ => if let Some(mv) = cursor_move_of(key) {
+                    app.move_cursor(mv);
+                },
+            }
+        },
+
+        Screen::Diff { prfl_idx, with, list, only_changes } => {
+            let rows = diff_rows(&app.prfls, prfl_idx, &with, only_changes);
+
+            match key.code {
+                KeyCode::Esc => app.pop(),
+
+                KeyCode::Enter => match rows.get(list.cursor) {
+                    Some(&row) if row.kind != DiffKind::Same => take_diff(app, Some(row)),
+                    _ => {},
+                },
+
+                _ if is_char(key, 'a') => {
+                    let count = diff_count(&rows);
+                    match count.is_empty() {
+                        true => app.status = Some(String::from(" == Nothing to take, the tabs are the same")),
+                        false => app.dialog = Some(Dialog::Confirm {
+                            question: format!("Take {} into \"{}\"? (y/n)", diff_summary(count), app.prfls[prfl_idx].get_name()),
+                            action: ConfirmAction::TakeAllChanges,
+                        }),
+                    }
+                },
+
+                _ if is_char(key, 'c') => {
+                    app.set_screen(Screen::Diff { prfl_idx, with, list, only_changes: !only_changes });
+                    app.fit_screens();
+                },
+
+                _ => if let Some(mv) = cursor_move_of(key) {
+                    app.move_cursor(mv);
+                },
+            }
+        },
+
+        Screen::Help { .. } => {
+            match key.code {
+                KeyCode::Esc => app.pop(),
+
+                _ => if let Some(mv) = cursor_move_of(key) {
+                    app.move_cursor(mv);
+                },
+            }
+        },
+
+        Screen::GlobalSearch { mut input, list } => {
+            match key.code {
+                KeyCode::Esc => app.pop(),
+
+                KeyCode::Enter => {
+                    if let Some(hit) = global_hits(&app.prfls, &input).get(list.cursor) {
+                        app.push(profile_screen(&app.prfls, hit.prfl_idx, Some(hit.tab_idx)));
+                    }
+                },
+
+                KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown => {
+                    if let Some(mv) = cursor_move_of(key) {
+                        app.move_cursor(mv);
+                    }
+                },
+
+                _ => {
+                    input.handle_key(key);
+                    app.set_screen(Screen::GlobalSearch { input, list: ListState::at(0) });
+                    app.fit_screens();
+                },
+            }
+        },
+    }
+}
+
+
+/// The tabs at `tab_idxs` of the profile at `prfl_idx`, as [Effect::Launch] takes them
+fn tabs_of(prfl_idx: usize, tab_idxs: &[usize]) -> Vec<(usize, usize)> {
+    tab_idxs.iter().map(|&tab_idx| (prfl_idx, tab_idx)).collect()
+}
+
+/// Opens the `tabs`, after asking for the values of the placeholders of the template tabs among them
+fn open_tabs(app: &mut App, tabs: Vec<(usize, usize)>, mode: LaunchMode) {
+    ask_values(app, tabs, mode, Vec::new())
+}
+
+///
+///Asks for the value of the next placeholder of the template tabs among `tabs` without one in `values`,
+///starting with the newest value it was opened with, or else opens them.
+///
+fn ask_values(app: &mut App, tabs: Vec<(usize, usize)>, mode: LaunchMode, values: Vec<(String, String)>) {
+    let pairs: Vec<&URLTitlePair> = tabs
+        .iter()
+        .filter_map(|&(prfl_idx, tab_idx)| app.prfls.get(prfl_idx)?.get_pairs().get(tab_idx))
+        .collect();
+
+    let next = pairs
+        .iter()
+        .flat_map(|pair| pair.url_placeholders())
+        .find(|placeholder| !values.iter().any(|(other, _)| other == placeholder));
+    let Some(placeholder) = next else {
+        app.effects.push(Effect::Launch { tabs, values, mode });
+        return;
+    };
+
+    let mut recent: Vec<String> = Vec::new();
+    for value in pairs.iter().flat_map(|pair| pair.get_recent_values(&placeholder)) {
+        if !recent.contains(value) {
+            recent.push(value.clone());
+        }
+    }
+
+    app.dialog = Some(Dialog::Text {
+        label: match recent.is_empty() {
+            true => format!("Open with {{{}}} = ", placeholder),
+            false => format!("Open with {{{}}} (UP / DOWN for recent ones) = ", placeholder),
+        },
+        input: TextInput::new(recent.first().map(String::as_str).unwrap_or_default()),
+        action: TextAction::LaunchValue { tabs, mode, placeholder, recent, values },
+    });
+}
+
+///
+///Asks for the value of the next placeholder of the template at `prfl_idx` without one in `values`,
+///or else creates the profile called `name` from it.
+///
+fn fill_template(app: &mut App, prfl_idx: usize, name: &str, values: Vec<(String, String)>) {
+    let Some(template) = app.prfls.get(prfl_idx) else {
+        return;
+    };
+
+    let next = template
+        .template_placeholders()
+        .into_iter()
+        .find(|placeholder| !values.iter().any(|(other, _)| other == placeholder));
+    match next {
+        Some(placeholder) => app.dialog = Some(Dialog::Text {
+            label: format!("Value of {{{}}}: ", placeholder),
+            input: TextInput::default(),
+            action: TextAction::TemplateValue { prfl_idx, name: name.to_string(), placeholder, values },
+        }),
+        None => {
+            let new_prfl = template.from_template(name, &values);
+            let status = format!(
+                " == Created \"{}\" from the template \"{}\" with {} tab(s)",
+                new_prfl.get_name(), template.get_name(), new_prfl.get_pairs().len()
+            );
+            if add_profile(app, new_prfl) {
+                app.status = Some(status);
+            }
+        },
+    }
+}
+
+///
+///Adds and saves `new_prfl` unless there is a profile of its name already, highlighting it
+///in the list of profiles; returns whether it did.
+///
+fn add_profile(app: &mut App, new_prfl: Profile) -> bool {
+    if name_taken(app, new_prfl.get_name()) {
+        return false;
+    }
+
+    let prfl_idx = app.insert_profile(new_prfl);
+    keep_tree_row(app, Some(&TreeRow::Profile { prfl_idx, depth: 0 }));
+
+    true
+}
+
+/// Whether a profile is called `name` already, ignoring case, which the status then says
+fn name_taken(app: &mut App, name: &str) -> bool {
+    let taken = app.prfls.iter().any(|prfl| prfl.get_name().to_lowercase() == name.to_lowercase());
+    if taken {
+        app.status = Some(format!(" == There is a profile called \"{}\" already", name));
+    }
+
+    taken
+}
+
+/// Moves the archived ones of the tabs at `tab_idxs` of the profile at `prfl_idx` back to their sections
+fn unarchive_tabs(app: &mut App, prfl_idx: usize, tab_idxs: &[usize]) {
+    app.touch(prfl_idx);
+    let Some(prfl) = app.prfls.get_mut(prfl_idx) else {
+        return;
+    };
+
+    let before = prfl.clone();
+    let count = prfl.unarchive_pairs(tab_idxs);
+    app.status = Some(match count {
+        0 => String::from(" == No archived tab to bring back, archived tabs are in the Archive section"),
+        count => format!(" == Brought {} tab(s) back from the archive", count),
+    });
+    if count > 0 {
+        app.effects.push(Effect::Save { prfl_idx });
+        app.reordered_tabs(prfl_idx, &before);
+    }
+}
+
+/// Asks after how many days the tabs of the profile at `prfl_idx` go stale, starting with `text`
+fn expire_dialog(text: &str, prfl_idx: usize) -> Dialog {
+    Dialog::Text {
+        label: String::from("Stale after how many days unopened (empty for never): "),
+        input: TextInput::new(text),
+        action: TextAction::ExpireAfter { prfl_idx },
+    }
+}
+
+/// Opens the editor for the note on `target`, starting with the note it has
+fn write_note(app: &mut App, target: NoteTarget) {
+    if let Some(note) = target.note_in(&app.prfls) {
+        app.push(Screen::Note { target, editor: TextArea::new(&note) });
+    }
+}
+
+/// Gives `target` the `note` written in [Screen::Note], and saves it if it changed
+fn save_note(app: &mut App, target: NoteTarget, note: String) {
+    if target.note_in(&app.prfls).as_ref() == Some(&note) {
+        return;
+    }
+
+    let (NoteTarget::Profile { prfl_idx } | NoteTarget::Tab { prfl_idx, .. }) = target;
+    app.touch(prfl_idx);
+    let Some(prfl) = app.prfls.get_mut(prfl_idx) else {
+        return;
+    };
+
+    app.status = Some(String::from(match note.is_empty() {
+        true => " == Removed the note",
+        false => " == Saved the note",
+    }));
+    match target {
+        NoteTarget::Profile { .. } => prfl.set_note(note),
+        NoteTarget::Tab { tab_idx, .. } => prfl.set_pair_note(tab_idx, note),
+    }
+    app.effects.push(Effect::Save { prfl_idx });
+}
+
+/// Asks for the tags of the tab at `tab_idx` of `prfl` (at `prfl_idx`), starting with the ones it has
+fn tab_tags_dialog(prfl: &Profile, prfl_idx: usize, tab_idx: usize) -> Dialog {
+    let tags = prfl.get_pairs().get(tab_idx).map(|pair| pair.get_tags().join(" ")).unwrap_or_default();
+
+    Dialog::Text {
+        label: String::from("Tags: "),
+        input: TextInput::new(&tags),
+        action: TextAction::EditTabTags { prfl_idx, tab_idx },
+    }
+}
+
+/// Sorts the list of profiles (only ever shown at the bottom of the stack), keeping the cursor on its profile
+fn sort_profiles_by(app: &mut App, mode: ProfileSortMode) {
+    let highlighted = highlighted_tree_row(app);
+
+    app.profile_sort = mode;
+    sort_profiles(&mut app.prfls, mode);
+    app.effects.push(Effect::SaveSettings { settings: Settings { profile_sort: mode } });
+    app.status = Some(format!(" == Sorted the profiles by {}", mode.label()));
+
+    keep_highlighted_tree_row(app, highlighted);
+}
+
+/// The row highlighted in the list of profiles, with the id of its profile (or else the group), see [keep_highlighted_tree_row]
+fn highlighted_tree_row(app: &App) -> Option<Result<usize, TreeRow>> {
+    tree_row(app).map(|row| match row {
+        TreeRow::Profile { prfl_idx, .. } => Ok(app.prfls[prfl_idx].get_id()),
+        group => Err(group),
+    })
+}
+
+/// Keeps the cursor of the list of profiles on the `highlighted` row after the profiles moved
+fn keep_highlighted_tree_row(app: &mut App, highlighted: Option<Result<usize, TreeRow>>) {
+    // the profiles moved, the groups did not
+    let row = match highlighted {
+        Some(Ok(id)) => position_of(&app.prfls, id).map(|prfl_idx| TreeRow::Profile { prfl_idx, depth: 0 }),
+        Some(Err(group)) => Some(group),
+        None => None,
+    };
+    keep_tree_row(app, row.as_ref());
+}
+
+/// The row highlighted in the list of profiles (only ever shown at the bottom of the stack)
+fn tree_row(app: &App) -> Option<TreeRow> {
+    match app.screens.first() {
+        Some(Screen::ProfileList { list, collapsed, .. }) => profile_tree(&app.prfls, collapsed).get(list.cursor).cloned(),
+        _ => None,
+    }
+}
+
+///
+///Puts the cursor of the list of profiles on the row of the same group or profile as `row`
+///(compared by path / index only), after the tree changed; it stays where it is if there is none.
+///
+fn keep_tree_row(app: &mut App, row: Option<&TreeRow>) {
+    let Some(Screen::ProfileList { list, collapsed, .. }) = app.screens.first_mut() else {
+        return;
+    };
+
+    let found = row.and_then(|row| {
+        profile_tree(&app.prfls, collapsed).iter().position(|other| match (row, other) {
+            (TreeRow::Group { path, .. }, TreeRow::Group { path: other, .. }) => path == other,
+            (TreeRow::Profile { prfl_idx, .. }, TreeRow::Profile { prfl_idx: other, .. }) => prfl_idx == other,
+            _ => false,
+        })
+    });
+    if let Some(idx) = found {
+        list.cursor = idx;
+    }
+    app.fit_screens();
+}
+
+/// Collapses exactly the groups at the paths in `collapsed`, keeping the cursor on `row`
+fn set_collapsed(app: &mut App, collapsed: Vec<String>, row: Option<&TreeRow>) {
+    if let Some(Screen::ProfileList { collapsed: current, .. }) = app.screens.first_mut() {
+        *current = collapsed;
+    }
+    keep_tree_row(app, row);
+}
+
+/// Expands `group` and the groups it is in, so that what is in it can be seen
+fn reveal_group(app: &mut App, group: &str) {
+    if let Some(Screen::ProfileList { collapsed, .. }) = app.screens.first_mut() {
+        collapsed.retain(|path| !group_is_in(group, path));
+    }
+}
+
+/// The screen with the tabs of the profile at `prfl_idx`, the cursor on the one at `tab_idx` (or on the first row)
+fn profile_screen(prfls: &[Profile], prfl_idx: usize, tab_idx: Option<usize>) -> Screen {
+    let cursor = tab_idx
+        .and_then(|tab_idx| {
+            let rows = prfls.get(prfl_idx)?.rows(&[]);
+            pair_row_position(&rows, &PairRow::Pair { tab_idx })
+        })
+        .unwrap_or(0);
+
+    Screen::Profile {
+        prfl_idx,
+        list: ListState::at(cursor),
+        url_elide: Elide::default(),
+        search: None,
+        selected: Vec::new(),
+        collapsed: Vec::new(),
+    }
+}
+
+/// Where `row` is in `rows`, comparing sections by name and tabs by index only
+fn pair_row_position(rows: &[PairRow], row: &PairRow) -> Option<usize> {
+    rows.iter().position(|other| match (row, other) {
+        (PairRow::Section { name, .. }, PairRow::Section { name: other, .. }) => name == other,
+        (PairRow::Pair { tab_idx }, PairRow::Pair { tab_idx: other }) => tab_idx == other,
+        _ => false,
+    })
+}
+
+/// The indices of the tabs of `prfl` in the section `name`
+fn section_tabs(prfl: &Profile, name: &str) -> Vec<usize> {
+    prfl.get_pairs()
+        .iter()
+        .enumerate()
+        .filter(|(_, pair)| pair.get_section() == name)
+        .map(|(tab_idx, _)| tab_idx)
+        .collect()
+}
+
+///
+///Collapses exactly the sections named in `collapsed` in the profile shown,
+///keeping the cursor on `row` (compared as in [pair_row_position]) if it is still there.
+///
+fn set_sections_collapsed(app: &mut App, collapsed: Vec<String>, row: Option<&PairRow>) {
+    let Screen::Profile { prfl_idx, list, collapsed: current, .. } = app.screens.last_mut().expect("there is always a screen") else {
+        return;
+    };
+    *current = collapsed;
+
+    let found = match (app.prfls.get(*prfl_idx), row) {
+        (Some(prfl), Some(row)) => pair_row_position(&prfl.rows(current), row),
+        _ => None,
+    };
+    if let Some(idx) = found {
+        list.cursor = idx;
+    }
+    app.fit_screens();
+}
+
+/// Moves the section `name` of the profile at `prfl_idx` and saves the new order
+fn move_section(app: &mut App, prfl_idx: usize, name: &str, mv: TabMove) {
+    app.touch(prfl_idx);
+    let Some(prfl) = app.prfls.get_mut(prfl_idx) else {
+        return;
+    };
+
+    let before = prfl.clone();
+    prfl.move_section(name, mv);
+    app.status = Some(format!(" == Moved the section \"{}\"", name));
+    app.effects.push(Effect::Save { prfl_idx });
+
+    app.reordered_tabs(prfl_idx, &before);
+}
+
+/// Moves the tabs at `tab_idxs` of the profile at `prfl_idx` and saves the new (manual) order
+fn move_tabs(app: &mut App, prfl_idx: usize, tab_idxs: &[usize], mv: TabMove) {
+    app.touch(prfl_idx);
+    let Some(prfl) = app.prfls.get_mut(prfl_idx) else {
+        return;
+    };
+
+    let was_manual = *prfl.get_sort_mode() == SortMode::Manual;
+    let before = prfl.clone();
+    prfl.move_pairs(tab_idxs, mv);
+
+    let whereto = match mv {
+        TabMove::Up => "up",
+        TabMove::Down => "down",
+        TabMove::Top => "to the top",
+        TabMove::Bottom => "to the bottom",
+    };
+    app.status = Some(match was_manual {
+        true => format!(" == Moved {} tab(s) {}", tab_idxs.len(), whereto),
+        false => format!(" == Moved {} tab(s) {}, the tabs are in manual order now", tab_idxs.len(), whereto),
+    });
+    app.effects.push(Effect::Save { prfl_idx });
+
+    app.reordered_tabs(prfl_idx, &before);
+}
+
+/// Sorts the tabs of the profile at `prfl_idx` and saves the order, keeping cursors and selections on their tabs
+fn sort_tabs_by(app: &mut App, prfl_idx: usize, mode: SortMode) {
+    app.touch(prfl_idx);
+    let Some(prfl) = app.prfls.get_mut(prfl_idx) else {
+        return;
+    };
+
+    let before = prfl.clone();
+    prfl.change_sort_mode(mode);
+    app.status = Some(format!(" == Sorted the tabs {}", mode.label()));
+    app.effects.push(Effect::Save { prfl_idx });
+
+    app.reordered_tabs(prfl_idx, &before);
+}
+
+
+///
+///Handles `key` if it starts, edits, ends or jumps through the search of the list shown,
+///and returns whether it did.
+///
+fn update_on_search_key(app: &mut App, key: &KeyEvent) -> bool {
+    let hits = search_hits(&app.prfls, app.get_screen()).unwrap_or_default();
+    // n / N go through the hits in the order they are listed, not by score
+    let mut in_order = hits.clone();
+    in_order.sort_unstable();
+
+    let Some((list, search)) = app.top_mut().list_and_search_mut() else {
+        return false;
+    };
+
+    let Some(current) = search else {
+        if key.code != KeyCode::Char('/') {
+            return false;
+        }
+        *search = Some(Search::new(list.cursor));
+        list.cursor = 0;
+        app.status = None;
+        return true;
+    };
+
+    if current.typing {
+        match key.code {
+            KeyCode::Esc => {
+                list.cursor = current.cursor_before;
+                *search = None;
+            },
+            KeyCode::Enter if current.is_empty() => {
+                list.cursor = current.cursor_before;
+                *search = None;
+            },
+            KeyCode::Enter => {
+                list.cursor = hits.get(list.cursor).copied().unwrap_or(current.cursor_before);
+                current.typing = false;
+            },
+
+            // moving through the hits while typing; the cursor is on a hit, so the tabs are not moved (SHIFT)
+            KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown if !key.modifiers.contains(KeyModifiers::SHIFT) => {
+                return false;
+            },
+            KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown => {},
+
+            _ => {
+                current.input.handle_key(key);
+                list.cursor = 0;
+            },
+        }
+
+        return true;
+    }
+
+    match key.code {
+        KeyCode::Char('n') => {
+            let next = in_order.iter().find(|&&idx| idx > list.cursor).or(in_order.first());
+            if let Some(&idx) = next {
+                list.cursor = idx;
+            }
+        },
+        KeyCode::Char('N') => {
+            let prev = in_order.iter().rev().find(|&&idx| idx < list.cursor).or(in_order.last());
+            if let Some(&idx) = prev {
+                list.cursor = idx;
+            }
+        },
+        KeyCode::Char('/') => {
+            current.cursor_before = list.cursor;
+            current.typing = true;
+            list.cursor = hits.iter().position(|&idx| idx == list.cursor).unwrap_or(0);
+        },
+        KeyCode::Esc => *search = None,
+
+        _ => return false,
+    }
+
+    true
+}
+
+
+fn update_on_dialog_key(app: &mut App, key: &KeyEvent) {
+    let Some(dialog) = app.dialog.take() else {
+        return;
+    };
+
+    match dialog {
+        Dialog::Text { label, mut input, action } => match key.code {
+            KeyCode::Esc => {},
+            KeyCode::Enter => accept_text(app, input.get_text(), action),
+            // from the newest recent value to older ones and back, then to nothing
+            KeyCode::Up | KeyCode::Down if matches!(action, TextAction::LaunchValue { .. }) => {
+                if let TextAction::LaunchValue { recent, .. } = &action {
+                    let pos = recent.iter().position(|value| value == input.get_text());
+                    let next = match (key.code, pos) {
+                        (KeyCode::Up, None) => recent.first(),
+                        (KeyCode::Up, Some(pos)) => recent.get(pos + 1).or(recent.get(pos)),
+                        (_, Some(pos)) if pos > 0 => recent.get(pos - 1),
+                        _ => None,
+                    };
+                    input = TextInput::new(next.map(String::as_str).unwrap_or_default());
+                }
+                app.dialog = Some(Dialog::Text { label, input, action });
+            },
+            _ => {
+                input.handle_key(key);
+                app.dialog = Some(Dialog::Text { label, input, action });
+            },
+        },
+
+        Dialog::Confirm { question, action } => match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => confirm(app, action),
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {},
+            _ => app.dialog = Some(Dialog::Confirm { question, action }),
+        },
+    }
+}
+
+
+/// `text` was typed for `action`
+fn accept_text(app: &mut App, text: &str, action: TextAction) {
+    match action {
+        TextAction::EditTitle { prfl_idx, tab_idx } => {
+            let Some(pair) = app.prfls.get(prfl_idx).and_then(|prfl| prfl.get_pairs().get(tab_idx)) else {
+                return;
+            };
+
+            app.dialog = Some(Dialog::Text {
+                label: String::from("URL: "),
+                input: TextInput::new(&pair.url),
+                action: TextAction::EditURL { prfl_idx, tab_idx, title: text.trim().to_string() },
+            });
+        },
+
+        TextAction::EditURL { prfl_idx, tab_idx, title } => {
+            app.touch(prfl_idx);
+            let Some(prfl) = app.prfls.get_mut(prfl_idx) else {
+                return;
+            };
+
+            match validate_url(text).and_then(|url| prfl.edit_pair(tab_idx, &url, &title)) {
+                Ok(()) => {
+                    app.status = Some(format!(" == Changed \"{}\"", title));
+                    app.effects.push(Effect::Save { prfl_idx });
+                },
+                Err(e) => {
+                    // let the user fix what they typed
+                    app.status = Some(format!(" == {}", e));
+                    app.dialog = Some(Dialog::Text {
+                        label: String::from("URL: "),
+                        input: TextInput::new(text),
+                        action: TextAction::EditURL { prfl_idx, tab_idx, title },
+                    });
+                },
+            }
+        },
+
+        TextAction::SelectMatching { prfl_idx } => {
+            let query = match Query::parse(text) {
+                Ok(query) => query,
+                Err(e) => {
+                    app.status = Some(format!(" == {}", e));
+                    app.dialog = Some(Dialog::Text {
+                        label: String::from("Select: "),
+                        input: TextInput::new(text),
+                        action: TextAction::SelectMatching { prfl_idx },
+                    });
+                    return;
+                },
+            };
+
+            let Some(prfl) = app.prfls.get(prfl_idx) else {
+                return;
+            };
+            let matching: Vec<usize> = prfl
+                .get_pairs()
+                .iter()
+                .enumerate()
+                .filter(|(_, pair)| query.matches(prfl, pair))
+                .map(|(idx, _)| idx)
+                .collect();
+
+            if let Screen::Profile { selected, .. } = app.top_mut() {
+                selected.extend(&matching);
+                selected.sort_unstable();
+                selected.dedup();
+
+                let count = selected.len();
+                app.status = Some(format!(" == {} tab(s) match, {} selected", matching.len(), count));
+            }
+        },
+
+        TextAction::EditTabTags { prfl_idx, tab_idx } => {
+            app.touch(prfl_idx);
+            let Some(prfl) = app.prfls.get_mut(prfl_idx) else {
+                return;
+            };
+
+            let tags = parse_tags(text);
+            app.status = Some(format!(" == The tab has {} tag(s) now", tags.len()));
+            prfl.set_pair_tags(tab_idx, tags);
+            app.effects.push(Effect::Save { prfl_idx });
+        },
+
+        TextAction::AddTabTags { prfl_idx, tab_idxs } => {
+            app.touch(prfl_idx);
+            let Some(prfl) = app.prfls.get_mut(prfl_idx) else {
+                return;
+            };
+
+            let tags = parse_tags(text);
+            if tags.is_empty() {
+                return;
+            }
+            prfl.add_pair_tags(&tab_idxs, &tags);
+            app.status = Some(format!(" == Tagged {} tab(s) with {}", tab_idxs.len(), format_tags(&tags)));
+            app.effects.push(Effect::Save { prfl_idx });
+        },
+
+        TextAction::MoveToGroup { prfl_idx } => {
+            app.touch(prfl_idx);
+            let Some(prfl) = app.prfls.get_mut(prfl_idx) else {
+                return;
+            };
+
+            prfl.set_group(text);
+            app.status = Some(match prfl.get_group() {
+                "" => format!(" == \"{}\" is in no group now", prfl.get_name()),
+                group => format!(" == Moved \"{}\" to {}/", prfl.get_name(), group),
+            });
+            app.effects.push(Effect::Save { prfl_idx });
+
+            let group = prfl.get_group().to_string();
+            reveal_group(app, &group);
+            keep_tree_row(app, Some(&TreeRow::Profile { prfl_idx, depth: 0 }));
+        },
+
+        TextAction::RenameGroup { path } => {
+            let new_path = normalize_group(text);
+            if new_path == path {
+                return;
+            }
+
+            let mut moved = 0;
+            for prfl_idx in 0..app.prfls.len() {
+                if !group_is_in(app.prfls[prfl_idx].get_group(), &path) {
+                    continue;
+                }
+                app.touch(prfl_idx);
+                let prfl = &mut app.prfls[prfl_idx];
+                // what is left of the group path below the renamed group, e.g. `/rust`
+                let inner = prfl.get_group()[path.len()..].to_string();
+                prfl.set_group(&format!("{}{}", new_path, inner));
+                app.effects.push(Effect::Save { prfl_idx });
+                moved += 1;
+            }
+
+            app.status = Some(match new_path.is_empty() {
+                true => format!(" == Took {} profile(s) out of {}/", moved, path),
+                false => format!(" == Moved {} profile(s) from {}/ to {}/", moved, path, new_path),
+            });
+            reveal_group(app, &new_path);
+            keep_tree_row(app, Some(&TreeRow::Group { path: new_path, depth: 0, collapsed: false }));
+        },
+
+        TextAction::MoveToSection { prfl_idx, tab_idxs } => {
+            if is_reserved_section(text) {
+                app.status = Some(format!(" == \"{}\" is only for archived tabs, pick another section", ARCHIVE_SECTION));
+                return;
+            }
+            app.touch(prfl_idx);
+            let Some(prfl) = app.prfls.get_mut(prfl_idx) else {
+                return;
+            };
+
+            let before = prfl.clone();
+            prfl.set_pair_section(&tab_idxs, text);
+            let section = text.trim().to_string();
+            app.status = Some(match section.is_empty() {
+                true => format!(" == {} tab(s) are in no section now", tab_idxs.len()),
+                false => format!(" == Put {} tab(s) in the section \"{}\"", tab_idxs.len(), section),
+            });
+            app.effects.push(Effect::Save { prfl_idx });
+
+            // so that the cursor can follow the tab
+            if let Screen::Profile { collapsed, .. } = app.top_mut() {
+                collapsed.retain(|name| *name != section);
+            }
+            app.reordered_tabs(prfl_idx, &before);
+        },
+
+        TextAction::RenameSection { prfl_idx, name } => {
+            if is_reserved_section(&name) {
+                app.status = Some(String::from(" == The archive cannot be renamed, press B to bring its tabs back"));
+                return;
+            }
+            if is_reserved_section(text) {
+                app.status = Some(format!(" == \"{}\" is only for archived tabs, pick another section", ARCHIVE_SECTION));
+                return;
+            }
+            app.touch(prfl_idx);
+            let Some(prfl) = app.prfls.get_mut(prfl_idx) else {
+                return;
+            };
+
+            let new_name = text.trim().to_string();
+            prfl.rename_section(&name, &new_name);
+            app.status = Some(match new_name.is_empty() {
+                true => format!(" == The tabs of \"{}\" are in no section now", name),
+                false => format!(" == Renamed the section \"{}\" to \"{}\"", name, new_name),
+            });
+            app.effects.push(Effect::Save { prfl_idx });
+
+            let row = PairRow::Section { name: new_name, collapsed: false, count: 0 };
+            let collapsed = match app.get_screen() {
+                Screen::Profile { collapsed, .. } => collapsed.iter().filter(|other| **other != name).cloned().collect(),
+                _ => Vec::new(),
+            };
+            set_sections_collapsed(app, collapsed, Some(&row));
+        },
+
+        TextAction::SplitSelection { prfl_idx, tab_idxs } => {
+            let name = text.trim();
+            if name.is_empty() {
+                return;
+            }
+
+            match split_profile(app, prfl_idx, &tab_idxs, name) {
+                Some(_) => app.status = Some(format!(" == Moved {} tab(s) to the new profile \"{}\"", tab_idxs.len(), name)),
+                None => app.dialog = Some(Dialog::Text {
+                    label: format!("New profile for the {} selected tab(s): ", tab_idxs.len()),
+                    input: TextInput::new(text),
+                    action: TextAction::SplitSelection { prfl_idx, tab_idxs },
+                }),
+            }
+        },
+
+        TextAction::SplitBy { prfl_idx } => {
+            let Some(by) = SplitBy::from_name(text) else {
+                app.status = Some(format!(" == Cannot split by \"{}\", only by host or tag", text.trim()));
+                app.dialog = Some(Dialog::Text {
+                    label: String::from("Split by (host or tag): "),
+                    input: TextInput::new(text),
+                    action: TextAction::SplitBy { prfl_idx },
+                });
+                return;
+            };
+            let Some(prfl) = app.prfls.get(prfl_idx) else {
+                return;
+            };
+
+            let name = prfl.get_name().clone();
+            // every split moves the tabs behind it, so they are found again by URL
+            let plan: Vec<(String, Vec<String>)> = prfl
+                .split_plan(by)
+                .into_iter()
+                .map(|(key, tab_idxs)| (key, tab_idxs.iter().map(|&idx| prfl.get_pairs()[idx].url.clone()).collect()))
+                .collect();
+            let moved: usize = plan.iter().map(|(_, urls)| urls.len()).sum();
+            // all or nothing, a taken name stops the split before any tab moves
+            if plan.iter().any(|(key, _)| name_taken(app, &format!("{} - {}", name, key))) {
+                return;
+            }
+
+            let mut prfl_idx = prfl_idx;
+            for (key, urls) in &plan {
+                let tab_idxs: Vec<usize> = urls.iter().filter_map(|url| app.prfls[prfl_idx].find_url(url)).collect();
+                prfl_idx = split_profile(app, prfl_idx, &tab_idxs, &format!("{} - {}", name, key)).unwrap_or(prfl_idx);
+            }
+
+            app.status = Some(match plan.len() {
+                0 => format!(" == No tab of \"{}\" has a {}", name, text.trim().to_lowercase()),
+                n => format!(" == Moved {} tab(s) of \"{}\" to {} new profile(s)", moved, name, n),
+            });
+        },
+
+        TextAction::ExpireAfter { prfl_idx } => {
+            let days = match text.trim() {
+                "" | "0" | "never" => None,
+                days => match days.parse::<u32>() {
+                    Ok(days) => Some(days),
+                    Err(_) => {
+                        app.status = Some(format!(" == \"{}\" is not a number of days", days));
+                        app.dialog = Some(expire_dialog(text, prfl_idx));
+                        return;
+                    },
+                },
+            };
+            app.touch(prfl_idx);
+            let Some(prfl) = app.prfls.get_mut(prfl_idx) else {
+                return;
+            };
+
+            prfl.set_expire_after(days);
+            app.status = Some(match days {
+                Some(days) => format!(" == Tabs of \"{}\" unopened for {} day(s) are stale now", prfl.get_name(), days),
+                None => format!(" == Tabs of \"{}\" never go stale now", prfl.get_name()),
+            });
+            app.effects.push(Effect::Save { prfl_idx });
+        },
+
+        TextAction::NameFromTemplate { prfl_idx } => {
+            let name = text.trim();
+            if !name.is_empty() {
+                fill_template(app, prfl_idx, name, Vec::new());
+            }
+        },
+        TextAction::TemplateValue { prfl_idx, name, placeholder, mut values } => {
+            values.push((placeholder, text.to_string()));
+            fill_template(app, prfl_idx, &name, values);
+        },
+
+        TextAction::LaunchValue { tabs, mode, placeholder, mut values, .. } => {
+            values.push((placeholder, text.to_string()));
+            ask_values(app, tabs, mode, values);
+        },
+
+        TextAction::DuplicateProfile { prfl_idx } => {
+            let name = text.trim();
+            let Some(prfl) = app.prfls.get(prfl_idx) else {
+                return;
+            };
+            if name.is_empty() {
+                return;
+            }
+
+            let new_prfl = prfl.duplicate(name);
+            let status = format!(" == Copied \"{}\" to \"{}\"", prfl.get_name(), name);
+            if add_profile(app, new_prfl) {
+                app.status = Some(status);
+            }
+        },
+
+        TextAction::DiffWithFile { prfl_idx } => {
+            let path = text.trim();
+            if !path.is_empty() {
+                app.effects.push(Effect::ReadToCompare { prfl_idx, path: path.to_string() });
+            }
+        },
+
+        TextAction::EditProfileTags { prfl_idx } => {
+            app.touch(prfl_idx);
+            let Some(prfl) = app.prfls.get_mut(prfl_idx) else {
+                return;
+            };
+
+            let tags = parse_tags(text);
+            app.status = Some(format!(" == \"{}\" has {} tag(s) now", prfl.get_name(), tags.len()));
+            prfl.set_tags(tags);
+            app.effects.push(Effect::Save { prfl_idx });
+        },
+    }
+}
+
+
+/// `action` was answered with yes
+fn confirm(app: &mut App, action: ConfirmAction) {
+    match action {
+        ConfirmAction::DeleteTab { prfl_idx, tab_idx } => {
+            app.touch(prfl_idx);
+            let Some(pair) = app.prfls.get_mut(prfl_idx).and_then(|prfl| prfl.remove_pair(tab_idx)) else {
+                return;
+            };
+
+            app.status = Some(format!(" == Deleted \"{}\"", pair.title));
+            app.effects.push(Effect::Save { prfl_idx });
+            app.forget_tab(prfl_idx, tab_idx);
+        },
+
+        ConfirmAction::DeleteTabs { prfl_idx, tab_idxs } => {
+            // from the back, so that the indices still to delete stay valid
+            app.touch(prfl_idx);
+            let deleted = tab_idxs
+                .iter()
+                .rev()
+                .filter(|&&tab_idx| {
+                    let removed = app.prfls.get_mut(prfl_idx).and_then(|prfl| prfl.remove_pair(tab_idx)).is_some();
+                    if removed {
+                        app.forget_tab(prfl_idx, tab_idx);
+                    }
+                    removed
+                })
+                .count();
+
+            app.status = Some(format!(" == Deleted {} tab(s)", deleted));
+            app.effects.push(Effect::Save { prfl_idx });
+        },
+
+        ConfirmAction::TakeAllChanges => take_diff(app, None),
+
+        ConfirmAction::ArchiveStale { at } => {
+            let mut archived = 0;
+            let mut profiles = 0;
+            for prfl_idx in 0..app.prfls.len() {
+                let tab_idxs = app.prfls[prfl_idx].stale_pairs(at);
+                if tab_idxs.is_empty() {
+                    continue;
+                }
+
+                app.touch(prfl_idx);
+                app.prfls[prfl_idx].archive_pairs(&tab_idxs);
+                app.effects.push(Effect::Save { prfl_idx });
+                archived += tab_idxs.len();
+                profiles += 1;
+            }
+
+            app.status = Some(format!(" == Archived {} tab(s) of {} profile(s)", archived, profiles));
+            app.fit_screens();
+        },
+    }
+}
+
+
+/// The profile at `target` was chosen for `action`
+fn pick_profile(app: &mut App, target: usize, action: PickAction) {
+    let (
+        PickAction::CopyTabs { prfl_idx, .. }
+        | PickAction::MoveTabs { prfl_idx, .. }
+        | PickAction::MergeInto { prfl_idx }
+        | PickAction::DiffWith { prfl_idx }
+    ) = action;
+
+    if target == prfl_idx {
+        app.status = Some(String::from(match action {
+            PickAction::MergeInto { .. } => " == A profile cannot be merged into itself, pick another one",
+            PickAction::DiffWith { .. } => " == A profile has the same tabs as itself, pick another one",
+            _ => " == The tabs are already in this profile, pick another one",
+        }));
+        return;
+    }
+    let Some(prfl) = app.prfls.get(prfl_idx) else {
+        return app.pop();
+    };
+    let target_name = app.prfls[target].get_name().clone();
+
+    match action {
+        PickAction::CopyTabs { tab_idxs, .. } => {
+            // the copies keep t_created, the launch history, tags and notes
+            let pairs = tab_idxs.iter().filter_map(|&idx| prfl.get_pairs().get(idx)).cloned().collect();
+            app.touch(target);
+            let count = app.prfls[target].merge_pairs(pairs);
+            app.effects.push(Effect::Save { prfl_idx: target });
+
+            app.status = Some(format!(" == Copied {} to \"{}\"", merge_summary(count), target_name));
+            app.pop();
+        },
+        PickAction::MoveTabs { tab_idxs, .. } => {
+            app.touch(prfl_idx);
+            app.touch(target);
+            let pairs = app.prfls[prfl_idx].remove_pairs(&tab_idxs);
+            let count = app.prfls[target].merge_pairs(pairs);
+            app.effects.push(Effect::Save { prfl_idx: target });
+            app.effects.push(Effect::Save { prfl_idx });
+
+            app.status = Some(format!(" == Moved {} to \"{}\"", merge_summary(count), target_name));
+            // from the back, so that the indices still to forget stay valid
+            for &tab_idx in tab_idxs.iter().rev() {
+                app.forget_tab(prfl_idx, tab_idx);
+            }
+        },
+        PickAction::MergeInto { .. } => {
+            let Some(prfl) = app.remove_profile(prfl_idx) else {
+                return;
+            };
+            let name = prfl.get_name().clone();
+            let target = match target > prfl_idx {
+                true => target - 1,
+                false => target,
+            };
+
+            let file_name = prfl.get_file_name().map(str::to_string);
+            app.touch(target);
+            let count = app.prfls[target].merge_profile(prfl);
+            // only once the tabs are safe in the other profile
+            app.effects.push(Effect::Save { prfl_idx: target });
+            if let Some(file_name) = file_name {
+                app.effects.push(Effect::Delete { file_name });
+            }
+
+            app.status = Some(format!(" == Merged \"{}\" into \"{}\": {}", name, target_name, merge_summary(count)));
+            keep_tree_row(app, Some(&TreeRow::Profile { prfl_idx: target, depth: 0 }));
+        },
+        PickAction::DiffWith { .. } => {
+            app.pop();
+            let with = DiffWith::Profile { prfl_idx: target };
+            app.push(Screen::Diff { prfl_idx, with, list: ListState::default(), only_changes: false });
+        },
+    }
+}
+
+///
+///Makes the profile on the left of [Screen::Diff] like the one on the right:
+///as far as `row` goes, or as far as all rows go if there is none.
+///
+fn take_diff(app: &mut App, row: Option<DiffRow>) {
+    let Screen::Diff { prfl_idx, with, .. } = app.get_screen().clone() else {
+        return;
+    };
+    let Some(other) = with.profile(&app.prfls).cloned() else {
+        return;
+    };
+
+    let rows = match row {
+        Some(row) => vec![row],
+        None => diff_rows(&app.prfls, prfl_idx, &with, true),
+    };
+    app.touch(prfl_idx);
+    let count = take_changes(&mut app.prfls[prfl_idx], &other, &rows);
+    if !count.is_empty() {
+        app.effects.push(Effect::Save { prfl_idx });
+    }
+
+    app.status = Some(format!(" == Took {} into \"{}\"", diff_summary(count), app.prfls[prfl_idx].get_name()));
+    app.fit_screens();
+}
+
+/// `2 added, 1 removed, 0 retitled` for the status line
+fn diff_summary(count: DiffCount) -> String {
+    format!("{} added, {} removed, {} retitled", count.added, count.removed, count.retitled)
+}
+
+/// `3 tab(s), 1 of them merged into the same page there` for the status line
+fn merge_summary(count: MergeCount) -> String {
+    match count.merged {
+        0 => format!("{} tab(s)", count.added),
+        merged => format!("{} tab(s), {} of them merged into the same page there", count.added + merged, merged),
+    }
+}
+
+///
+///Moves the tabs at `tab_idxs` of the profile at `prfl_idx` into a new profile called `name`
+///(see [Profile::split_off]), which is added where the order puts it and saved.
+///Returns where the profile at `prfl_idx` is afterwards, or `None` if nothing was split
+///because a profile is called `name` already.
+///
+fn split_profile(app: &mut App, prfl_idx: usize, tab_idxs: &[usize], name: &str) -> Option<usize> {
+    if name_taken(app, name) {
+        return None;
+    }
+
+    app.touch(prfl_idx);
+    let prfl = app.prfls.get_mut(prfl_idx)?;
+
+    let id = prfl.get_id();
+    let new_prfl = prfl.split_off(tab_idxs, name);
+    app.insert_profile(new_prfl);
+    let prfl_idx = position_of(&app.prfls, id).unwrap_or_default();
+    app.effects.push(Effect::Save { prfl_idx });
+
+    let mut tab_idxs = tab_idxs.to_vec();
+    tab_idxs.sort_unstable();
+    // from the back, so that the indices still to forget stay valid
+    for &tab_idx in tab_idxs.iter().rev() {
+        app.forget_tab(prfl_idx, tab_idx);
+    }
+
+    Some(prfl_idx)
+}
+
+
+fn update_on_paste(app: &mut App, prfl_idx: usize, text: Result<String, Errors>) {
+    app.touch(prfl_idx);
+    let Some(prfl) = app.prfls.get_mut(prfl_idx) else {
+        return;
+    };
+
+    let text = match text {
+        Ok(text) => text,
+        Err(e) => {
+            app.status = Some(format!(" == {}", e));
+            return;
+        },
+    };
+
+    let (pairs, invalid) = parse_url_lines(&text);
+    let total = pairs.len();
+    // the sort mode decides where they go
+    let before = prfl.clone();
+    let added = pairs
+        .into_iter()
+        .filter(|pair| prfl.add_pair(pair.clone()).is_ok())
+        .count();
+
+    app.status = Some(format!(
+        " == Pasted {} URL(s), skipped {} duplicate(s) and {} invalid line(s)",
+        added, total - added, invalid.len()
+    ));
+
+    if added > 0 {
+        app.effects.push(Effect::Save { prfl_idx });
+        app.reordered_tabs(prfl_idx, &before);
+    }
+}
+
+
+
+
+/// Draws the screen on top of the stack, and the status line
+pub fn view(app: &App, out: &mut impl Backend) -> Result<(), Errors> {
+    let screen = app.get_screen();
+    let shown = shown_rows(&app.prfls, screen);
+    let needle = screen
+        .get_search()
+        .and_then(|search| search.parse().ok())
+        .map(|query| query.free_text());
+    let filter = RowFilter {
+        shown: shown.as_deref(),
+        needle: needle.as_deref(),
+        selected: match screen {
+            Screen::Profile { selected, .. } => selected,
+            _ => &[],
+        },
+    };
+
+    match *screen {
+        Screen::ProfileList { list, ref collapsed, .. } => {
+            render_beginning(out, app.profile_sort)?;
+
+            if app.prfls.is_empty() {
+                render_line(
+                    out,
+                    " == You currently do not have any profiles, or the reading failed for some reason.",
+                    Some(COLOR_FG_DECLARE)
+                )?;
+            } else {
+                let tree = profile_tree(&app.prfls, collapsed);
+                render_profile_tree(out, &app.prfls, &tree, STATIC_INFO_MAINMENU_LEN, 0, list.cursor, &list.viewport, &filter)?;
+            }
+        },
+
+        Screen::Profile { prfl_idx, ref list, url_elide, ref collapsed, .. } => {
+            let prfl = &app.prfls[prfl_idx];
+            let highlight_idx = match prfl.get_pairs().is_empty() {
+                true => None,
+                false => Some(list.cursor),
+            };
+
+            render_profile_beginning(out, prfl)?;
+            let rows = prfl.rows(collapsed);
+            render_one_profile(out, prfl, &rows, STATIC_INFO_PROFILE_LEN, 0, highlight_idx, &list.viewport, url_elide, &filter)?;
+        },
+
+        Screen::BrowserTab { prfl_idx, tab_idx } => {
+            let prfl = &app.prfls[prfl_idx];
+            let pair = &prfl.get_pairs()[tab_idx];
+
+            let url = normalize_url(&pair.url);
+            let also_in: Vec<&str> = app.prfls
+                .iter()
+                .enumerate()
+                .filter(|&(idx, other)| idx != prfl_idx && other.find_url(&url).is_some())
+                .map(|(_, other)| other.get_name().as_str())
+                .collect();
+
+            render_browser_tab(out, prfl, pair, &also_in)?;
+        },
+
+        Screen::PickProfile { list, ref action } => {
+            let prfl_idx = match *action {
+                PickAction::CopyTabs { prfl_idx, .. }
+                | PickAction::MoveTabs { prfl_idx, .. }
+                | PickAction::MergeInto { prfl_idx }
+                | PickAction::DiffWith { prfl_idx } => prfl_idx,
+            };
+            let prfl = &app.prfls[prfl_idx];
+            let tabs = |tab_idxs: &[usize]| match tab_idxs {
+                [tab_idx] => format!("\"{}\"", prfl.get_pairs()[*tab_idx].title),
+                _ => format!("the {} selected tabs", tab_idxs.len()),
+            };
+
+            let question = match action {
+                PickAction::CopyTabs { tab_idxs, .. } => format!("Copy {} to which profile?", tabs(tab_idxs)),
+                PickAction::MoveTabs { tab_idxs, .. } => format!("Move {} to which profile?", tabs(tab_idxs)),
+                PickAction::MergeInto { .. } => format!("Merge \"{}\" into which profile?", prfl.get_name()),
+                PickAction::DiffWith { .. } => format!("Compare \"{}\" with which profile?", prfl.get_name()),
+            };
+            render_pick_beginning(out, &question)?;
+            render_list_of_profiles(out, &app.prfls, STATIC_INFO_PICK_LEN, 0, list.cursor, &list.viewport, &RowFilter::default())?;
+        },
+
+        Screen::SortMenu { list, target } => {
+            let (question, labels, current) = match target {
+                SortTarget::Profiles => (
+                    String::from("Sort the profiles by"),
+                    ProfileSortMode::ALL.map(|mode| mode.label()).to_vec(),
+                    ProfileSortMode::ALL.iter().position(|&mode| mode == app.profile_sort),
+                ),
+                SortTarget::Tabs { prfl_idx } => {
+                    let prfl = &app.prfls[prfl_idx];
+                    (
+                        format!("Sort the tabs of \"{}\"", prfl.get_name()),
+                        SortMode::ALL.map(|mode| mode.label()).to_vec(),
+                        SortMode::ALL.iter().position(|mode| mode == prfl.get_sort_mode()),
+                    )
+                },
+            };
+
+            render_sort_menu(out, &question, &labels, current.unwrap_or(0), list.cursor, &list.viewport)?;
+        },
+
+        Screen::Stale { at, list } => {
+            let tabs: Vec<_> = stale_tabs(&app.prfls, at)
+                .into_iter()
+                .map(|(prfl_idx, tab_idx)| {
+                    let prfl = &app.prfls[prfl_idx];
+                    (prfl, &prfl.get_pairs()[tab_idx])
+                })
+                .collect();
+
+            render_stale_tabs(out, &tabs, at, list.cursor, &list.viewport)?;
+        },
+
+        Screen::Diff { prfl_idx, ref with, list, only_changes } => {
+            let (Some(prfl), Some(other)) = (app.prfls.get(prfl_idx), with.profile(&app.prfls)) else {
+                return Ok(());
+            };
+            let rows = diff_rows(&app.prfls, prfl_idx, with, only_changes);
+
+            render_diff(out, prfl, other, &with.label(&app.prfls), &rows, only_changes, list.cursor, &list.viewport)?;
+        },
+
+        Screen::TagList { list } => render_tag_list(out, &tag_counts(&app.prfls), list.cursor, &list.viewport)?,
+
+        Screen::Help { what, keys, list } => render_help(out, what, keys, list.cursor, &list.viewport)?,
+
+        Screen::TagView { ref tag, list } => {
+            let hits: Vec<_> = tag_hits(&app.prfls, tag)
+                .into_iter()
+                .map(|hit| {
+                    let prfl = &app.prfls[hit.prfl_idx];
+                    (prfl, &prfl.get_pairs()[hit.tab_idx])
+                })
+                .collect();
+
+            render_tag_view_beginning(out, tag, hits.len())?;
+            render_search_results(out, &hits, STATIC_INFO_TAG_VIEW_LEN, 0, list.cursor, &list.viewport, None)?;
+        },
+
+        Screen::Note { target, ref editor } => {
+            let what = match target {
+                NoteTarget::Profile { prfl_idx } => format!("the profile \"{}\"", app.prfls[prfl_idx].get_name()),
+                NoteTarget::Tab { prfl_idx, tab_idx } => format!("\"{}\"", app.prfls[prfl_idx].get_pairs()[tab_idx].title),
+            };
+
+            render_note_editor(out, &what, editor)?;
+        },
+
+        Screen::GlobalSearch { ref input, list } => {
+            let hits: Vec<_> = global_hits(&app.prfls, input)
+                .into_iter()
+                .map(|hit| {
+                    let prfl = &app.prfls[hit.prfl_idx];
+                    (prfl, &prfl.get_pairs()[hit.tab_idx])
+                })
+                .collect();
+
+            render_search_beginning(out, hits.len())?;
+            let needle = Query::parse(input.get_text()).map(|query| query.free_text()).unwrap_or_default();
+            render_search_results(out, &hits, STATIC_INFO_SEARCH_LEN, 0, list.cursor, &list.viewport, Some(&needle))?;
+        },
+    }
+
+    let search = screen.get_search();
+    match (app.get_dialog(), screen) {
+        (Some(Dialog::Text { label, input, action }), _) => {
+            render_prompt(out, label, input.get_text(), Some(input.get_cursor()))?;
+            if let (TextAction::SelectMatching { .. }, Err(e)) = (action, Query::parse(input.get_text())) {
+                render_prompt_error(out, &e.to_string())?;
+            }
+        },
+        (Some(Dialog::Confirm { question, .. }), _) => render_prompt(out, question, "", None)?,
+
+        (None, Screen::GlobalSearch { input, .. }) => {
+            render_prompt(out, "Search: ", input.get_text(), Some(input.get_cursor()))?;
+            if let Err(e) = Query::parse(input.get_text()) {
+                render_prompt_error(out, &e.to_string())?;
+            }
+        },
+        (None, _) => match (search, app.get_status()) {
+            (Some(search), _) if search.is_typing() => {
+                render_prompt(out, "/", search.get_query(), Some(search.get_input().get_cursor()))?;
+                if let Err(e) = search.parse() {
+                    render_prompt_error(out, &e.to_string())?;
+                }
+            },
+            (_, Some(msg)) => render_status_line(out, msg)?,
+            (Some(search), None) => {
+                let hits = search_hits(&app.prfls, screen).unwrap_or_default();
+                render_status_line(out, &format!(
+                    " /{}: {} hit(s), n / N for the next / previous one, ESC to clear",
+                    search.get_query(), hits.len()
+                ))?;
+            },
+            (None, None) => if let Screen::Profile { selected, .. } = screen {
+                if !selected.is_empty() {
+                    render_status_line(out, &format!(
+                        " {} tab(s) selected: I / O to open, D to delete, T to tag, G to section, C / M to copy / move, X to split them off, ESC to clear",
+                        selected.len()
+                    ))?;
+                }
+            },
+        },
+    }
+
+    Ok(())
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyEventState, KeyModifiers};
+
+    use crate::backend::ScreenBuffer;
+    use crate::URLTitlePair;
+
+    use super::*;
+
+    const SIZE: (u16, u16) = (100, 40);
+
+    fn profile(name: &str, tabs: usize) -> Profile {
+        let pairs = (0..tabs)
+            .map(|n| URLTitlePair::new(&format!("https://{}.com/{:02}", name.to_lowercase(), n), &format!("Tab {:02}", n)))
+            .collect();
+
+        Profile::builder()
+            .add_name(name)
+            .add_many_title_url_pairs(pairs)
+            .build()
+    }
+
+    fn app(prfls: Vec<Profile>) -> App {
+        App::new(prfls, SIZE)
+    }
+
+    fn key_with(code: KeyCode, modifiers: KeyModifiers) -> AppEvent {
+        AppEvent::Terminal(Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }))
+    }
+
+    fn press(mut app: App, codes: &[KeyCode]) -> App {
+        for &code in codes {
+            app = update(app, key_with(code, KeyModifiers::NONE));
+        }
+        app
+    }
+
+    fn type_text(app: App, text: &str) -> App {
+        press(app, &text.chars().map(KeyCode::Char).collect::<Vec<_>>())
+    }
+
+    fn list(app: &App) -> ListState {
+        app.get_screen().clone().list_mut().cloned().expect("a list is shown")
+    }
+
+    fn titles(app: &App, prfl_idx: usize) -> Vec<&str> {
+        app.get_profiles()[prfl_idx].get_pairs().iter().map(|pair| pair.title.as_str()).collect()
+    }
+
+    fn render(app: &App) -> ScreenBuffer {
+        let mut screen = ScreenBuffer::new(SIZE.0, SIZE.1);
+        view(app, &mut screen).unwrap();
+        screen
+    }
+
+    #[test]
+    fn renders_the_main_menu() {
+        let screen = render(&app(vec![profile("Alpha", 2), profile("Beta", 12)]));
+
+        assert_eq!(screen.row(0), "=== Firefox Tab Resumer ===");
+        let first = screen.find(">> Alpha ").expect("the profiles are listed");
+        assert!(screen.row(first).contains("|     2 |"));
+        assert!(screen.row(first + 1).starts_with(">> Beta "));
+        assert!(screen.row(first + 1).contains("|    12 |"));
+        assert_eq!(screen.row(SIZE.1 - 2), "-- 1 of 2 --");
+        assert_eq!(screen.row(SIZE.1 - 1), "");
+    }
+
+    #[test]
+    fn small_terminals_keep_room_for_the_list() {
+        let app = App::new(vec![profile("Alpha", 30)], (80, 24));
+        assert!(list(&app).get_viewport().get_height() >= 10);
+
+        let app = press(app, &[KeyCode::Enter]);
+        assert!(list(&app).get_viewport().get_height() >= 15);
+
+        // the rest of the keys are behind ?
+        let app = press(app, &[KeyCode::Char('?'), KeyCode::End]);
+        assert!(matches!(app.get_screen(), Screen::Help { keys, .. } if keys == &HELP_PROFILE));
+        let mut screen = ScreenBuffer::new(80, 24);
+        view(&app, &mut screen).unwrap();
+        assert_eq!(screen.row(0), "=== Keys: profile ===");
+        assert!(screen.row(screen.find(">> Press ESC to go back to the list").unwrap()).len() <= 80);
+
+        let app = press(app, &[KeyCode::Esc]);
+        assert!(matches!(app.get_screen(), Screen::Profile { .. }));
+    }
+
+    #[test]
+    fn renders_a_profile_longer_than_the_screen() {
+        let app = press(app(vec![profile("Alpha", 100)]), &[KeyCode::Enter]);
+        let height = list(&app).get_viewport().get_height() as u16;
+        let hidden = 100 - height;
+
+        let screen = render(&app);
+        assert_eq!(screen.row(0), "=== Profile: Alpha (100 tabs, oldest first) ===");
+        let first = screen.find(">> Tab 00 ").expect("the first tabs are shown");
+        assert!(screen.row(first).ends_with("| https://alpha.com/00"));
+        assert!(screen.row(first + height - 1).starts_with(&format!(">> Tab {:02} ", height - 1)));
+        assert!(screen.find(&format!("Tab {:02}", height)).is_none());
+        assert_eq!(screen.row(SIZE.1 - 2), format!("-- 1 of 100 | v {} more below --", hidden));
+
+        let screen = render(&press(app, &[KeyCode::End]));
+        assert_eq!(screen.find(&format!(">> Tab {:02} ", hidden)), Some(first));
+        assert!(screen.row(first + height - 1).starts_with(">> Tab 99 "));
+        assert!(screen.find("Tab 00").is_none());
+        assert_eq!(screen.row(SIZE.1 - 2), format!("-- ^ {} more above | 100 of 100 --", hidden));
+    }
+
+    #[test]
+    fn cursor_wraps_around() {
+        let app = app(vec![profile("Alpha", 1), profile("Beta", 1), profile("Gamma", 1)]);
+
+        let app = press(app, &[KeyCode::Up]);
+        assert_eq!(list(&app).get_cursor(), 2);
+        let app = press(app, &[KeyCode::Down]);
+        assert_eq!(list(&app).get_cursor(), 0);
+    }
+
+    #[test]
+    fn pages_move_by_the_viewport() {
+        let app = press(app(vec![profile("Alpha", 100)]), &[KeyCode::Enter]);
+        let height = list(&app).get_viewport().get_height();
+        assert!(height > 0 && height < 100);
+
+        let app = press(app, &[KeyCode::PageDown, KeyCode::PageDown]);
+        assert_eq!(list(&app).get_cursor(), 2 * height);
+        assert!(list(&app).get_viewport().get_offset() <= 2 * height);
+        assert!(list(&app).get_viewport().get_offset() + height > 2 * height);
+
+        let app = press(app, &[KeyCode::PageUp]);
+        assert_eq!(list(&app).get_cursor(), height);
+        let app = press(app, &[KeyCode::End, KeyCode::PageDown]);
+        assert_eq!(list(&app).get_cursor(), 99);
+        let app = press(app, &[KeyCode::Home, KeyCode::PageUp]);
+        assert_eq!(list(&app).get_cursor(), 0);
+    }
+
+    #[test]
+    fn screens_are_pushed_and_popped() {
+        let app = press(app(vec![profile("Alpha", 2), profile("Beta", 2)]), &[KeyCode::Down, KeyCode::Enter]);
+        assert!(matches!(app.get_screen(), Screen::Profile { prfl_idx: 1, .. }));
+
+        let app = press(app, &[KeyCode::Enter]);
+        assert!(matches!(app.get_screen(), Screen::BrowserTab { prfl_idx: 1, tab_idx: 0 }));
+        assert_eq!(app.get_screens().len(), 3);
+
+        let app = press(app, &[KeyCode::Esc, KeyCode::Esc]);
+        assert_eq!(app.get_screens().len(), 1);
+        assert_eq!(list(&app).get_cursor(), 1);
+        assert!(app.is_running());
+
+        let app = press(app, &[KeyCode::Esc]);
+        assert!(!app.is_running());
+    }
+
+    #[test]
+    fn resizing_refits_the_lists() {
+        let app = press(app(vec![profile("Alpha", 100)]), &[KeyCode::Enter, KeyCode::End]);
+        let tall = list(&app).get_viewport().get_height();
+
+        let app = update(app, AppEvent::Terminal(Event::Resize(80, 30)));
+        assert_eq!(app.get_size(), (80, 30));
+        assert_eq!(list(&app).get_viewport().get_height(), tall - 10);
+        assert_eq!(list(&app).get_cursor(), 99);
+        assert_eq!(list(&app).get_viewport().get_offset(), 100 - (tall - 10));
+    }
+
+    #[test]
+    fn pasting_goes_through_effects() {
+        let mut app = press(app(vec![profile("Alpha", 1)]), &[KeyCode::Enter, KeyCode::Char('p')]);
+        assert_eq!(app.take_effects(), vec![Effect::ReadClipboard { prfl_idx: 0 }]);
+        assert!(app.take_effects().is_empty());
+
+        let text = "https://alpha.com/00\nhttps://new.com\tNew\nnot a url\n";
+        let mut app = update(app, AppEvent::Pasted { prfl_idx: 0, text: Ok(text.to_string()) });
+        assert_eq!(app.get_status(), Some(" == Pasted 1 URL(s), skipped 1 duplicate(s) and 1 invalid line(s)"));
+        assert_eq!(app.get_profiles()[0].get_pairs().len(), 2);
+        assert_eq!(app.take_effects(), vec![Effect::Save { prfl_idx: 0 }]);
+
+        let app = update(app, AppEvent::Saved { prfl_idx: 0, result: Err(Errors::usage("disk full")) });
+        assert!(app.get_status().unwrap().starts_with(" == Saving \"Alpha\" failed: "));
+    }
+
+    #[test]
+    fn tabs_are_moved_and_deleted_from_their_detail_screen() {
+        let app = press(app(vec![profile("Alpha", 2), profile("Beta", 1)]), &[KeyCode::Enter, KeyCode::Enter]);
+        let app = press(app, &[KeyCode::Char('m'), KeyCode::Enter]);
+        assert_eq!(app.get_status(), Some(" == The tabs are already in this profile, pick another one"));
+
+        let mut app = press(app, &[KeyCode::Down, KeyCode::Enter]);
+        assert_eq!(app.get_status(), Some(" == Moved 1 tab(s) to \"Beta\""));
+        assert_eq!(app.take_effects(), vec![Effect::Save { prfl_idx: 1 }, Effect::Save { prfl_idx: 0 }]);
+        assert_eq!(app.get_profiles()[0].get_pairs().len(), 1);
+        assert_eq!(app.get_profiles()[1].get_pairs()[1].title, "Tab 00");
+        // the screens of the moved tab are gone
+        assert!(matches!(app.get_screen(), Screen::Profile { prfl_idx: 0, .. }));
+
+        let app = press(app, &[KeyCode::Enter, KeyCode::Char('d')]);
+        let screen = render(&app);
+        assert_eq!(screen.row(SIZE.1 - 1), "Delete \"Tab 01\"? (y/n)");
+        assert!(screen.find("Tab 01").unwrap() < SIZE.1 - 1);
+
+        let app = press(app, &[KeyCode::Char('y')]);
+        assert_eq!(app.get_status(), Some(" == Deleted \"Tab 01\""));
+        assert!(app.get_profiles()[0].get_pairs().is_empty());
+    }
+
+    #[test]
+    fn a_search_jumps_between_its_hits() {
+        let app = press(app(vec![profile("Alpha", 12)]), &[KeyCode::Enter, KeyCode::Down, KeyCode::Down]);
+        let app = type_text(press(app, &[KeyCode::Char('/')]), "tab 0");
+        assert!(app.get_screen().get_search().unwrap().is_typing());
+        assert_eq!(app.get_screen().get_search().unwrap().get_query(), "tab 0");
+
+        // "Tab 00" to "Tab 09" match equally well, "Tab 10" not as well
+        let app = press(app, &[KeyCode::Enter]);
+        assert!(!app.get_screen().get_search().unwrap().is_typing());
+        assert_eq!(list(&app).get_cursor(), 0);
+
+        let app = press(app, &[KeyCode::Char('n')]);
+        assert_eq!(list(&app).get_cursor(), 1);
+        let app = press(app, &[KeyCode::Char('N'), KeyCode::Char('N')]);
+        assert_eq!(list(&app).get_cursor(), 10);
+        let app = press(app, &[KeyCode::Char('n')]);
+        assert_eq!(list(&app).get_cursor(), 0);
+        let app = press(app, &[KeyCode::Char('N')]);
+        assert_eq!(list(&app).get_cursor(), 10);
+
+        // the cursor stays where the search left it
+        let app = press(app, &[KeyCode::Esc]);
+        assert!(app.get_screen().get_search().is_none());
+        assert_eq!(list(&app).get_cursor(), 10);
+
+        // giving up while typing goes back to where the search started
+        let app = type_text(press(app, &[KeyCode::Char('/')]), "tab 0");
+        let app = press(app, &[KeyCode::Esc]);
+        assert!(app.get_screen().get_search().is_none());
+        assert_eq!(list(&app).get_cursor(), 10);
+    }
+
+    #[test]
+    fn the_global_search_opens_the_profile_of_a_hit() {
+        let app = press(app(vec![profile("Alpha", 8), profile("Beta", 8)]), &[KeyCode::Char('f')]);
+        let app = type_text(app, "beta.com/05");
+        assert_eq!(row_count(app.get_profiles(), app.get_screen()), 1);
+
+        let app = press(app, &[KeyCode::Enter]);
+        assert!(matches!(app.get_screen(), Screen::Profile { prfl_idx: 1, .. }));
+        assert_eq!(list(&app).get_cursor(), 5);
+
+        let app = press(app, &[KeyCode::Esc]);
+        assert!(matches!(app.get_screen(), Screen::GlobalSearch { .. }));
+    }
+
+    #[test]
+    fn sorting_keeps_the_cursor_on_its_row() {
+        let app = press(app(vec![profile("Beta", 1), profile("Alpha", 3), profile("Gamma", 2)]), &[KeyCode::Down]);
+        let mut app = press(app, &[KeyCode::Char('r')]);
+        assert_eq!(app.get_profile_sort(), ProfileSortMode::ByTabCount);
+        assert_eq!(app.get_profiles()[list(&app).get_cursor()].get_name(), "Alpha");
+        assert_eq!(
+            app.take_effects(),
+            vec![Effect::SaveSettings { settings: Settings { profile_sort: ProfileSortMode::ByTabCount } }]
+        );
+
+        let app = press(app, &[KeyCode::Enter, KeyCode::Down, KeyCode::Char('r')]);
+        assert_eq!(*app.get_profiles()[0].get_sort_mode(), SortMode::ByDateCreationRev);
+        assert_eq!(app.get_status(), Some(" == Sorted the tabs newest first"));
+        assert_eq!(app.get_profiles()[0].get_pairs()[list(&app).get_cursor()].title, "Tab 01");
+    }
+
+    #[test]
+    fn moving_a_tab_switches_to_manual_order() {
+        let app = press(app(vec![profile("Alpha", 4)]), &[KeyCode::Enter, KeyCode::Down, KeyCode::Char('>')]);
+        assert_eq!(app.get_status(), Some(" == Moved 1 tab(s) to the bottom, the tabs are in manual order now"));
+        assert_eq!(*app.get_profiles()[0].get_sort_mode(), SortMode::Manual);
+        // the cursor goes with the tab
+        assert_eq!(list(&app).get_cursor(), 3);
+        assert_eq!(app.get_profiles()[0].get_pairs()[3].title, "Tab 01");
+
+        let app = update(app, key_with(KeyCode::Up, KeyModifiers::SHIFT));
+        assert_eq!(app.get_status(), Some(" == Moved 1 tab(s) up"));
+        assert_eq!(list(&app).get_cursor(), 2);
+
+        // j / k do not move tabs
+        let app = press(app, &[KeyCode::Char('j'), KeyCode::Char('k')]);
+        assert_eq!(app.get_profiles()[0].get_pairs()[2].title, "Tab 01");
+    }
+
+    #[test]
+    fn tagged_tabs_are_found_from_the_tag_list() {
+        let app = press(app(vec![profile("Alpha", 3), profile("Beta", 1)]), &[KeyCode::Enter, KeyCode::Down, KeyCode::Char('t')]);
+        let mut app = press(type_text(app, "#news, later"), &[KeyCode::Enter]);
+        assert_eq!(app.get_profiles()[0].get_pairs()[1].get_tags(), ["news", "later"]);
+        assert_eq!(app.take_effects(), vec![Effect::Save { prfl_idx: 0 }]);
+
+        let app = press(app, &[KeyCode::Esc, KeyCode::Char('#')]);
+        assert!(render(&app).find("#news").is_some());
+
+        // "later" comes first
+        let app = press(app, &[KeyCode::Down, KeyCode::Enter]);
+        assert!(matches!(app.get_screen(), Screen::TagView { tag, .. } if tag == "news"));
+        let app = press(app, &[KeyCode::Enter]);
+        assert!(matches!(app.get_screen(), Screen::Profile { prfl_idx: 0, .. }));
+        assert_eq!(list(&app).get_cursor(), 1);
+    }
+
+    #[test]
+    fn profiles_are_moved_between_groups() {
+        let app = press(app(vec![profile("Alpha", 1), profile("Beta", 1)]), &[KeyCode::Char('g')]);
+        let app = press(type_text(app, "work / rust"), &[KeyCode::Enter]);
+        assert_eq!(app.get_status(), Some(" == Moved \"Alpha\" to work/rust/"));
+        assert_eq!(app.get_profiles()[0].get_group(), "work/rust");
+        // work/, rust/, then Alpha
+        assert_eq!(list(&app).get_cursor(), 2);
+
+        let app = press(app, &[KeyCode::Left, KeyCode::Enter]);
+        assert_eq!(list(&app).get_cursor(), 1);
+        assert!(matches!(app.get_screen(), Screen::ProfileList { collapsed, .. } if collapsed == &["work/rust"]));
+        assert!(render(&app).find(">> Alpha").is_none());
+
+        let app = press(type_text(press(app, &[KeyCode::Char('g')]), "/play"), &[KeyCode::Enter]);
+        assert_eq!(app.get_status(), Some(" == Moved 1 profile(s) from work/rust/ to work/rust/play/"));
+        assert_eq!(app.get_profiles()[0].get_group(), "work/rust/play");
+    }
+
+    #[test]
+    fn splits_stop_at_a_taken_name() {
+        let app = press(app(vec![profile("Alpha", 3), profile("Beta", 1)]), &[KeyCode::Enter, KeyCode::Char(' '), KeyCode::Char('x')]);
+        let mut app = press(type_text(app, "beta"), &[KeyCode::Enter]);
+        assert_eq!(app.get_status(), Some(" == There is a profile called \"beta\" already"));
+        assert!(app.get_dialog().is_some());
+        assert_eq!(app.get_profiles()[0].get_pairs().len(), 3);
+        assert!(app.take_effects().is_empty());
+
+        let app = press(app, &[KeyCode::Backspace; 4]);
+        let app = press(type_text(app, "Gamma"), &[KeyCode::Enter]);
+        assert_eq!(app.get_status(), Some(" == Moved 1 tab(s) to the new profile \"Gamma\""));
+        assert_eq!(app.get_profiles().len(), 3);
+    }
+
+    #[test]
+    fn splits_by_host_stop_at_a_taken_name() {
+        // every tab of Alpha would go to "Alpha - alpha.com"
+        let app = press(app(vec![profile("Alpha", 3), profile("Alpha - alpha.com", 1)]), &[KeyCode::Enter, KeyCode::Char('x'), KeyCode::Enter]);
+        assert_eq!(app.get_status(), Some(" == There is a profile called \"Alpha - alpha.com\" already"));
+        assert_eq!(app.get_profiles()[0].get_pairs().len(), 3);
+        assert_eq!(app.get_profiles().len(), 2);
+    }
+
+    #[test]
+    fn tabs_are_put_in_sections() {
+        let app = press(app(vec![profile("Alpha", 3)]), &[KeyCode::Enter, KeyCode::Char('g')]);
+        let app = type_text(app, "Later");
+        assert_eq!(render(&app).row(SIZE.1 - 1), "Section: Later");
+
+        let mut app = press(app, &[KeyCode::Enter]);
+        assert_eq!(app.get_status(), Some(" == Put 1 tab(s) in the section \"Later\""));
+        assert_eq!(app.take_effects(), vec![Effect::Save { prfl_idx: 0 }]);
+        assert_eq!(app.get_profiles()[0].get_sections(), ["Later"]);
+        assert_eq!(titles(&app, 0), ["Tab 01", "Tab 02", "Tab 00"]);
+        assert!(render(&app).find("Later").is_some());
+    }
+
+    #[test]
+    fn edits_are_undone_and_redone() {
+        let app = press(app(vec![profile("Alpha", 2)]), &[KeyCode::Char('u')]);
+        assert_eq!(app.get_status(), Some(" == Nothing to undo"));
+
+        let app = press(app, &[KeyCode::Enter, KeyCode::Enter, KeyCode::Char('d'), KeyCode::Char('y')]);
+        assert_eq!(titles(&app, 0), ["Tab 01"]);
+
+        let mut app = press(app, &[KeyCode::Char('u')]);
+        assert_eq!(app.get_status(), Some(" == Undid the change to \"Alpha\" (0 to undo, 1 to redo)"));
+        assert_eq!(titles(&app, 0), ["Tab 00", "Tab 01"]);
+        assert!(app.take_effects().contains(&Effect::Save { prfl_idx: 0 }));
+
+        let app = update(app, key_with(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(app.get_status(), Some(" == Redid the change to \"Alpha\" (1 to undo, 0 to redo)"));
+        assert_eq!(titles(&app, 0), ["Tab 01"]);
+        let app = update(app, key_with(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(app.get_status(), Some(" == Nothing to redo"));
+    }
+
+    #[test]
+    fn undo_keeps_what_was_launched_since() {
+        let app = press(app(vec![profile("Alpha", 2)]), &[KeyCode::Char('a')]);
+        assert!(app.get_profiles()[0].is_template());
+
+        let at = NaiveDateTime::parse_from_str("2026-01-02 03:04:05", "%Y-%m-%d %H:%M:%S").unwrap();
+        let app = update(app, AppEvent::Launched { tabs: vec![(0, 1)], values: vec![], at, result: Ok(()) });
+        let app = press(app, &[KeyCode::Char('u')]);
+
+        let prfl = &app.get_profiles()[0];
+        assert!(!prfl.is_template());
+        assert_eq!(prfl.get_pairs()[1].get_times_opened(), &[at]);
+        assert!(prfl.get_pairs()[0].get_times_opened().is_empty());
+    }
+
+    #[test]
+    fn lists_fit_after_deleting() {
+        let app = press(app(vec![profile("Alpha", 3)]), &[KeyCode::Enter, KeyCode::End]);
+        assert_eq!(list(&app).get_cursor(), 2);
+
+        let mut app = press(app, &[KeyCode::Char('d'), KeyCode::Char('y')]);
+        assert_eq!(titles(&app, 0), vec!["Tab 00", "Tab 01"]);
+        assert_eq!(list(&app).get_cursor(), 1);
+        assert!(matches!(app.take_effects().as_slice(), [Effect::Save { prfl_idx: 0 }]));
+
+        let app = press(app, &[KeyCode::Char('d'), KeyCode::Char('y'), KeyCode::Char('d'), KeyCode::Char('y')]);
+        assert!(titles(&app, 0).is_empty());
+        assert_eq!(list(&app).get_cursor(), 0);
+    }
+
+    #[test]
+    fn tabs_stay_put_while_a_search_is_typed() {
+        let app = press(app(vec![profile("Alpha", 5)]), &[KeyCode::Enter, KeyCode::Char('/')]);
+        let app = type_text(app, "Tab 03");
+        let app = update(app, key_with(KeyCode::Up, KeyModifiers::SHIFT));
+        let app = update(app, key_with(KeyCode::Down, KeyModifiers::SHIFT));
+
+        assert_eq!(titles(&app, 0), vec!["Tab 00", "Tab 01", "Tab 02", "Tab 03", "Tab 04"]);
+        assert!(app.get_screen().get_search().is_some_and(Search::is_typing));
+    }
+
+    #[test]
+    fn tabs_move_with_shift() {
+        let app = press(app(vec![profile("Alpha", 3)]), &[KeyCode::Enter, KeyCode::End]);
+        let app = update(app, key_with(KeyCode::Up, KeyModifiers::SHIFT));
+        assert_eq!(titles(&app, 0), vec!["Tab 00", "Tab 02", "Tab 01"]);
+        assert_eq!(list(&app).get_cursor(), 1);
+
+        // only with SHIFT, or < / >
+        let app = press(app, &[KeyCode::Char('k'), KeyCode::Char('j'), KeyCode::Char('<')]);
+        assert_eq!(titles(&app, 0), vec!["Tab 02", "Tab 00", "Tab 01"]);
+    }
+}
@@ -0,0 +1,378 @@
+use std::{fmt, io::{self, Stdout, Write}};
+
+use crossterm::{style::{Color, Colored}, terminal};
+use unicode_width::UnicodeWidthChar;
+
+use crate::Errors;
+
+//
+//
+//Where the RENDERER draws to: the real terminal, or a screen kept in memory
+//
+//
+
+
+
+///
+///Anything the renderer can draw on.
+///
+///Drawing happens by writing crossterm commands (ANSI escape sequences) to it,
+///so all a backend has to add to [Write] is its size.
+///
+pub trait Backend: Write {
+    /// `(columns, rows)`
+    fn size(&self) -> Result<(u16, u16), Errors>;
+}
+
+impl Backend for Stdout {
+    fn size(&self) -> Result<(u16, u16), Errors> {
+        terminal::size().map_err(|source| Errors::WriteToStdoutError { source })
+    }
+}
+
+
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cell {
+    /// empty for the right half of a wide character
+    pub symbol: String,
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self { symbol: " ".to_string(), fg: None, bg: None }
+    }
+}
+
+
+///
+///A terminal that only exists in memory.
+///
+///It understands the subset of escape sequences the renderer writes
+///(cursor moves, clearing, colours), so after rendering into it
+///the screen can be inspected row by row, e.g. in snapshot tests:
+///
+///```ignore
+///let mut screen = ScreenBuffer::new(80, 24);
+///render_beginning(&mut screen, ProfileSortMode::default())?;
+///assert!(screen.row(0).starts_with("=== Firefox Tab Resumer ==="));
+///```
+///
+#[derive(Debug, Clone)]
+pub struct ScreenBuffer {
+    cols: u16,
+    rows: u16,
+    cells: Vec<Vec<Cell>>,
+
+    cursor: (u16, u16),
+    fg: Option<Color>,
+    bg: Option<Color>,
+
+    /// an escape sequence cut in half by the writes
+    pending: Vec<u8>,
+}
+
+impl ScreenBuffer {
+    pub fn new(cols: u16, rows: u16) -> Self {
+        Self {
+            cols,
+            rows,
+            cells: vec![vec![Cell::default(); cols as usize]; rows as usize],
+            cursor: (0, 0),
+            fg: None,
+            bg: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Changes the size the renderer sees, keeping what fits of the current contents
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        self.cells.resize(rows as usize, vec![Cell::default(); cols as usize]);
+        for row in self.cells.iter_mut() {
+            row.resize(cols as usize, Cell::default());
+        }
+
+        self.cols = cols;
+        self.rows = rows;
+        self.cursor = (self.cursor.0.min(cols.saturating_sub(1)), self.cursor.1.min(rows.saturating_sub(1)));
+    }
+
+    /// `(column, row)`, 0-based like [crossterm::cursor::MoveTo]
+    pub fn cursor(&self) -> (u16, u16) {
+        self.cursor
+    }
+
+    pub fn cell(&self, col: u16, row: u16) -> Option<&Cell> {
+        self.cells.get(row as usize)?.get(col as usize)
+    }
+
+    /// The text of `row`, without trailing spaces
+    pub fn row(&self, row: u16) -> String {
+        self.cells
+            .get(row as usize)
+            .map(|cells| cells.iter().map(|cell| cell.symbol.as_str()).collect::<String>())
+            .unwrap_or_default()
+            .trim_end()
+            .to_string()
+    }
+
+    /// The text of all rows, see [ScreenBuffer::row]
+    pub fn rows(&self) -> Vec<String> {
+        (0..self.rows).map(|row| self.row(row)).collect()
+    }
+
+    /// The first row containing `text`
+    pub fn find(&self, text: &str) -> Option<u16> {
+        (0..self.rows).find(|&row| self.row(row).contains(text))
+    }
+
+
+    fn clear(&mut self) {
+        for row in self.cells.iter_mut() {
+            row.fill(Cell::default());
+        }
+    }
+
+    fn clear_from_cursor_down(&mut self) {
+        self.clear_line(true);
+        for cells in self.cells.iter_mut().skip(self.cursor.1 as usize + 1) {
+            cells.fill(Cell::default());
+        }
+    }
+
+    fn clear_line(&mut self, from_cursor: bool) {
+        let (col, row) = self.cursor;
+        let start = if from_cursor { col as usize } else { 0 };
+
+        if let Some(cells) = self.cells.get_mut(row as usize) {
+            cells.iter_mut().skip(start).for_each(|cell| *cell = Cell::default());
+        }
+    }
+
+    fn new_line(&mut self) {
+        // the renderer never relies on scrolling, so the last row just stays the last row
+        self.cursor.1 = (self.cursor.1 + 1).min(self.rows.saturating_sub(1));
+    }
+
+    fn put(&mut self, c: char) {
+        let width = UnicodeWidthChar::width(c).unwrap_or(0) as u16;
+        if width == 0 {
+            return;
+        }
+
+        // like a terminal with auto-wrap: what does not fit continues on the next row
+        if self.cursor.0 + width > self.cols {
+            self.cursor.0 = 0;
+            self.new_line();
+        }
+        let (col, row) = self.cursor;
+        if col + width > self.cols {
+            return;
+        }
+
+        let cells = &mut self.cells[row as usize];
+        cells[col as usize] = Cell { symbol: c.to_string(), fg: self.fg, bg: self.bg };
+        for filler in 1..width {
+            cells[(col + filler) as usize] = Cell { symbol: String::new(), fg: self.fg, bg: self.bg };
+        }
+
+        self.cursor.0 = col + width;
+    }
+
+    fn set_graphics(&mut self, params: &str) {
+        if params.is_empty() || params == "0" {
+            self.fg = None;
+            self.bg = None;
+            return;
+        }
+
+        match Colored::parse_ansi(params) {
+            Some(Colored::ForegroundColor(Color::Reset)) => self.fg = None,
+            Some(Colored::BackgroundColor(Color::Reset)) => self.bg = None,
+            Some(Colored::ForegroundColor(color)) => self.fg = Some(color),
+            Some(Colored::BackgroundColor(color)) => self.bg = Some(color),
+            _ => {},
+        }
+    }
+
+    /// Handles `ESC [ params final`
+    fn control_sequence(&mut self, params: &str, final_byte: char) {
+        let numbers: Vec<u16> = params
+            .trim_start_matches('?')
+            .split(';')
+            .map(|n| n.parse().unwrap_or(0))
+            .collect();
+        let nth = |idx: usize| numbers.get(idx).copied().filter(|&n| n > 0).unwrap_or(1);
+
+        let (col, row) = self.cursor;
+        let last_col = self.cols.saturating_sub(1);
+        let last_row = self.rows.saturating_sub(1);
+
+        match final_byte {
+            'H' | 'f' => self.cursor = ((nth(1) - 1).min(last_col), (nth(0) - 1).min(last_row)),
+            'A' => self.cursor.1 = row.saturating_sub(nth(0)),
+            'B' => self.cursor.1 = (row + nth(0)).min(last_row),
+            'C' => self.cursor.0 = (col + nth(0)).min(last_col),
+            'D' => self.cursor.0 = col.saturating_sub(nth(0)),
+            'G' => self.cursor.0 = (nth(0) - 1).min(last_col),
+            'J' => match numbers[0] {
+                0 => self.clear_from_cursor_down(),
+                _ => self.clear(),
+            },
+            'K' => self.clear_line(numbers[0] == 0),
+            'm' => self.set_graphics(params),
+            // showing / hiding the cursor, alternate screen, ...
+            _ => {},
+        }
+    }
+
+    fn feed(&mut self, text: &str) {
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\x1b' => match chars.peek() {
+                    Some('[') => {
+                        chars.next();
+
+                        let mut params = String::new();
+                        for c in chars.by_ref() {
+                            match c {
+                                '\x40'..='\x7e' => {
+                                    self.control_sequence(&params, c);
+                                    break;
+                                },
+                                _ => params.push(c),
+                            }
+                        }
+                    },
+                    // single character escapes (save / restore cursor, ...) change nothing we keep
+                    _ => { chars.next(); },
+                },
+                '\r' => self.cursor.0 = 0,
+                '\n' => self.new_line(),
+                _ => self.put(c),
+            }
+        }
+    }
+}
+
+
+/// Where the escape sequence at the end of `bytes` starts, if it is not complete yet
+fn unfinished_escape(bytes: &[u8]) -> Option<usize> {
+    let esc = bytes.iter().rposition(|&b| b == b'\x1b')?;
+
+    match &bytes[esc + 1..] {
+        [] => Some(esc),
+        [b'[', params @ ..] if !params.iter().any(|b| (0x40..=0x7e).contains(b)) => Some(esc),
+        _ => None,
+    }
+}
+
+
+impl Write for ScreenBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+
+        // keep incomplete UTF-8 and escape sequences until the rest of them is written
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(text) => text.len(),
+            Err(e) => e.valid_up_to(),
+        };
+        let complete = unfinished_escape(&self.pending[..valid]).unwrap_or(valid);
+
+        let text = String::from_utf8_lossy(&self.pending[..complete]).into_owned();
+        self.pending.drain(..complete);
+        self.feed(&text);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Backend for ScreenBuffer {
+    fn size(&self) -> Result<(u16, u16), Errors> {
+        Ok((self.cols, self.rows))
+    }
+}
+
+
+impl fmt::Display for ScreenBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            writeln!(f, "{}", row)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use crossterm::{cursor::MoveTo, queue, style::{Print, ResetColor, SetForegroundColor}, terminal::{Clear, ClearType}};
+
+    use super::*;
+
+    #[test]
+    fn follows_cursor_moves_and_line_breaks() {
+        let mut screen = ScreenBuffer::new(10, 3);
+
+        queue!(screen, Print("ab\n\rc"), MoveTo(4, 0), Print("d"), MoveTo(8, 1), Print("xyz")).unwrap();
+        // what does not fit continues on the next row
+        assert_eq!(screen.rows(), vec!["ab  d", "c       xy", "z"]);
+        assert_eq!(screen.cursor(), (1, 2));
+        assert_eq!(screen.find("d"), Some(0));
+        assert_eq!(screen.find("q"), None);
+    }
+
+    #[test]
+    fn keeps_colours_per_cell() {
+        let mut screen = ScreenBuffer::new(10, 1);
+
+        queue!(screen, SetForegroundColor(Color::Cyan), Print("a"), ResetColor, Print("b")).unwrap();
+        assert_eq!(screen.cell(0, 0).and_then(|cell| cell.fg), Some(Color::Cyan));
+        assert_eq!(screen.cell(1, 0).and_then(|cell| cell.fg), None);
+    }
+
+    #[test]
+    fn wide_chars_take_two_cells() {
+        let mut screen = ScreenBuffer::new(5, 2);
+
+        queue!(screen, Print("日本語")).unwrap();
+        assert_eq!(screen.rows(), vec!["日本", "語"]);
+        assert_eq!(screen.cell(1, 0).map(|cell| cell.symbol.as_str()), Some(""));
+        assert_eq!(screen.cursor(), (2, 1));
+    }
+
+    #[test]
+    fn clears_lines_and_the_screen() {
+        let mut screen = ScreenBuffer::new(6, 3);
+
+        queue!(screen, Print("aaaaaa\n\rbbbbbb\n\rcccccc"), MoveTo(2, 0), Clear(ClearType::UntilNewLine)).unwrap();
+        assert_eq!(screen.rows(), vec!["aa", "bbbbbb", "cccccc"]);
+        queue!(screen, MoveTo(3, 1), Clear(ClearType::FromCursorDown)).unwrap();
+        assert_eq!(screen.rows(), vec!["aa", "bbb", ""]);
+        queue!(screen, Clear(ClearType::All)).unwrap();
+        assert_eq!(screen.rows(), vec!["", "", ""]);
+    }
+
+    #[test]
+    fn escapes_and_chars_may_be_split_across_writes() {
+        let mut screen = ScreenBuffer::new(10, 2);
+        let text = "\x1b[2;3H日x".as_bytes();
+
+        for byte in text {
+            screen.write_all(std::slice::from_ref(byte)).unwrap();
+        }
+        assert_eq!(screen.rows(), vec!["", "  日x"]);
+    }
+}
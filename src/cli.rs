@@ -0,0 +1,1762 @@
+use std::{io::Read, path::Path};
+
+use chrono::NaiveDateTime;
+use itertools::Itertools;
+
+use serde_json::{json, Value};
+
+use crate::{
+    diff::{diff_profiles, take_changes, unified_diff, DiffKind},
+    export::{export, export_to_file, ExportFormat, ExportScope},
+    io::{delete_profile, delete_profile_file, import_json, parse_url_lines, read_profile_to_compare, read_profiles_with_errors, read_settings, save_profile},
+    launcher::{launch_profile, launch_tabs, Browser, LaunchMode},
+    query::Query,
+    search::{search_profiles, SearchHit},
+    urls::{placeholders, validate_url},
+    exit_code, group_is_in, is_reserved_section, normalize_group, profile_order, sort_profiles, Errors, Profile, SplitBy, TabKind, URLTitlePair, ARCHIVE_SECTION,
+};
+
+//
+//
+//Non-interactive commands, for scripting.
+//Running without any arguments starts the TUI instead.
+//
+//
+
+
+
+pub const USAGE: &str = "\
+Usage: firefox_resumer [OPTIONS] [COMMAND]
+
+Without a COMMAND, the interactive terminal UI is started.
+
+Commands:
+  list                                     List all profiles
+  show <profile>                           List the tabs of a profile
+  add <profile> <url> [--title <title>] [--section <section>] [--template]
+                                           Add a tab (the profile is created if needed); with --template
+                                           the URL has placeholders like {q} filled when it is opened
+  add <profile> --stdin                    Add the URLs read from stdin, in the same format as for import
+  rm <profile> [<tab>]                     Remove a tab (by number or URL), or the whole profile
+  open <profile> [--normal | --private] [--browser <browser>] [--param <name>=<value>]...
+                                           Open all tabs of a profile (privately by default),
+                                           one window per section; --param fills the placeholders
+                                           of template tabs (percent-encoded)
+  open --group <group> [--normal | --private] [--browser <browser>] [--param <name>=<value>]...
+                                           Open all tabs of the profiles in a group (and the groups in it)
+  group <profile> [<group>]                Put a profile in a group like work/rust, or in none
+  merge <profile> --into <profile>         Move all tabs of a profile into another one and delete it;
+                                           a tab already there is merged into it (tags, notes, times)
+  split <profile> --by host|tag            Move the tabs of every host (or first tag) to a new profile
+  split <profile> <tab>... --name <name>   Move some tabs (by number or URL) to a new profile
+  mv <profile> <tab>... --to <profile>     Move tabs to another profile, keeping their times, tags and notes
+  cp <profile> <tab>... --to <profile>     Copy tabs to another profile, keeping their times, tags and notes
+  diff <profile> <profile> [--take]        Show the tabs added to, removed from or retitled in the second profile
+                                           compared to the first (by URL); --take makes the first like the second
+  diff <profile> --with <file> [--take]    The same against a profile file or backup of it (.toml),
+                                           a JSON export (.json) or a text file of URLs
+  expire <profile> <days>|never            Set after how many days unopened the tabs of a profile are stale
+  stale [<profile>]                        List the stale tabs of a profile, or of all profiles
+  archive [<profile>]                      Move the stale tabs into the Archive section of their profile,
+                                           which is kept and searched but not opened with the profile
+  unarchive <profile> [<tab>...]           Move archived tabs (by default all of them) back to their sections
+  template <profile> [on|off]              Mark a profile as a template (or not any more)
+  new <profile> --from <template> [--param <name>=<value>]...
+                                           Create a profile with the tabs of a template, filling the
+                                           placeholders like {query} in its URLs, titles and name with
+                                           the values given ({date} is today unless given)
+  clone <profile> --name <name>            Copy a profile, with everything in it, to a new one
+  import <file> [--into <profile>]         Import a JSON export, or a text file of URLs
+                                           (one per line, optionally followed by a TAB and a title);
+                                           tabs go into the profile of the same name, or --into
+  export [<profile>] [--to json|csv|md|html] [--output <file>]
+                                           Export one or all profiles (JSON to stdout by default)
+  search <query>                           Find tabs matching <query>, best first: words match title, URL
+                                           or host fuzzily (or the note), and field:value terms (host, url,
+                                           title, tag, note, profile, added, opened) filter, e.g.
+                                             search rust host:youtube.com -title:live added:>2024-01-01
+  help                                     Show this message
+
+Options (anywhere on the command line):
+  --format plain|tsv|json                  Output format (plain by default); tsv starts with a header row
+  -q, --quiet                              Only print requested data, no reports or error messages
+  -v, --verbose                            Also print what is being done to stderr
+
+<profile> is the profile name (case-insensitive).
+<browser> is firefox, chrome, chromium, brave, edge or a path to an executable;
+the default can be set with the ITR_BROWSER environment variable.
+
+Exit codes:
+  0 success, 1 other failure, 2 wrong usage, 3 not found, 4 invalid input,
+  5 file / output error, 6 tab already exists, 7 browser could not be started";
+
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// for humans
+    #[default] Plain,
+    /// a header row with the field names, then one row per record
+    Tsv,
+    /// an array of objects with the same field names as the TSV header
+    Json,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "plain" | "text" => Some(Self::Plain),
+            "tsv" => Some(Self::Tsv),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Quiet,
+    #[default] Normal,
+    Verbose,
+}
+
+
+/// The flags that apply to every command
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    pub format: OutputFormat,
+    pub verbosity: Verbosity,
+}
+
+impl Options {
+    /// Prints `msg` to stderr, but only with `--verbose`
+    fn log(&self, msg: &str) {
+        if self.verbosity == Verbosity::Verbose {
+            eprintln!("{}", msg);
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    List,
+    Show { profile: String },
+    /// a [TabKind::Template] if `template`
+    Add { profile: String, url: String, title: Option<String>, section: Option<String>, template: bool },
+    AddStdin { profile: String },
+    Rm { profile: String, tab: Option<String> },
+    /// `params` fill the placeholders of the [crate::TabKind::Template] tabs
+    Open { profile: String, mode: LaunchMode, browser: Option<String>, params: Vec<(String, String)> },
+    OpenGroup { group: String, mode: LaunchMode, browser: Option<String>, params: Vec<(String, String)> },
+    Group { profile: String, group: String },
+    Merge { profile: String, into: String },
+    /// by host or tag
+    Split { profile: String, by: SplitBy },
+    /// the `tabs` into a new profile called `name`
+    SplitTabs { profile: String, tabs: Vec<String>, name: String },
+    /// copy (or move, unless `keep`) the `tabs` to the profile `to`
+    Transfer { profile: String, tabs: Vec<String>, to: String, keep: bool },
+    /// against the profile `other` or the profile read from the file `with`, see [crate::diff]
+    Diff { profile: String, other: Option<String>, with: Option<String>, take: bool },
+    /// never if `days` is [None]
+    Expire { profile: String, days: Option<u32> },
+    Stale { profile: Option<String> },
+    Archive { profile: Option<String> },
+    /// all archived tabs of the profile if `tabs` is empty
+    Unarchive { profile: String, tabs: Vec<String> },
+    Template { profile: String, template: bool },
+    /// a profile called `name` from the template `from`, see [Profile::from_template]
+    FromTemplate { name: String, from: String, params: Vec<(String, String)> },
+    Clone { profile: String, name: String },
+    Import { file: String, into: Option<String> },
+    Export { profile: Option<String>, to: ExportFormat, output: Option<String> },
+    Search { text: String },
+    Help,
+}
+
+
+
+
+// ============== PARSING
+
+
+
+/// What is left of the command line after the command name
+struct ParsedArgs {
+    positional: Vec<String>,
+    flags: Vec<(String, Option<String>)>,
+}
+
+impl ParsedArgs {
+    fn has(&self, flag: &str) -> bool {
+        self.flags.iter().any(|(name, _)| name == flag)
+    }
+
+    fn value(&self, flag: &str) -> Option<String> {
+        self.flags
+            .iter()
+            .rev()
+            .find(|(name, _)| name == flag)
+            .and_then(|(_, value)| value.clone())
+    }
+
+    /// The values of a flag given any number of times, in order
+    fn values(&self, flag: &str) -> Vec<String> {
+        self.flags
+            .iter()
+            .filter(|(name, _)| name == flag)
+            .filter_map(|(_, value)| value.clone())
+            .collect()
+    }
+
+    /// Fails unless there are between `min` and `max` positional arguments
+    fn expect_positional(&self, min: usize, max: usize) -> Result<(), Errors> {
+        match (min..=max).contains(&self.positional.len()) {
+            true => Ok(()),
+            false if min == max => Err(Errors::usage(&format!(
+                "expected {} argument(s), got {}", min, self.positional.len()
+            ))),
+            false => Err(Errors::usage(&format!(
+                "expected {} to {} arguments, got {}", min, max, self.positional.len()
+            ))),
+        }
+    }
+}
+
+
+///
+///Splits `args` into positional arguments and flags.
+///`value_flags` take the next argument (or what follows `=`) as their value,
+///`bool_flags` take none; any other flag is an error.
+///A lone `--` ends flag parsing.
+///
+fn parse_rest(args: &[String], value_flags: &[&str], bool_flags: &[&str]) -> Result<ParsedArgs, Errors> {
+    let mut parsed = ParsedArgs { positional: Vec::new(), flags: Vec::new() };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            parsed.positional.extend(iter.by_ref().cloned());
+            break;
+        }
+        if !arg.starts_with('-') || arg == "-" {
+            parsed.positional.push(arg.clone());
+            continue;
+        }
+
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+
+        if value_flags.contains(&name) {
+            let value = match inline_value {
+                Some(value) => value,
+                None => iter.next().ok_or(Errors::usage(&format!("{} needs a value", name)))?.clone(),
+            };
+            parsed.flags.push((name.to_string(), Some(value)));
+        } else if bool_flags.contains(&name) && inline_value.is_none() {
+            parsed.flags.push((name.to_string(), None));
+        } else {
+            return Err(Errors::usage(&format!("unknown flag {}", arg)));
+        }
+    }
+
+    Ok(parsed)
+}
+
+
+/// `name=value` pairs, as given to `--param`
+fn parse_params(params: &[String]) -> Result<Vec<(String, String)>, Errors> {
+    params
+        .iter()
+        .map(|param| match param.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => Ok((name.trim().to_string(), value.to_string())),
+            _ => Err(Errors::usage(&format!("--param needs <name>=<value>, got \"{}\"", param))),
+        })
+        .collect()
+}
+
+
+///
+///Fails unless `params` have a value for every placeholder of the template tabs among `tabs`
+///(see [crate::TabKind::Template]), telling the values each missing one was opened with lately.
+///
+fn check_params(tabs: &[(&Profile, usize)], params: &[(String, String)]) -> Result<(), Errors> {
+    let mut missing: Vec<(String, &[String])> = Vec::new();
+    for &(prfl, idx) in tabs {
+        let pair = &prfl.get_pairs()[idx];
+        for name in pair.url_placeholders() {
+            if !params.iter().any(|(other, _)| *other == name) && !missing.iter().any(|(other, _)| *other == name) {
+                let recent = pair.get_recent_values(&name);
+                missing.push((name, recent));
+            }
+        }
+    }
+
+    match missing.is_empty() {
+        true => Ok(()),
+        false => Err(Errors::usage(&missing
+            .iter()
+            .map(|(name, recent)| match recent.is_empty() {
+                true => format!("{{{}}} needs --param {}=<value>", name, name),
+                false => format!("{{{}}} needs --param {}=<value> (lately: {})", name, name, recent.join(", ")),
+            })
+            .join("\n"))),
+    }
+}
+
+
+fn unknown_format(name: &str) -> Errors {
+    Errors::usage(&format!("unknown output format \"{}\" (expected plain, tsv or json)", name))
+}
+
+///
+///Takes the [Options] flags out of `args`, wherever they are
+///(up to a lone `--`), and returns them with the remaining arguments.
+///
+fn parse_options(args: &[String]) -> Result<(Options, Vec<String>), Errors> {
+    let mut opts = Options::default();
+    let mut rest = Vec::with_capacity(args.len());
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--" => {
+                rest.push(arg.clone());
+                rest.extend(iter.by_ref().cloned());
+            },
+            "-q" | "--quiet" => opts.verbosity = Verbosity::Quiet,
+            "-v" | "--verbose" => opts.verbosity = Verbosity::Verbose,
+            "--format" => {
+                let name = iter.next().ok_or(Errors::usage("--format needs a value"))?;
+                opts.format = OutputFormat::from_name(name).ok_or(unknown_format(name))?;
+            },
+            _ => match arg.strip_prefix("--format=") {
+                Some(name) => opts.format = OutputFormat::from_name(name).ok_or(unknown_format(name))?,
+                None => rest.push(arg.clone()),
+            },
+        }
+    }
+
+    Ok((opts, rest))
+}
+
+
+///
+///Parses the command line (without the program name).
+///
+///Returns [Ok(None)] if no command was given, i.e. the TUI should be started.
+///
+pub fn parse_args(args: &[String]) -> Result<Option<(Command, Options)>, Errors> {
+    let (opts, args) = parse_options(args)?;
+
+    Ok(parse_command(&args)?.map(|cmd| (cmd, opts)))
+}
+
+fn parse_command(args: &[String]) -> Result<Option<Command>, Errors> {
+    let (cmd, rest) = match args.split_first() {
+        Some((cmd, rest)) => (cmd.as_str(), rest),
+        None => return Ok(None),
+    };
+
+    let cmd = match cmd {
+        "list" | "ls" => {
+            parse_rest(rest, &[], &[])?.expect_positional(0, 0)?;
+            Command::List
+        },
+        "show" => {
+            let parsed = parse_rest(rest, &[], &[])?;
+            parsed.expect_positional(1, 1)?;
+            Command::Show { profile: parsed.positional[0].clone() }
+        },
+        "add" => {
+            let parsed = parse_rest(rest, &["--title", "--section"], &["--stdin", "--template"])?;
+            if parsed.has("--stdin") {
+                parsed.expect_positional(1, 1)?;
+                if parsed.has("--title") || parsed.has("--section") || parsed.has("--template") {
+                    return Err(Errors::usage("--title, --section and --template cannot be used with --stdin"));
+                }
+                return Ok(Some(Command::AddStdin { profile: parsed.positional[0].clone() }));
+            }
+
+            parsed.expect_positional(2, 2)?;
+            if parsed.value("--section").is_some_and(|section| is_reserved_section(&section)) {
+                return Err(Errors::usage(&format!("the {} section is only for archived tabs, see archive", ARCHIVE_SECTION)));
+            }
+            Command::Add {
+                profile: parsed.positional[0].clone(),
+                url: parsed.positional[1].clone(),
+                title: parsed.value("--title"),
+                section: parsed.value("--section"),
+                template: parsed.has("--template"),
+            }
+        },
+        "rm" | "remove" => {
+            let parsed = parse_rest(rest, &[], &[])?;
+            parsed.expect_positional(1, 2)?;
+            Command::Rm {
+                profile: parsed.positional[0].clone(),
+                tab: parsed.positional.get(1).cloned(),
+            }
+        },
+        "open" => {
+            let parsed = parse_rest(rest, &["--browser", "--group", "--param"], &["--normal", "--private"])?;
+            if parsed.has("--normal") && parsed.has("--private") {
+                return Err(Errors::usage("--normal and --private cannot be used together"));
+            }
+            let mode = match parsed.has("--normal") {
+                true => LaunchMode::Normal,
+                false => LaunchMode::Private,
+            };
+            let params = parse_params(&parsed.values("--param"))?;
+
+            match parsed.value("--group") {
+                Some(group) => {
+                    parsed.expect_positional(0, 0)?;
+                    Command::OpenGroup { group, mode, browser: parsed.value("--browser"), params }
+                },
+                None => {
+                    parsed.expect_positional(1, 1)?;
+                    Command::Open { profile: parsed.positional[0].clone(), mode, browser: parsed.value("--browser"), params }
+                },
+            }
+        },
+        "group" => {
+            let parsed = parse_rest(rest, &[], &[])?;
+            parsed.expect_positional(1, 2)?;
+            Command::Group {
+                profile: parsed.positional[0].clone(),
+                group: parsed.positional.get(1).cloned().unwrap_or_default(),
+            }
+        },
+        "merge" => {
+            let parsed = parse_rest(rest, &["--into"], &[])?;
+            parsed.expect_positional(1, 1)?;
+            Command::Merge {
+                profile: parsed.positional[0].clone(),
+                into: parsed.value("--into").ok_or(Errors::usage("merge needs --into <profile>"))?,
+            }
+        },
+        "split" => {
+            let parsed = parse_rest(rest, &["--by", "--name"], &[])?;
+            match (parsed.value("--by"), parsed.value("--name")) {
+                (Some(by), None) => {
+                    parsed.expect_positional(1, 1)?;
+                    Command::Split {
+                        profile: parsed.positional[0].clone(),
+                        by: SplitBy::from_name(&by)
+                            .ok_or(Errors::usage(&format!("cannot split by \"{}\" (expected host or tag)", by)))?,
+                    }
+                },
+                (None, Some(name)) => {
+                    parsed.expect_positional(2, usize::MAX)?;
+                    Command::SplitTabs {
+                        profile: parsed.positional[0].clone(),
+                        tabs: parsed.positional[1..].to_vec(),
+                        name,
+                    }
+                },
+                _ => return Err(Errors::usage("split needs either --by host|tag or some tabs and --name <name>")),
+            }
+        },
+        "mv" | "move" | "cp" | "copy" => {
+            let parsed = parse_rest(rest, &["--to"], &[])?;
+            parsed.expect_positional(2, usize::MAX)?;
+            Command::Transfer {
+                profile: parsed.positional[0].clone(),
+                tabs: parsed.positional[1..].to_vec(),
+                to: parsed.value("--to").ok_or(Errors::usage(&format!("{} needs --to <profile>", cmd)))?,
+                keep: matches!(cmd, "cp" | "copy"),
+            }
+        },
+        "diff" => {
+            let parsed = parse_rest(rest, &["--with"], &["--take"])?;
+            let with = parsed.value("--with");
+            match with {
+                Some(_) => parsed.expect_positional(1, 1)?,
+                None => parsed.expect_positional(2, 2)?,
+            }
+            Command::Diff {
+                profile: parsed.positional[0].clone(),
+                other: parsed.positional.get(1).cloned(),
+                with,
+                take: parsed.has("--take"),
+            }
+        },
+        "expire" => {
+            let parsed = parse_rest(rest, &[], &[])?;
+            parsed.expect_positional(2, 2)?;
+            let days = match parsed.positional[1].as_str() {
+                "never" | "0" => None,
+                days => Some(days.parse::<u32>().map_err(|_| {
+                    Errors::usage(&format!("\"{}\" is neither a number of days nor never", days))
+                })?),
+            };
+            Command::Expire { profile: parsed.positional[0].clone(), days }
+        },
+        "stale" | "archive" => {
+            let parsed = parse_rest(rest, &[], &[])?;
+            parsed.expect_positional(0, 1)?;
+            let profile = parsed.positional.first().cloned();
+            match cmd {
+                "stale" => Command::Stale { profile },
+                _ => Command::Archive { profile },
+            }
+        },
+        "unarchive" => {
+            let parsed = parse_rest(rest, &[], &[])?;
+            parsed.expect_positional(1, usize::MAX)?;
+            Command::Unarchive { profile: parsed.positional[0].clone(), tabs: parsed.positional[1..].to_vec() }
+        },
+        "template" => {
+            let parsed = parse_rest(rest, &[], &[])?;
+            parsed.expect_positional(1, 2)?;
+            let template = match parsed.positional.get(1).map(String::as_str) {
+                None | Some("on") => true,
+                Some("off") => false,
+                Some(other) => return Err(Errors::usage(&format!("\"{}\" is neither on nor off", other))),
+            };
+            Command::Template { profile: parsed.positional[0].clone(), template }
+        },
+        "new" => {
+            let parsed = parse_rest(rest, &["--from", "--param"], &[])?;
+            parsed.expect_positional(1, 1)?;
+            Command::FromTemplate {
+                name: parsed.positional[0].clone(),
+                from: parsed.value("--from").ok_or(Errors::usage("new needs --from <template>"))?,
+                params: parse_params(&parsed.values("--param"))?,
+            }
+        },
+        "clone" => {
+            let parsed = parse_rest(rest, &["--name"], &[])?;
+            parsed.expect_positional(1, 1)?;
+            Command::Clone {
+                profile: parsed.positional[0].clone(),
+                name: parsed.value("--name").ok_or(Errors::usage("clone needs --name <name>"))?,
+            }
+        },
+        "import" => {
+            let parsed = parse_rest(rest, &["--into"], &[])?;
+            parsed.expect_positional(1, 1)?;
+            Command::Import {
+                file: parsed.positional[0].clone(),
+                into: parsed.value("--into"),
+            }
+        },
+        "export" => {
+            let parsed = parse_rest(rest, &["--to", "--output", "-o"], &[])?;
+            parsed.expect_positional(0, 1)?;
+            let to = match parsed.value("--to") {
+                Some(name) => ExportFormat::from_name(&name)
+                    .ok_or(Errors::usage(&format!("unknown export format \"{}\" (expected json, csv, md or html)", name)))?,
+                None => ExportFormat::Json,
+            };
+            Command::Export {
+                profile: parsed.positional.first().cloned(),
+                to,
+                output: parsed.value("--output").or(parsed.value("-o")),
+            }
+        },
+        "search" | "find" => {
+            // `-title:live` is a negated query term, not a flag
+            let terms: Vec<&str> = rest.iter().map(String::as_str).filter(|&arg| arg != "--").collect();
+            if terms.is_empty() {
+                return Err(Errors::usage("nothing to search for"));
+            }
+            Command::Search { text: terms.join(" ") }
+        },
+        "help" | "--help" | "-h" => Command::Help,
+        _ => return Err(Errors::usage(&format!("unknown command \"{}\"", cmd))),
+    };
+
+    Ok(Some(cmd))
+}
+
+
+
+
+// ============== OUTPUT
+
+
+
+const PROFILE_FIELDS: &[&str] = &["name", "id", "group", "tabs", "t_created", "t_last_modified", "file", "tags", "note", "template"];
+const TAB_FIELDS: &[&str] = &["profile", "index", "title", "url", "t_created", "tags", "note", "section"];
+/// positions are 0-based char indices into title / URL, for highlighting
+const SEARCH_FIELDS: &[&str] = &["profile", "index", "title", "url", "t_created", "tags", "note", "section", "score", "title_positions", "url_positions"];
+const CHANGE_FIELDS: &[&str] = &["action", "profile", "index", "title", "url", "reason"];
+const OPEN_FIELDS: &[&str] = &["action", "profile", "tabs", "browser", "mode"];
+const GROUP_FIELDS: &[&str] = &["action", "profile", "group", "previous_group"];
+const TEMPLATE_FIELDS: &[&str] = &["action", "profile", "template"];
+/// `from` is the template or the profile cloned
+const CREATE_FIELDS: &[&str] = &["action", "profile", "from", "tabs"];
+const EXPIRE_FIELDS: &[&str] = &["action", "profile", "expire_after_days"];
+/// `t_last_opened` is null for a tab never opened, `days_unused` counts from when it was added then
+const STALE_FIELDS: &[&str] = &["profile", "index", "title", "url", "t_last_opened", "days_unused"];
+/// indices start from 1 like those of `show`, and are null for the profile without the tab
+const DIFF_FIELDS: &[&str] = &["change", "url", "old_title", "new_title", "old_index", "new_index"];
+const EXPORT_FIELDS: &[&str] = &["action", "file", "format", "profiles"];
+
+const OUTPUT_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+
+
+///
+///What a command prints: rows with a fixed set of field names
+///(the same for every row, so that TSV / JSON consumers can rely on them),
+///each with a line for humans.
+///
+struct Records {
+    fields: &'static [&'static str],
+    rows: Vec<(Vec<Value>, String)>,
+
+    /// requested data is printed even with `--quiet`, reports of what was done are not
+    is_report: bool,
+}
+
+impl Records {
+    fn data(fields: &'static [&'static str]) -> Self {
+        Self { fields, rows: Vec::new(), is_report: false }
+    }
+    fn report(fields: &'static [&'static str]) -> Self {
+        Self { fields, rows: Vec::new(), is_report: true }
+    }
+
+    fn push(&mut self, values: Vec<Value>, plain: String) {
+        debug_assert_eq!(values.len(), self.fields.len());
+
+        self.rows.push((values, plain));
+    }
+
+    fn print(&self, opts: &Options) {
+        if self.is_report && opts.verbosity == Verbosity::Quiet {
+            return;
+        }
+
+        match opts.format {
+            OutputFormat::Plain => {
+                for (_, plain) in &self.rows {
+                    println!("{}", plain);
+                }
+            },
+            OutputFormat::Tsv => {
+                println!("{}", self.fields.join("\t"));
+                for (values, _) in &self.rows {
+                    let row: Vec<String> = values.iter().map(tsv_value).collect();
+                    println!("{}", row.join("\t"));
+                }
+            },
+            OutputFormat::Json => {
+                let objects: Vec<Value> = self.rows
+                    .iter()
+                    .map(|(values, _)| {
+                        let object = self.fields
+                            .iter()
+                            .zip(values)
+                            .map(|(field, value)| (field.to_string(), value.clone()))
+                            .collect();
+                        Value::Object(object)
+                    })
+                    .collect();
+                println!("{}", Value::Array(objects));
+            },
+        }
+    }
+}
+
+/// TSV fields cannot contain tabs or line breaks, so these are escaped like in C
+fn tsv_value(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn format_time(t: chrono::NaiveDateTime) -> String {
+    t.format(OUTPUT_TIME_FORMAT).to_string()
+}
+
+fn tab_values(prfl: &Profile, idx: usize, pair: &URLTitlePair) -> Vec<Value> {
+    vec![
+        json!(prfl.get_name()),
+        json!(idx + 1),
+        json!(pair.title),
+        json!(pair.url),
+        json!(format_time(pair.get_time_created())),
+        json!(pair.get_tags()),
+        json!(pair.get_note()),
+        json!(pair.get_section()),
+    ]
+}
+
+fn search_values(prfl: &Profile, pair: &URLTitlePair, hit: &SearchHit) -> Vec<Value> {
+    let mut values = tab_values(prfl, hit.tab_idx, pair);
+    values.extend([
+        json!(hit.score),
+        json!(hit.title_positions),
+        json!(hit.url_positions),
+    ]);
+
+    values
+}
+
+fn change_values(action: &str, prfl: &Profile, idx: Option<usize>, pair: Option<&URLTitlePair>, reason: Option<&str>) -> Vec<Value> {
+    vec![
+        json!(action),
+        json!(prfl.get_name()),
+        json!(idx.map(|idx| idx + 1)),
+        json!(pair.map(|pair| &pair.title)),
+        json!(pair.map(|pair| &pair.url)),
+        json!(reason),
+    ]
+}
+
+
+///
+///Prints `err` to stderr in the requested format (nothing with `--quiet`)
+///and returns the exit code for it.
+///
+pub fn report_error(err: &Errors, opts: &Options) -> u8 {
+    let code = err.exit_code();
+
+    match (opts.verbosity, opts.format) {
+        (Verbosity::Quiet, _) => {},
+        (_, OutputFormat::Json) => eprintln!(
+            "{}",
+            json!({ "error": err.kind(), "message": err.to_string(), "exit_code": code })
+        ),
+        (_, OutputFormat::Tsv) => eprintln!(
+            "error\tmessage\texit_code\n{}\t{}\t{}",
+            err.kind(), tsv_value(&json!(err.to_string())), code
+        ),
+        (_, OutputFormat::Plain) => {
+            eprintln!("error: {}", err);
+            if code == exit_code::USAGE {
+                eprintln!("\n{}", USAGE);
+            }
+        },
+    }
+
+    code
+}
+
+
+
+
+// ============== RUNNING
+
+
+
+///
+///All saved profiles; having none yet is not an error here.
+///Files that cannot be read are reported on stderr (unless `--quiet`) and skipped.
+///
+fn load_profiles(opts: &Options) -> Result<Vec<Profile>, Errors> {
+    let (prfls, errors) = read_profiles_with_errors()?;
+
+    if opts.verbosity != Verbosity::Quiet {
+        for err in errors {
+            eprintln!("warning: {}", err);
+        }
+    }
+    opts.log(&format!("loaded {} profile(s)", prfls.len()));
+
+    Ok(prfls)
+}
+
+fn save(prfl: &mut Profile, opts: &Options) -> Result<(), Errors> {
+    save_profile(prfl)?;
+    opts.log(&format!("saved \"{}\" to {}", prfl.get_name(), prfl.get_file_name().unwrap_or_default()));
+
+    Ok(())
+}
+
+/// `prfls` in the order of [profile_order], i.e. by group
+fn tree_order(prfls: &[Profile]) -> Vec<&Profile> {
+    profile_order(prfls)
+        .into_iter()
+        .map(|prfl_idx| &prfls[prfl_idx])
+        .collect()
+}
+
+fn find_profile(prfls: &[Profile], name: &str) -> Option<usize> {
+    prfls
+        .iter()
+        .position(|prfl| prfl.get_name().to_lowercase() == name.to_lowercase())
+}
+
+fn get_profile<'a>(prfls: &'a mut [Profile], name: &str) -> Result<&'a mut Profile, Errors> {
+    let idx = find_profile(prfls, name).ok_or(Errors::not_found(format!("profile \"{}\"", name)))?;
+
+    Ok(&mut prfls[idx])
+}
+
+///
+///Adds the URLs in `text` (see [parse_url_lines]) to `prfl`,
+///recording for each line whether it was added or skipped (and why).
+///
+fn add_lines(prfl: &mut Profile, text: &str, records: &mut Records) {
+    let (pairs, invalid) = parse_url_lines(text);
+
+    for pair in pairs {
+        match prfl.add_pair(pair.clone()) {
+            Ok(()) => records.push(
+                change_values("added", prfl, prfl.find_url(&pair.url), Some(&pair), None),
+                format!("added \"{}\"", pair.url),
+            ),
+            Err(_) => records.push(
+                change_values("skipped", prfl, None, Some(&pair), Some("duplicate")),
+                format!("skipped \"{}\" (duplicate)", pair.url),
+            ),
+        }
+    }
+    for err in invalid {
+        let url = match &err {
+            Errors::URLParseError { url, .. } => json!(url),
+            _ => Value::Null,
+        };
+        records.push(
+            vec![json!("skipped"), json!(prfl.get_name()), Value::Null, Value::Null, url, json!("invalid")],
+            format!("skipped: {}", err),
+        );
+    }
+}
+
+///
+///Resolves `tab` to a pair index of `prfl`:
+///either the number shown by `show` (starting from 1), or the URL of the tab.
+///
+fn find_tab(prfl: &Profile, tab: &str) -> Option<usize> {
+    match tab.parse::<usize>() {
+        Ok(num) if (1..=prfl.get_pairs().len()).contains(&num) => Some(num - 1),
+        _ => prfl.find_url(tab),
+    }
+}
+
+/// [find_tab] for every one of `tabs`, failing on the first that is not there
+fn find_tabs(prfl: &Profile, tabs: &[String]) -> Result<Vec<usize>, Errors> {
+    tabs.iter()
+        .map(|tab| {
+            find_tab(prfl, tab)
+                .ok_or_else(|| Errors::not_found(format!("tab \"{}\" in profile \"{}\"", tab, prfl.get_name())))
+        })
+        .collect()
+}
+
+/// Fails if one of `prfls` is called `name` already
+fn check_name_free(prfls: &[Profile], name: &str) -> Result<(), Errors> {
+    match find_profile(prfls, name) {
+        Some(_) => Err(Errors::usage(&format!("there is a profile called \"{}\" already", name))),
+        None => Ok(()),
+    }
+}
+
+/// Saves `new_prfl`, made from the profile called `from`, unless `prfls` have one of its name already
+fn create_profile(prfls: &[Profile], mut new_prfl: Profile, from: &str, action: &str, opts: &Options) -> Result<(), Errors> {
+    check_name_free(prfls, new_prfl.get_name())?;
+    save(&mut new_prfl, opts)?;
+
+    let mut records = Records::report(CREATE_FIELDS);
+    records.push(
+        vec![json!(action), json!(new_prfl.get_name()), json!(from), json!(new_prfl.get_pairs().len())],
+        format!("{} \"{}\" from \"{}\" with {} tab(s)", action, new_prfl.get_name(), from, new_prfl.get_pairs().len()),
+    );
+    records.print(opts);
+
+    Ok(())
+}
+
+///
+///The stale tabs (see [Profile::stale_pairs]) as of `now`, of the profile called `name` or else of all of them,
+///by profile and in the order of the profile
+///
+fn stale_tabs<'a>(prfls: &'a [Profile], name: Option<&str>, now: NaiveDateTime) -> Result<Vec<(&'a Profile, usize)>, Errors> {
+    let prfls: Vec<&Profile> = match name {
+        Some(name) => {
+            let idx = find_profile(prfls, name).ok_or(Errors::not_found(format!("profile \"{}\"", name)))?;
+            vec![&prfls[idx]]
+        },
+        None => tree_order(prfls),
+    };
+
+    Ok(prfls
+        .into_iter()
+        .flat_map(|prfl| prfl.stale_pairs(now).into_iter().map(move |idx| (prfl, idx)))
+        .collect())
+}
+
+///
+///Saves the profiles split off `prfl` (see [Profile::split_off]) and then `prfl` itself.
+///If a save fails, the new profiles saved so far are removed again, so no tab is in two profiles.
+///
+fn save_split(prfl: &mut Profile, new_prfls: Vec<Profile>, records: &mut Records, opts: &Options) -> Result<(), Errors> {
+    let mut saved: Vec<Profile> = Vec::new();
+    let result = new_prfls
+        .into_iter()
+        .try_for_each(|mut new_prfl| {
+            save(&mut new_prfl, opts)?;
+            saved.push(new_prfl);
+            Ok(())
+        })
+        .and_then(|_| save(prfl, opts));
+    if let Err(e) = result {
+        for new_prfl in &saved {
+            // the error that stopped the split is the one to report
+            let _ = delete_profile(new_prfl);
+        }
+        return Err(e);
+    }
+
+    for new_prfl in &saved {
+        for (idx, pair) in new_prfl.get_pairs().iter().enumerate() {
+            records.push(
+                change_values("moved", new_prfl, Some(idx), Some(pair), None),
+                format!("moved \"{}\" to \"{}\"", pair.title, new_prfl.get_name()),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Runs `cmd`, printing its results as requested by `opts`
+pub fn run(cmd: Command, opts: &Options) -> Result<(), Errors> {
+    match cmd {
+        Command::List => {
+            // in the order the TUI shows them, with all groups open
+            let mut prfls = load_profiles(opts)?;
+            sort_profiles(&mut prfls, read_settings().unwrap_or_default().profile_sort);
+
+            let mut records = Records::data(PROFILE_FIELDS);
+            for prfl in tree_order(&prfls) {
+                let name = match prfl.get_group() {
+                    "" => prfl.get_name().to_string(),
+                    group => format!("{}/{}", group, prfl.get_name()),
+                };
+
+                records.push(
+                    vec![
+                        json!(prfl.get_name()),
+                        json!(prfl.get_id()),
+                        json!(prfl.get_group()),
+                        json!(prfl.get_pairs().len()),
+                        json!(format_time(prfl.get_time_created())),
+                        json!(format_time(prfl.get_time_last_visited())),
+                        json!(prfl.get_file_name()),
+                        json!(prfl.get_tags()),
+                        json!(prfl.get_note()),
+                        json!(prfl.is_template()),
+                    ],
+                    match prfl.is_template() {
+                        true => format!("{} | {} tabs | {} | template", name, prfl.get_pairs().len(), prfl.get_time_last_visited()),
+                        false => format!("{} | {} tabs | {}", name, prfl.get_pairs().len(), prfl.get_time_last_visited()),
+                    },
+                );
+            }
+            records.print(opts);
+        },
+
+        Command::Show { profile } => {
+            let mut prfls = load_profiles(opts)?;
+            let prfl = get_profile(&mut prfls, &profile)?;
+
+            let mut records = Records::data(TAB_FIELDS);
+            for (idx, pair) in prfl.get_pairs().iter().enumerate() {
+                let section = match pair.get_section() {
+                    "" => String::new(),
+                    section => format!("[{}] ", section),
+                };
+                records.push(tab_values(prfl, idx, pair), format!("{:>3}. {}{} | {}", idx + 1, section, pair.title, pair.url));
+            }
+            records.print(opts);
+        },
+
+        Command::Add { profile, url, title, section, template } => {
+            let url = validate_url(&url)?;
+            if template && placeholders(&url).is_empty() {
+                return Err(Errors::NoPlaceholderError { url });
+            }
+            let kind = match template {
+                true => TabKind::Template,
+                false => TabKind::Page,
+            };
+            let title = title.unwrap_or_else(|| url.clone());
+
+            let mut prfls = load_profiles(opts)?;
+            let mut new_prfl;
+            let prfl = match find_profile(&prfls, &profile) {
+                Some(idx) => &mut prfls[idx],
+                None => {
+                    opts.log(&format!("creating new profile \"{}\"", profile));
+                    new_prfl = Profile::builder().add_name(&profile).build();
+                    &mut new_prfl
+                },
+            };
+
+            let pair = URLTitlePair::new(&url, &title)
+                .with_section(section.unwrap_or_default())
+                .with_kind(kind);
+            prfl.add_pair(pair.clone())?;
+            save(prfl, opts)?;
+
+            let mut records = Records::report(CHANGE_FIELDS);
+            // the sort mode decides where it went
+            records.push(
+                change_values("added", prfl, prfl.find_url(&pair.url), Some(&pair), None),
+                format!("added \"{}\" to \"{}\"", pair.title, prfl.get_name()),
+            );
+            records.print(opts);
+        },
+
+        Command::AddStdin { profile } => {
+            let mut text = String::new();
+            std::io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|source| Errors::FSReadError { path: String::from("<stdin>"), source })?;
+
+            let mut prfls = load_profiles(opts)?;
+            let mut new_prfl;
+            let prfl = match find_profile(&prfls, &profile) {
+                Some(idx) => &mut prfls[idx],
+                None => {
+                    opts.log(&format!("creating new profile \"{}\"", profile));
+                    new_prfl = Profile::builder().add_name(&profile).build();
+                    &mut new_prfl
+                },
+            };
+
+            let mut records = Records::report(CHANGE_FIELDS);
+            add_lines(prfl, &text, &mut records);
+            save(prfl, opts)?;
+
+            records.print(opts);
+        },
+
+        Command::Rm { profile, tab } => {
+            let mut prfls = load_profiles(opts)?;
+            let prfl = get_profile(&mut prfls, &profile)?;
+
+            let mut records = Records::report(CHANGE_FIELDS);
+            match tab {
+                Some(tab) => {
+                    let idx = find_tab(prfl, &tab)
+                        .ok_or_else(|| Errors::not_found(format!("tab \"{}\" in profile \"{}\"", tab, prfl.get_name())))?;
+                    let pair = prfl.remove_pair(idx).expect("find_tab returns valid indices");
+                    save(prfl, opts)?;
+
+                    records.push(
+                        change_values("removed", prfl, Some(idx), Some(&pair), None),
+                        format!("removed \"{}\" from \"{}\"", pair.title, prfl.get_name()),
+                    );
+                },
+                None => {
+                    delete_profile(prfl)?;
+
+                    records.push(
+                        change_values("deleted", prfl, None, None, None),
+                        format!("deleted profile \"{}\"", prfl.get_name()),
+                    );
+                },
+            }
+            records.print(opts);
+        },
+
+        Command::Open { profile, mode, browser, params } => {
+            let mut prfls = load_profiles(opts)?;
+            let prfl = get_profile(&mut prfls, &profile)?;
+
+            let browser = browser
+                .map(|name| Browser::from_name(&name))
+                .unwrap_or_default();
+            let idxs = prfl.active_pairs();
+            let tabs: Vec<(&Profile, usize)> = idxs.iter().map(|&idx| (&*prfl, idx)).collect();
+            check_params(&tabs, &params)?;
+            opts.log(&format!("opening {} tab(s) with {:?} ({:?})", idxs.len(), browser, mode));
+
+            launch_profile(prfl, &params, &browser, mode)?;
+            prfl.record_opened(&idxs, chrono::Utc::now().naive_utc());
+            prfl.remember_values(&idxs, &params);
+            save(prfl, opts)?;
+
+            let mut records = Records::report(OPEN_FIELDS);
+            records.push(
+                vec![
+                    json!("opened"),
+                    json!(prfl.get_name()),
+                    json!(idxs.len()),
+                    json!(format!("{:?}", browser)),
+                    json!(format!("{:?}", mode)),
+                ],
+                format!("opened {} tabs of \"{}\"", idxs.len(), prfl.get_name()),
+            );
+            records.print(opts);
+        },
+
+        Command::OpenGroup { group, mode, browser, params } => {
+            let mut prfls = load_profiles(opts)?;
+            sort_profiles(&mut prfls, read_settings().unwrap_or_default().profile_sort);
+
+            let group = normalize_group(&group);
+            let in_group: Vec<&Profile> = tree_order(&prfls)
+                .into_iter()
+                .filter(|prfl| !group.is_empty() && group_is_in(prfl.get_group(), &group))
+                .collect();
+            if in_group.is_empty() {
+                return Err(Errors::not_found(format!("group \"{}\"", group)));
+            }
+
+            let browser = browser
+                .map(|name| Browser::from_name(&name))
+                .unwrap_or_default();
+            let tabs: Vec<(&Profile, usize)> = in_group
+                .iter()
+                .flat_map(|&prfl| prfl.active_pairs().into_iter().map(move |idx| (prfl, idx)))
+                .collect();
+            check_params(&tabs, &params)?;
+            opts.log(&format!("opening {} tab(s) of {} profile(s) with {:?} ({:?})", tabs.len(), in_group.len(), browser, mode));
+
+            // the tabs in no section all in one window, every section in one of its own
+            launch_tabs(&tabs, &params, &browser, mode)?;
+
+            let mut records = Records::report(OPEN_FIELDS);
+            let ids: Vec<usize> = in_group.iter().map(|prfl| prfl.get_id()).collect();
+            let at = chrono::Utc::now().naive_utc();
+            for id in ids {
+                let Some(prfl) = prfls.iter_mut().find(|prfl| prfl.get_id() == id) else {
+                    continue;
+                };
+                let idxs = prfl.active_pairs();
+                prfl.record_opened(&idxs, at);
+                prfl.remember_values(&idxs, &params);
+                save(prfl, opts)?;
+
+                records.push(
+                    vec![
+                        json!("opened"),
+                        json!(prfl.get_name()),
+                        json!(idxs.len()),
+                        json!(format!("{:?}", browser)),
+                        json!(format!("{:?}", mode)),
+                    ],
+                    format!("opened {} tabs of \"{}\"", idxs.len(), prfl.get_name()),
+                );
+            }
+            records.print(opts);
+        },
+
+        Command::Group { profile, group } => {
+            let mut prfls = load_profiles(opts)?;
+            let prfl = get_profile(&mut prfls, &profile)?;
+
+            let previous = prfl.get_group().to_string();
+            prfl.set_group(&group);
+            save(prfl, opts)?;
+
+            let mut records = Records::report(GROUP_FIELDS);
+            records.push(
+                vec![json!("grouped"), json!(prfl.get_name()), json!(prfl.get_group()), json!(previous)],
+                match prfl.get_group() {
+                    "" => format!("\"{}\" is in no group now", prfl.get_name()),
+                    group => format!("moved \"{}\" to {}/", prfl.get_name(), group),
+                },
+            );
+            records.print(opts);
+        },
+
+        Command::Merge { profile, into } => {
+            let mut prfls = load_profiles(opts)?;
+            let from = find_profile(&prfls, &profile).ok_or(Errors::not_found(format!("profile \"{}\"", profile)))?;
+            let to = find_profile(&prfls, &into).ok_or(Errors::not_found(format!("profile \"{}\"", into)))?;
+            if from == to {
+                return Err(Errors::usage("a profile cannot be merged into itself"));
+            }
+
+            let from = prfls.remove(from);
+            let to = get_profile(&mut prfls, &into)?;
+            let mut records = Records::report(CHANGE_FIELDS);
+            for pair in from.get_pairs() {
+                let action = match to.find_url(&pair.url) {
+                    Some(_) => "merged",
+                    None => "added",
+                };
+                records.push(
+                    change_values(action, to, None, Some(pair), None),
+                    format!("{} \"{}\" to \"{}\"", action, pair.title, to.get_name()),
+                );
+            }
+
+            // only once the tabs are safe in the other profile
+            let file_name = from.get_file_name().map(str::to_string);
+            to.merge_profile(from);
+            save(to, opts)?;
+            if let Some(file_name) = file_name {
+                delete_profile_file(&file_name)?;
+            }
+
+            records.print(opts);
+        },
+
+        Command::Split { profile, by } => {
+            let mut prfls = load_profiles(opts)?;
+            let idx = find_profile(&prfls, &profile).ok_or(Errors::not_found(format!("profile \"{}\"", profile)))?;
+
+            let name = prfls[idx].get_name().clone();
+            let urls: Vec<(String, Vec<String>)> = prfls[idx]
+                .split_plan(by)
+                .into_iter()
+                .map(|(key, idxs)| (format!("{} - {}", name, key), idxs.iter().map(|&i| prfls[idx].get_pairs()[i].url.clone()).collect()))
+                .collect();
+            if urls.is_empty() {
+                return Err(Errors::not_found(format!("tab of \"{}\" to split off", name)));
+            }
+            for (new_name, _) in &urls {
+                check_name_free(&prfls, new_name)?;
+            }
+
+            let prfl = &mut prfls[idx];
+            let new_prfls: Vec<Profile> = urls
+                .into_iter()
+                .map(|(new_name, urls)| {
+                    let idxs: Vec<usize> = urls.iter().filter_map(|url| prfl.find_url(url)).collect();
+                    prfl.split_off(&idxs, &new_name)
+                })
+                .collect();
+
+            let mut records = Records::report(CHANGE_FIELDS);
+            save_split(prfl, new_prfls, &mut records, opts)?;
+
+            records.print(opts);
+        },
+
+        Command::SplitTabs { profile, tabs, name } => {
+            let mut prfls = load_profiles(opts)?;
+            check_name_free(&prfls, &name)?;
+            let prfl = get_profile(&mut prfls, &profile)?;
+
+            let idxs = find_tabs(prfl, &tabs)?;
+            let new_prfl = prfl.split_off(&idxs, &name);
+            let mut records = Records::report(CHANGE_FIELDS);
+            save_split(prfl, vec![new_prfl], &mut records, opts)?;
+
+            records.print(opts);
+        },
+
+        Command::Transfer { profile, tabs, to, keep } => {
+            let mut prfls = load_profiles(opts)?;
+            let from = find_profile(&prfls, &profile).ok_or(Errors::not_found(format!("profile \"{}\"", profile)))?;
+            let target = find_profile(&prfls, &to).ok_or(Errors::not_found(format!("profile \"{}\"", to)))?;
+            if from == target {
+                return Err(Errors::usage("the tabs are already in this profile"));
+            }
+
+            let idxs = find_tabs(&prfls[from], &tabs)?;
+            // the pairs keep t_created, the launch history, tags and notes
+            let pairs: Vec<URLTitlePair> = match keep {
+                true => idxs.iter().map(|&idx| prfls[from].get_pairs()[idx].clone()).collect(),
+                false => prfls[from].remove_pairs(&idxs),
+            };
+
+            let mut records = Records::report(CHANGE_FIELDS);
+            for pair in &pairs {
+                let action = match (prfls[target].find_url(&pair.url), keep) {
+                    (Some(_), _) => "merged",
+                    (None, true) => "copied",
+                    (None, false) => "moved",
+                };
+                records.push(
+                    change_values(action, &prfls[target], None, Some(pair), None),
+                    format!("{} \"{}\" to \"{}\"", action, pair.title, prfls[target].get_name()),
+                );
+            }
+
+            prfls[target].merge_pairs(pairs);
+            save(&mut prfls[target], opts)?;
+            if !keep {
+                save(&mut prfls[from], opts)?;
+            }
+
+            records.print(opts);
+        },
+
+        Command::Diff { profile, other, with, take } => {
+            let mut prfls = load_profiles(opts)?;
+            let idx = find_profile(&prfls, &profile).ok_or(Errors::not_found(format!("profile \"{}\"", profile)))?;
+            let (new, new_label) = match (other, with) {
+                (_, Some(file)) => (read_profile_to_compare(&file, prfls[idx].get_name())?, file),
+                (Some(other), None) => {
+                    let other_idx = find_profile(&prfls, &other).ok_or(Errors::not_found(format!("profile \"{}\"", other)))?;
+                    (prfls[other_idx].clone(), prfls[other_idx].get_name().clone())
+                },
+                (None, None) => return Err(Errors::usage("diff needs another profile or --with <file>")),
+            };
+            let old = &mut prfls[idx];
+            let rows = diff_profiles(old, &new);
+
+            match opts.format {
+                OutputFormat::Plain => println!("{}", unified_diff(old, &new, &rows, old.get_name(), &new_label)),
+                _ => {
+                    let mut records = Records::data(DIFF_FIELDS);
+                    for row in rows.iter().filter(|row| row.kind != DiffKind::Same) {
+                        let old_pair = row.old_idx.map(|idx| &old.get_pairs()[idx]);
+                        let new_pair = row.new_idx.map(|idx| &new.get_pairs()[idx]);
+                        let url = new_pair.or(old_pair).map(|pair| &pair.url);
+                        records.push(
+                            vec![
+                                json!(row.kind.label()),
+                                json!(url),
+                                json!(old_pair.map(|pair| &pair.title)),
+                                json!(new_pair.map(|pair| &pair.title)),
+                                json!(row.old_idx.map(|idx| idx + 1)),
+                                json!(row.new_idx.map(|idx| idx + 1)),
+                            ],
+                            String::new(),
+                        );
+                    }
+                    records.print(opts);
+                },
+            }
+
+            if take {
+                let count = take_changes(old, &new, &rows);
+                if !count.is_empty() {
+                    save(old, opts)?;
+                }
+                opts.log(&format!(
+                    "took {} added, {} removed and {} retitled tab(s) into \"{}\"",
+                    count.added, count.removed, count.retitled, old.get_name()
+                ));
+            }
+        },
+
+        Command::Expire { profile, days } => {
+            let mut prfls = load_profiles(opts)?;
+            let prfl = get_profile(&mut prfls, &profile)?;
+
+            prfl.set_expire_after(days);
+            save(prfl, opts)?;
+
+            let mut records = Records::report(EXPIRE_FIELDS);
+            records.push(
+                vec![json!("expire"), json!(prfl.get_name()), json!(days)],
+                match days {
+                    Some(days) => format!("tabs of \"{}\" unopened for {} day(s) are stale", prfl.get_name(), days),
+                    None => format!("tabs of \"{}\" never go stale", prfl.get_name()),
+                },
+            );
+            records.print(opts);
+        },
+
+        Command::Stale { profile } => {
+            let prfls = load_profiles(opts)?;
+            let now = chrono::Utc::now().naive_utc();
+            let stale = stale_tabs(&prfls, profile.as_deref(), now)?;
+
+            let mut records = Records::data(STALE_FIELDS);
+            for (prfl, idx) in stale {
+                let pair = &prfl.get_pairs()[idx];
+                let days = (now - pair.get_time_last_used()).num_days();
+                records.push(
+                    vec![
+                        json!(prfl.get_name()),
+                        json!(idx + 1),
+                        json!(pair.title),
+                        json!(pair.url),
+                        json!(pair.get_time_last_opened().map(format_time)),
+                        json!(days),
+                    ],
+                    format!("{} | {:>3}. {} | {} | unused for {} days", prfl.get_name(), idx + 1, pair.title, pair.url, days),
+                );
+            }
+            records.print(opts);
+        },
+
+        Command::Archive { profile } => {
+            let mut prfls = load_profiles(opts)?;
+            let now = chrono::Utc::now().naive_utc();
+            let ids: Vec<usize> = stale_tabs(&prfls, profile.as_deref(), now)?
+                .into_iter()
+                .map(|(prfl, _)| prfl.get_id())
+                .dedup()
+                .collect();
+
+            let mut records = Records::report(CHANGE_FIELDS);
+            for prfl in prfls.iter_mut().filter(|prfl| ids.contains(&prfl.get_id())) {
+                let idxs = prfl.stale_pairs(now);
+                let urls: Vec<String> = idxs.iter().map(|&idx| prfl.get_pairs()[idx].url.clone()).collect();
+                prfl.archive_pairs(&idxs);
+                save(prfl, opts)?;
+
+                for url in urls {
+                    let idx = prfl.find_url(&url);
+                    let pair = idx.map(|idx| &prfl.get_pairs()[idx]);
+                    records.push(
+                        change_values("archived", prfl, idx, pair, None),
+                        format!("archived \"{}\" of \"{}\"", pair.map(|pair| pair.title.as_str()).unwrap_or(&url), prfl.get_name()),
+                    );
+                }
+            }
+            records.print(opts);
+        },
+
+        Command::Unarchive { profile, tabs } => {
+            let mut prfls = load_profiles(opts)?;
+            let prfl = get_profile(&mut prfls, &profile)?;
+
+            let idxs: Vec<usize> = match tabs.is_empty() {
+                true => (0..prfl.get_pairs().len()).filter(|&idx| prfl.get_pairs()[idx].is_archived()).collect(),
+                false => find_tabs(prfl, &tabs)?,
+            };
+            let urls: Vec<String> = idxs
+                .iter()
+                .map(|&idx| &prfl.get_pairs()[idx])
+                .filter(|pair| pair.is_archived())
+                .map(|pair| pair.url.clone())
+                .collect();
+            if prfl.unarchive_pairs(&idxs) > 0 {
+                save(prfl, opts)?;
+            }
+
+            let mut records = Records::report(CHANGE_FIELDS);
+            for url in urls {
+                let idx = prfl.find_url(&url);
+                let pair = idx.map(|idx| &prfl.get_pairs()[idx]);
+                let section = pair.map(|pair| pair.get_section()).unwrap_or_default();
+                records.push(
+                    change_values("unarchived", prfl, idx, pair, None),
+                    format!(
+                        "brought \"{}\" of \"{}\" back to {}",
+                        pair.map(|pair| pair.title.as_str()).unwrap_or(&url),
+                        prfl.get_name(),
+                        match section.is_empty() {
+                            true => String::from("no section"),
+                            false => format!("the section \"{}\"", section),
+                        },
+                    ),
+                );
+            }
+            records.print(opts);
+        },
+
+        Command::Template { profile, template } => {
+            let mut prfls = load_profiles(opts)?;
+            let prfl = get_profile(&mut prfls, &profile)?;
+
+            prfl.set_template(template);
+            save(prfl, opts)?;
+
+            let mut records = Records::report(TEMPLATE_FIELDS);
+            records.push(
+                vec![json!("template"), json!(prfl.get_name()), json!(template)],
+                match template {
+                    true => format!("\"{}\" is a template", prfl.get_name()),
+                    false => format!("\"{}\" is no template any more", prfl.get_name()),
+                },
+            );
+            records.print(opts);
+        },
+
+        Command::FromTemplate { name, from, params } => {
+            let mut prfls = load_profiles(opts)?;
+            let template = get_profile(&mut prfls, &from)?;
+
+            let missing: Vec<String> = template
+                .template_placeholders()
+                .into_iter()
+                .filter(|placeholder| !params.iter().any(|(name, _)| name == placeholder))
+                .collect();
+            if !missing.is_empty() {
+                return Err(Errors::usage(&format!(
+                    "\"{}\" needs {}",
+                    template.get_name(),
+                    missing.iter().map(|name| format!("--param {}=<value>", name)).join(" ")
+                )));
+            }
+
+            let new_prfl = template.from_template(&name, &params);
+            let from = template.get_name().clone();
+            create_profile(&prfls, new_prfl, &from, "created", opts)?;
+        },
+
+        Command::Clone { profile, name } => {
+            let mut prfls = load_profiles(opts)?;
+            let prfl = get_profile(&mut prfls, &profile)?;
+
+            let new_prfl = prfl.duplicate(&name);
+            let from = prfl.get_name().clone();
+            create_profile(&prfls, new_prfl, &from, "cloned", opts)?;
+        },
+
+        Command::Import { file, into } => {
+            let is_json = Path::new(&file)
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+            let mut records = Records::report(CHANGE_FIELDS);
+            match is_json {
+                // into the profile of the same name (or `into`), so that importing an export again adds nothing
+                true => {
+                    let mut prfls = load_profiles(opts)?;
+                    let mut touched: Vec<usize> = Vec::new();
+
+                    for imported in import_json(&file)? {
+                        let name = into.clone().unwrap_or_else(|| imported.get_name().clone());
+                        let idx = match find_profile(&prfls, &name) {
+                            Some(idx) => idx,
+                            None => {
+                                opts.log(&format!("creating new profile \"{}\"", name));
+                                prfls.push(Profile::builder().add_name(&name).build());
+                                prfls.len() - 1
+                            },
+                        };
+                        let prfl = &mut prfls[idx];
+
+                        for pair in imported.get_pairs() {
+                            let action = match prfl.find_url(&pair.url) {
+                                Some(_) => "merged",
+                                None => "added",
+                            };
+                            records.push(
+                                change_values(action, prfl, None, Some(pair), None),
+                                format!("{} \"{}\" to \"{}\"", action, pair.title, prfl.get_name()),
+                            );
+                        }
+                        prfl.merge_profile(imported);
+                        if !touched.contains(&idx) {
+                            touched.push(idx);
+                        }
+                    }
+
+                    for idx in touched {
+                        save(&mut prfls[idx], opts)?;
+                    }
+                },
+                false => {
+                    let text = std::fs::read_to_string(&file)
+                        .map_err(|source| Errors::FSReadError { path: file.clone(), source })?;
+
+                    let name = into.unwrap_or_else(|| {
+                        Path::new(&file)
+                            .file_stem()
+                            .map(|stem| stem.to_string_lossy().to_string())
+                            .unwrap_or(file.clone())
+                    });
+
+                    let mut prfls = load_profiles(opts)?;
+                    let mut new_prfl;
+                    let prfl = match find_profile(&prfls, &name) {
+                        Some(idx) => &mut prfls[idx],
+                        None => {
+                            opts.log(&format!("creating new profile \"{}\"", name));
+                            new_prfl = Profile::builder().add_name(&name).build();
+                            &mut new_prfl
+                        },
+                    };
+
+                    add_lines(prfl, &text, &mut records);
+                    save(prfl, opts)?;
+                },
+            }
+            records.print(opts);
+        },
+
+        Command::Export { profile, to, output } => {
+            let mut prfls = load_profiles(opts)?;
+
+            let scope = match profile {
+                Some(profile) => ExportScope::Profile(get_profile(&mut prfls, &profile)?),
+                None => ExportScope::All(&prfls),
+            };
+
+            match output {
+                Some(path) => {
+                    export_to_file(scope, to, &path)?;
+
+                    let count = match scope {
+                        ExportScope::Profile(_) => 1,
+                        ExportScope::All(prfls) => prfls.len(),
+                    };
+                    let mut records = Records::report(EXPORT_FIELDS);
+                    records.push(
+                        vec![json!("exported"), json!(path), json!(to.extension()), json!(count)],
+                        format!("exported {} profile(s) to {}", count, path),
+                    );
+                    records.print(opts);
+                },
+                // the document itself is the output, whatever the --format
+                None => println!("{}", export(scope, to)?),
+            }
+        },
+
+        Command::Search { text } => {
+            let query = Query::parse(&text)?;
+            let prfls = load_profiles(opts)?;
+
+            let mut records = Records::data(SEARCH_FIELDS);
+            for hit in search_profiles(&prfls, &query) {
+                let prfl = &prfls[hit.prfl_idx];
+                let pair = &prfl.get_pairs()[hit.tab_idx];
+
+                records.push(
+                    search_values(prfl, pair, &hit),
+                    format!("{} #{} | {} | {} (score {})", prfl.get_name(), hit.tab_idx + 1, pair.title, pair.url, hit.score),
+                );
+            }
+
+            if records.rows.is_empty() {
+                return Err(Errors::not_found(format!("tabs matching \"{}\"", text)));
+            }
+            records.print(opts);
+        },
+
+        Command::Help => println!("{}", USAGE),
+    }
+
+    Ok(())
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn parse(line: &str) -> Result<Option<Command>, Errors> {
+        parse_args(&args(line)).map(|parsed| parsed.map(|(cmd, _)| cmd))
+    }
+
+    fn options(line: &str) -> Options {
+        parse_args(&args(line)).unwrap().expect("a command is given").1
+    }
+
+    #[test]
+    fn no_arguments_start_the_tui() {
+        assert!(matches!(parse(""), Ok(None)));
+    }
+
+    #[test]
+    fn parses_commands_and_their_flags() {
+        assert_eq!(parse("ls").unwrap(), Some(Command::List));
+        assert_eq!(
+            parse("add Work https://a.com --title=Home").unwrap(),
+            Some(Command::Add { profile: "Work".to_string(), url: "https://a.com".to_string(), title: Some("Home".to_string()), section: None, template: false })
+        );
+        assert_eq!(
+            parse("open Work --normal --browser firefox").unwrap(),
+            Some(Command::Open { profile: "Work".to_string(), mode: LaunchMode::Normal, browser: Some("firefox".to_string()), params: Vec::new() })
+        );
+        assert_eq!(
+            parse("export -o out.md --to md").unwrap(),
+            Some(Command::Export { profile: None, to: ExportFormat::Markdown, output: Some("out.md".to_string()) })
+        );
+        assert_eq!(parse("find rust  book").unwrap(), Some(Command::Search { text: "rust book".to_string() }));
+    }
+
+    #[test]
+    fn a_double_dash_ends_the_flags() {
+        assert_eq!(
+            parse("rm Work -- -1").unwrap(),
+            Some(Command::Rm { profile: "Work".to_string(), tab: Some("-1".to_string()) })
+        );
+    }
+
+    #[test]
+    fn rejects_bad_usage() {
+        for line in [
+            "frobnicate",
+            "ls extra",
+            "show",
+            "add Work",
+            "add Work https://a.com --title",
+            "open Work --normal --private",
+            "open Work --verbose=yes",
+            "export --to xml",
+            "search",
+        ] {
+            assert!(matches!(parse(line), Err(Errors::CLIUsageError { .. })), "{}", line);
+        }
+    }
+
+    #[test]
+    fn options_go_anywhere_before_a_double_dash() {
+        assert_eq!(options("ls"), Options::default());
+        assert_eq!(options("-q ls --format tsv"), Options { format: OutputFormat::Tsv, verbosity: Verbosity::Quiet });
+        assert_eq!(options("show Work --format=JSON -v").verbosity, Verbosity::Verbose);
+        assert_eq!(
+            parse_args(&args("rm Work -- -v")).unwrap(),
+            Some((Command::Rm { profile: "Work".to_string(), tab: Some("-v".to_string()) }, Options::default()))
+        );
+
+        assert!(matches!(parse("ls --format"), Err(Errors::CLIUsageError { .. })));
+        assert!(matches!(parse("ls --format=yaml"), Err(Errors::CLIUsageError { .. })));
+        // options alone do not start the TUI
+        assert!(matches!(parse("-q"), Ok(None)));
+    }
+
+    #[test]
+    fn errors_have_exit_codes_by_kind() {
+        let io_error = || std::io::Error::other("denied");
+
+        assert_eq!(Errors::usage("no command").exit_code(), exit_code::USAGE);
+        assert_eq!(Errors::not_found("profile \"Work\"".to_string()).exit_code(), exit_code::NOT_FOUND);
+        assert_eq!(
+            Errors::ParseJSONError { path: "a.json".to_string(), source: serde_json::from_str::<u8>("x").unwrap_err() }.exit_code(),
+            exit_code::PARSE
+        );
+        assert_eq!(Errors::FSWriteError { path: "a.toml".to_string(), source: io_error() }.exit_code(), exit_code::IO);
+        assert_eq!(
+            Errors::PairAlreadyExistsError { url: "https://a.com".to_string(), title: "A".to_string() }.exit_code(),
+            exit_code::ALREADY_EXISTS
+        );
+        assert_eq!(Errors::BrowserLaunchError { browser: "firefox".to_string(), source: io_error() }.exit_code(), exit_code::LAUNCH);
+    }
+
+    #[test]
+    fn pasted_lines_skip_duplicates_and_bad_urls() {
+        let mut prfl = Profile::builder().add_name("Work").add_title_url_pair("https://a.com", "A").build();
+        let mut records = Records::report(CHANGE_FIELDS);
+
+        add_lines(&mut prfl, "https://a.com\nhttps://b.com\tB\nnope", &mut records);
+        let lines: Vec<&str> = records.rows.iter().map(|(_, plain)| plain.as_str()).collect();
+        assert_eq!(lines, vec![
+            "skipped \"https://a.com\" (duplicate)",
+            "added \"https://b.com\"",
+            "skipped: line 3: \"nope\" is not a valid URL",
+        ]);
+        assert_eq!(prfl.get_pairs().len(), 2);
+    }
+
+    #[test]
+    fn parses_templates_and_clones() {
+        assert_eq!(parse("template Work").unwrap(), Some(Command::Template { profile: "Work".to_string(), template: true }));
+        assert_eq!(parse("template Work off").unwrap(), Some(Command::Template { profile: "Work".to_string(), template: false }));
+        assert_eq!(
+            parse("new Rust --from Search --param q=rust+book --param=page=2").unwrap(),
+            Some(Command::FromTemplate {
+                name: "Rust".to_string(),
+                from: "Search".to_string(),
+                params: vec![("q".to_string(), "rust+book".to_string()), ("page".to_string(), "2".to_string())],
+            })
+        );
+        assert_eq!(
+            parse("clone Work --name Work2").unwrap(),
+            Some(Command::Clone { profile: "Work".to_string(), name: "Work2".to_string() })
+        );
+
+        for line in ["template Work maybe", "new Rust", "new Rust --from Search --param q", "new Rust --from Search --param =x", "clone Work"] {
+            assert!(matches!(parse(line), Err(Errors::CLIUsageError { .. })), "{}", line);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::{env, process::{Command, Stdio}};
+
+use crate::{debug_println, Errors};
+
+//
+//
+//Reads the system clipboard through whatever helper program is installed
+//
+//
+
+
+
+/// Set this to a command printing the clipboard contents (e.g. `xclip -o -selection primary`)
+/// to use it instead of the helpers below
+pub const CLIPBOARD_ENV_VAR: &str = "ITR_CLIPBOARD_CMD";
+
+/// Tried in this order; the first one that runs successfully wins
+const PASTE_HELPERS: [&[&str]; 5] = [
+    &["wl-paste", "--no-newline"],
+    &["xclip", "-selection", "clipboard", "-o"],
+    &["xsel", "--clipboard", "--output"],
+    &["pbpaste"],
+    &["powershell.exe", "-NoProfile", "-Command", "Get-Clipboard"],
+];
+
+
+
+fn run_helper(cmd: &[&str]) -> Option<String> {
+    let (program, args) = cmd.split_first()?;
+
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    debug_println!("CLIPBOARD: {:?} -> {:?}", cmd, output.status);
+
+    match output.status.success() {
+        true => Some(String::from_utf8_lossy(&output.stdout).into_owned()),
+        false => None,
+    }
+}
+
+
+///
+///Returns the text currently in the system clipboard,
+///read with the command in [CLIPBOARD_ENV_VAR] if set,
+///or else with the first of `wl-paste`, `xclip`, `xsel`, `pbpaste`, `powershell.exe` that works.
+///
+pub fn read_clipboard() -> Result<String, Errors> {
+    if let Ok(cmd) = env::var(CLIPBOARD_ENV_VAR) {
+        let cmd: Vec<&str> = cmd.split_whitespace().collect();
+
+        return run_helper(&cmd).ok_or(Errors::ClipboardReadError { command: Some(cmd.join(" ")) });
+    }
+
+    PASTE_HELPERS
+        .iter()
+        .find_map(|cmd| run_helper(cmd))
+        .ok_or(Errors::ClipboardReadError { command: None })
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_env_var_replaces_the_helpers() {
+        env::set_var(CLIPBOARD_ENV_VAR, "echo https://a.com");
+        assert_eq!(read_clipboard().unwrap().trim_end(), "https://a.com");
+
+        env::set_var(CLIPBOARD_ENV_VAR, "false");
+        let err = read_clipboard().unwrap_err();
+        assert!(matches!(&err, Errors::ClipboardReadError { command: Some(command) } if command == "false"));
+        assert_eq!(err.to_string(), "could not read the clipboard with `false`");
+
+        env::remove_var(CLIPBOARD_ENV_VAR);
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{urls::normalize_url, Profile};
+
+//
+//
+//What changed between two PROFILES, or two versions of one:
+//tabs are the same tab if their normalized URLs are the same
+//
+//
+
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Same,
+    /// only in the new profile
+    Added,
+    /// only in the old profile
+    Removed,
+    /// in both, with another title in the new one
+    Retitled,
+}
+
+impl DiffKind {
+    /// What a row of this kind starts with, like in a unified diff
+    pub fn mark(self) -> char {
+        match self {
+            DiffKind::Same => ' ',
+            DiffKind::Added => '+',
+            DiffKind::Removed => '-',
+            DiffKind::Retitled => '~',
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DiffKind::Same => "same",
+            DiffKind::Added => "added",
+            DiffKind::Removed => "removed",
+            DiffKind::Retitled => "retitled",
+        }
+    }
+}
+
+
+/// One tab of either profile, by its index in the old and / or the new one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffRow {
+    pub kind: DiffKind,
+    pub old_idx: Option<usize>,
+    pub new_idx: Option<usize>,
+}
+
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DiffCount {
+    pub added: usize,
+    pub removed: usize,
+    pub retitled: usize,
+}
+
+impl DiffCount {
+    pub fn is_empty(&self) -> bool {
+        self.added + self.removed + self.retitled == 0
+    }
+}
+
+
+
+///
+///Every tab of `old` and `new`, in the order of `new`;
+///a tab only in `old` comes right before the tab that followed it there, if `new` has that one.
+///
+pub fn diff_profiles(old: &Profile, new: &Profile) -> Vec<DiffRow> {
+    let old_pairs = old.get_pairs();
+    let mut old_by_url: HashMap<String, usize> = HashMap::with_capacity(old_pairs.len());
+    for (idx, pair) in old_pairs.iter().enumerate() {
+        old_by_url.entry(normalize_url(&pair.url)).or_insert(idx);
+    }
+    let new_urls: HashSet<String> = new.get_pairs().iter().map(|pair| normalize_url(&pair.url)).collect();
+
+    let mut rows = Vec::with_capacity(old_pairs.len().max(new.get_pairs().len()));
+    let mut taken = vec![false; old_pairs.len()];
+    // the old tabs before this one are either taken or listed already
+    let mut old_next = 0;
+
+    for (new_idx, pair) in new.get_pairs().iter().enumerate() {
+        let Some(&old_idx) = old_by_url.get(&normalize_url(&pair.url)) else {
+            rows.push(DiffRow { kind: DiffKind::Added, old_idx: None, new_idx: Some(new_idx) });
+            continue;
+        };
+        if taken[old_idx] {
+            // the same URL twice in `new`
+            rows.push(DiffRow { kind: DiffKind::Added, old_idx: None, new_idx: Some(new_idx) });
+            continue;
+        }
+
+        for idx in old_next..old_idx {
+            if !taken[idx] && !new_urls.contains(&normalize_url(&old_pairs[idx].url)) {
+                taken[idx] = true;
+                rows.push(DiffRow { kind: DiffKind::Removed, old_idx: Some(idx), new_idx: None });
+            }
+        }
+        old_next = old_next.max(old_idx + 1);
+
+        taken[old_idx] = true;
+        let kind = match old_pairs[old_idx].title == pair.title {
+            true => DiffKind::Same,
+            false => DiffKind::Retitled,
+        };
+        rows.push(DiffRow { kind, old_idx: Some(old_idx), new_idx: Some(new_idx) });
+    }
+
+    rows.extend(
+        (0..old_pairs.len())
+            .filter(|&idx| !taken[idx])
+            .map(|idx| DiffRow { kind: DiffKind::Removed, old_idx: Some(idx), new_idx: None })
+    );
+
+    rows
+}
+
+
+pub fn diff_count(rows: &[DiffRow]) -> DiffCount {
+    let mut count = DiffCount::default();
+
+    for row in rows {
+        match row.kind {
+            DiffKind::Same => {},
+            DiffKind::Added => count.added += 1,
+            DiffKind::Removed => count.removed += 1,
+            DiffKind::Retitled => count.retitled += 1,
+        }
+    }
+
+    count
+}
+
+
+///
+///Makes `old` like `new` as far as `rows` go, which are rows of [diff_profiles] of the two:
+///the added tabs are added with everything they know, the removed ones removed and the titles changed.
+///Returns what was taken.
+///
+pub fn take_changes(old: &mut Profile, new: &Profile, rows: &[DiffRow]) -> DiffCount {
+    let mut count = DiffCount::default();
+
+    // titles first, while the indices of `old` still hold
+    for row in rows.iter().filter(|row| row.kind == DiffKind::Retitled) {
+        let (Some(old_idx), Some(new_idx)) = (row.old_idx, row.new_idx) else {
+            continue;
+        };
+        let url = old.get_pairs()[old_idx].url.clone();
+        if old.edit_pair(old_idx, &url, &new.get_pairs()[new_idx].title).is_ok() {
+            count.retitled += 1;
+        }
+    }
+
+    let removed: Vec<usize> = rows
+        .iter()
+        .filter(|row| row.kind == DiffKind::Removed)
+        .filter_map(|row| row.old_idx)
+        .collect();
+    count.removed = old.remove_pairs(&removed).len();
+
+    let added = rows
+        .iter()
+        .filter(|row| row.kind == DiffKind::Added)
+        .filter_map(|row| new.get_pairs().get(row.new_idx?).cloned())
+        .collect();
+    count.added = old.merge_pairs(added).added;
+
+    count
+}
+
+
+///
+///`rows` of [diff_profiles] as a unified diff without context lines, one `title | url` per tab:
+///```ignore
+/// --- old_label
+/// +++ new_label
+/// -Removed Page | https://example.com/gone
+/// +Added Page | https://example.com/new
+///```
+///A changed title is the `-` line of the old title followed by the `+` line of the new one.
+///
+pub fn unified_diff(old: &Profile, new: &Profile, rows: &[DiffRow], old_label: &str, new_label: &str) -> String {
+    let mut lines = vec![format!("--- {}", old_label), format!("+++ {}", new_label)];
+    let line = |mark: char, prfl: &Profile, idx: Option<usize>| {
+        let pair = &prfl.get_pairs()[idx.unwrap_or_default()];
+        format!("{}{} | {}", mark, pair.title, pair.url)
+    };
+
+    for row in rows {
+        match row.kind {
+            DiffKind::Same => {},
+            DiffKind::Added => lines.push(line('+', new, row.new_idx)),
+            DiffKind::Removed => lines.push(line('-', old, row.old_idx)),
+            DiffKind::Retitled => {
+                lines.push(line('-', old, row.old_idx));
+                lines.push(line('+', new, row.new_idx));
+            },
+        }
+    }
+
+    lines.join("\n")
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::URLTitlePair;
+
+    /// A profile of `(url, title)` tabs, oldest first
+    fn profile(tabs: &[(&str, &str)]) -> Profile {
+        let day = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let pairs = tabs
+            .iter()
+            .enumerate()
+            .map(|(n, (url, title))| URLTitlePair::from_save(url.to_string(), title.to_string(), day + chrono::Duration::days(n as i64)))
+            .collect();
+
+        Profile::builder().add_name("Reading").add_many_title_url_pairs(pairs).build()
+    }
+
+    fn kinds(rows: &[DiffRow]) -> Vec<(DiffKind, Option<usize>, Option<usize>)> {
+        rows.iter().map(|row| (row.kind, row.old_idx, row.new_idx)).collect()
+    }
+
+    #[test]
+    fn removed_tabs_stay_where_they_were() {
+        let old = profile(&[("https://a.com", "A"), ("https://b.com", "B"), ("https://c.com", "C"), ("https://d.com", "D")]);
+        let new = profile(&[("https://A.com/", "A"), ("https://c.com", "See"), ("https://e.com", "E")]);
+
+        let rows = diff_profiles(&old, &new);
+        assert_eq!(kinds(&rows), vec![
+            (DiffKind::Same, Some(0), Some(0)),
+            (DiffKind::Removed, Some(1), None),
+            (DiffKind::Retitled, Some(2), Some(1)),
+            (DiffKind::Added, None, Some(2)),
+            (DiffKind::Removed, Some(3), None),
+        ]);
+        assert_eq!(diff_count(&rows), DiffCount { added: 1, removed: 2, retitled: 1 });
+        assert!(diff_count(&diff_profiles(&old, &old)).is_empty());
+
+        assert_eq!(unified_diff(&old, &new, &rows, "old", "new"), [
+            "--- old",
+            "+++ new",
+            "-B | https://b.com",
+            "-C | https://c.com",
+            "+See | https://c.com",
+            "+E | https://e.com",
+            "-D | https://d.com",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn taking_changes_makes_the_profiles_alike() {
+        let mut old = profile(&[("https://a.com", "A"), ("https://b.com", "B"), ("https://c.com", "C")]);
+        let new = profile(&[("https://b.com", "Bee"), ("https://c.com", "C"), ("https://d.com", "D")]);
+
+        let rows = diff_profiles(&old, &new);
+        assert_eq!(take_changes(&mut old, &new, &rows), DiffCount { added: 1, removed: 1, retitled: 1 });
+        assert!(diff_count(&diff_profiles(&old, &new)).is_empty());
+
+        // only the rows given are taken
+        let mut old = profile(&[("https://a.com", "A"), ("https://b.com", "B")]);
+        let rows: Vec<DiffRow> = diff_profiles(&old, &new)
+            .into_iter()
+            .filter(|row| row.kind == DiffKind::Added)
+            .collect();
+        assert_eq!(take_changes(&mut old, &new, &rows), DiffCount { added: 2, removed: 0, retitled: 0 });
+        assert_eq!(diff_count(&diff_profiles(&old, &new)), DiffCount { added: 0, removed: 1, retitled: 1 });
+    }
+}
@@ -1,27 +1,53 @@
-#[derive(Debug, Clone, Copy)]
+use std::{fmt, io};
+
+#[derive(Debug)]
 pub enum Errors {
 
 
     MutexLockFailedError,
 
-    EventReadFailedError,
+    EventReadFailedError { source: io::Error },
 
     // TOML file IO
-    FSReadError,
+    FSReadError { path: String, source: io::Error },
     NoTOMLFilesFoundError,
-    ParseTOMLFilError,
-    TOMLFileIsEmpty,
+    ParseTOMLFilError { path: String, line: Option<usize>, source: Box<toml::de::Error> },
+    TOMLFileIsEmpty { path: String },
+    FSWriteError { path: String, source: io::Error },
+    SerializeTOMLError { path: String, source: Box<toml::ser::Error> },
+    /// a `yyyy::mm::dd::hh::mm::ss` timestamp that is not one;
+    /// `url` is set if it belongs to a tab, which is kept with the time repaired
+    ParseTimestampError { path: String, value: String, url: Option<String> },
+
+    // exporting / importing
+    SerializeJSONError { source: serde_json::Error },
+    ParseJSONError { path: String, source: serde_json::Error },
+
+    // command line
+    CLIUsageError { message: String },
+
+    // launching the browser
+    BrowserLaunchError { browser: String, source: io::Error },
+
+    /// `command` is the helper that failed, or [None] if none could be run at all
+    ClipboardReadError { command: Option<String> },
 
     // URL
     RequestGetError,
-    URLParseError,
-    
+    /// `line` is set if the URL was read from a list of them
+    URLParseError { url: String, line: Option<usize> },
+    /// a URL template without any placeholder to fill
+    NoPlaceholderError { url: String },
+
     ParseTextError,
+    /// `column` counts chars of `query`, starting at 1
+    QuerySyntaxError { query: String, column: usize, message: String },
 
-    PairAlreadyExistsError,
+    PairAlreadyExistsError { url: String, title: String },
 
     LookupDeletionFailedError,
-    NothingFoundError,
+    /// `what` describes what was looked for, e.g. `profile "News"`
+    NothingFoundError { what: String },
 
     SelectorGenerateError,
     ParseTitleError,
@@ -29,7 +55,237 @@ pub enum Errors {
     LookupFailedError,
 
 
-    WriteToStdoutError,
+    WriteToStdoutError { source: io::Error },
 
     CursorPosOverflowError,
-}
\ No newline at end of file
+}
+
+
+impl Errors {
+    /// The name of the variant, e.g. for machine-readable output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Errors::MutexLockFailedError => "MutexLockFailedError",
+            Errors::EventReadFailedError { .. } => "EventReadFailedError",
+            Errors::FSReadError { .. } => "FSReadError",
+            Errors::NoTOMLFilesFoundError => "NoTOMLFilesFoundError",
+            Errors::ParseTOMLFilError { .. } => "ParseTOMLFilError",
+            Errors::TOMLFileIsEmpty { .. } => "TOMLFileIsEmpty",
+            Errors::FSWriteError { .. } => "FSWriteError",
+            Errors::SerializeTOMLError { .. } => "SerializeTOMLError",
+            Errors::ParseTimestampError { .. } => "ParseTimestampError",
+            Errors::SerializeJSONError { .. } => "SerializeJSONError",
+            Errors::ParseJSONError { .. } => "ParseJSONError",
+            Errors::CLIUsageError { .. } => "CLIUsageError",
+            Errors::BrowserLaunchError { .. } => "BrowserLaunchError",
+            Errors::ClipboardReadError { .. } => "ClipboardReadError",
+            Errors::RequestGetError => "RequestGetError",
+            Errors::URLParseError { .. } => "URLParseError",
+            Errors::NoPlaceholderError { .. } => "NoPlaceholderError",
+            Errors::ParseTextError => "ParseTextError",
+            Errors::QuerySyntaxError { .. } => "QuerySyntaxError",
+            Errors::PairAlreadyExistsError { .. } => "PairAlreadyExistsError",
+            Errors::LookupDeletionFailedError => "LookupDeletionFailedError",
+            Errors::NothingFoundError { .. } => "NothingFoundError",
+            Errors::SelectorGenerateError => "SelectorGenerateError",
+            Errors::ParseTitleError => "ParseTitleError",
+            Errors::LookupFailedError => "LookupFailedError",
+            Errors::WriteToStdoutError { .. } => "WriteToStdoutError",
+            Errors::CursorPosOverflowError => "CursorPosOverflowError",
+        }
+    }
+
+    pub fn usage(message: &str) -> Self {
+        Errors::CLIUsageError { message: message.to_string() }
+    }
+
+    pub fn not_found(what: String) -> Self {
+        Errors::NothingFoundError { what }
+    }
+}
+
+
+impl fmt::Display for Errors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Errors::MutexLockFailedError => write!(f, "could not lock a mutex"),
+            Errors::EventReadFailedError { source } => write!(f, "could not read terminal input: {}", source),
+
+            Errors::FSReadError { path, source } => write!(f, "could not read {}: {}", path, source),
+            Errors::NoTOMLFilesFoundError => write!(f, "no profile files (ITR_PRFL_*.toml) found"),
+            Errors::ParseTOMLFilError { path, line: Some(line), source } => {
+                write!(f, "{} (line {}) is not a valid profile file: {}", path, line, source.message().trim().replace('\n', ", "))
+            },
+            Errors::ParseTOMLFilError { path, line: None, source } => {
+                write!(f, "{} is not a valid profile file: {}", path, source.message().trim().replace('\n', ", "))
+            },
+            Errors::TOMLFileIsEmpty { path } => write!(f, "{} is empty", path),
+            Errors::FSWriteError { path, source } => write!(f, "could not write {}: {}", path, source),
+            Errors::SerializeTOMLError { path, source } => write!(f, "could not turn the profile into {}: {}", path, source),
+            Errors::ParseTimestampError { path, value, url: None } => {
+                write!(f, "{}: \"{}\" is not a time of the form yyyy::mm::dd::hh::mm::ss", path, value)
+            },
+            Errors::ParseTimestampError { path, value, url: Some(url) } => {
+                write!(f, "{}: \"{}\" of the tab {} is not a time of the form yyyy::mm::dd::hh::mm::ss, the tab was kept anyway", path, value, url)
+            },
+
+            Errors::SerializeJSONError { source } => write!(f, "could not create JSON: {}", source),
+            Errors::ParseJSONError { path, source } => write!(f, "{} is not a valid JSON export: {}", path, source),
+
+            Errors::CLIUsageError { message } => write!(f, "{}", message),
+
+            Errors::BrowserLaunchError { browser, source } => write!(f, "could not start {}: {}", browser, source),
+
+            Errors::ClipboardReadError { command: Some(command) } => write!(f, "could not read the clipboard with `{}`", command),
+            Errors::ClipboardReadError { command: None } => {
+                write!(f, "could not read the clipboard: none of wl-paste, xclip, xsel, pbpaste, powershell.exe worked")
+            },
+
+            Errors::RequestGetError => write!(f, "could not fetch the page"),
+            Errors::URLParseError { url, line: Some(line) } => write!(f, "line {}: \"{}\" is not a valid URL", line, url),
+            Errors::URLParseError { url, line: None } => write!(f, "\"{}\" is not a valid URL", url),
+            Errors::NoPlaceholderError { url } => write!(f, "\"{}\" has no placeholder like {{q}} to fill", url),
+
+            Errors::ParseTextError => write!(f, "could not parse the text"),
+            Errors::QuerySyntaxError { query, column, message } => {
+                write!(f, "invalid query \"{}\", column {}: {}", query, column, message)
+            },
+
+            Errors::PairAlreadyExistsError { url, title } => {
+                write!(f, "\"{}\" ({}) is already in the profile", title, url)
+            },
+
+            Errors::LookupDeletionFailedError => write!(f, "could not delete what was looked up"),
+            Errors::NothingFoundError { what } => write!(f, "{} not found", what),
+
+            Errors::SelectorGenerateError => write!(f, "could not create the selector"),
+            Errors::ParseTitleError => write!(f, "could not find the title of the page"),
+
+            Errors::LookupFailedError => write!(f, "lookup failed"),
+
+            Errors::WriteToStdoutError { source } => write!(f, "could not write to the terminal: {}", source),
+
+            Errors::CursorPosOverflowError => write!(f, "the cursor moved past the end of the terminal"),
+        }
+    }
+}
+
+
+impl std::error::Error for Errors {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Errors::EventReadFailedError { source }
+            | Errors::FSReadError { source, .. }
+            | Errors::FSWriteError { source, .. }
+            | Errors::BrowserLaunchError { source, .. }
+            | Errors::WriteToStdoutError { source } => Some(source),
+
+            Errors::ParseTOMLFilError { source, .. } => Some(source.as_ref()),
+            Errors::SerializeTOMLError { source, .. } => Some(source.as_ref()),
+
+            Errors::SerializeJSONError { source }
+            | Errors::ParseJSONError { source, .. } => Some(source),
+
+            _ => None,
+        }
+    }
+}
+
+
+
+
+///
+///Exit codes of the command line interface, one per kind of failure.
+///
+///| code | meaning                                         |
+///|------|-------------------------------------------------|
+///| 0    | success                                         |
+///| 1    | any other failure (terminal, internal state)    |
+///| 2    | wrong usage (unknown command / flag, arguments) |
+///| 3    | profile, tab or search result not found         |
+///| 4    | invalid input (TOML / JSON / URL / text / query)|
+///| 5    | reading or writing files / stdin / stdout failed|
+///| 6    | the tab already exists in the profile           |
+///| 7    | the browser could not be started                |
+///
+pub mod exit_code {
+    pub const SUCCESS: u8 = 0;
+    pub const FAILURE: u8 = 1;
+    pub const USAGE: u8 = 2;
+    pub const NOT_FOUND: u8 = 3;
+    pub const PARSE: u8 = 4;
+    pub const IO: u8 = 5;
+    pub const ALREADY_EXISTS: u8 = 6;
+    pub const LAUNCH: u8 = 7;
+}
+
+impl Errors {
+    /// The [exit_code] the command line interface exits with on this error
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Errors::CLIUsageError { .. } => exit_code::USAGE,
+
+            Errors::NoTOMLFilesFoundError
+            | Errors::NothingFoundError { .. }
+            | Errors::LookupFailedError
+            | Errors::LookupDeletionFailedError => exit_code::NOT_FOUND,
+
+            Errors::ParseTOMLFilError { .. }
+            | Errors::TOMLFileIsEmpty { .. }
+            | Errors::ParseTimestampError { .. }
+            | Errors::URLParseError { .. }
+            | Errors::NoPlaceholderError { .. }
+            | Errors::ParseTextError
+            | Errors::QuerySyntaxError { .. }
+            | Errors::ParseTitleError
+            | Errors::ParseJSONError { .. }
+            | Errors::SerializeJSONError { .. }
+            | Errors::SerializeTOMLError { .. }
+            | Errors::SelectorGenerateError => exit_code::PARSE,
+
+            Errors::FSReadError { .. }
+            | Errors::FSWriteError { .. }
+            | Errors::WriteToStdoutError { .. }
+            | Errors::ClipboardReadError { .. } => exit_code::IO,
+
+            Errors::PairAlreadyExistsError { .. } => exit_code::ALREADY_EXISTS,
+
+            Errors::BrowserLaunchError { .. }
+            | Errors::RequestGetError => exit_code::LAUNCH,
+
+            Errors::MutexLockFailedError
+            | Errors::EventReadFailedError { .. }
+            | Errors::CursorPosOverflowError => exit_code::FAILURE,
+        }
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+
+    #[test]
+    fn messages_name_what_went_wrong() {
+        let err = Errors::URLParseError { url: "nope".to_string(), line: Some(3) };
+        assert_eq!(err.to_string(), "line 3: \"nope\" is not a valid URL");
+        assert_eq!(err.kind(), "URLParseError");
+
+        let err = Errors::not_found("profile \"News\"".to_string());
+        assert_eq!(err.to_string(), "profile \"News\" not found");
+        assert_eq!(err.exit_code(), exit_code::NOT_FOUND);
+    }
+
+    #[test]
+    fn sources_are_kept() {
+        let err = Errors::FSReadError { path: "a.toml".to_string(), source: io::Error::other("denied") };
+
+        assert_eq!(err.to_string(), "could not read a.toml: denied");
+        assert_eq!(err.source().map(|source| source.to_string()), Some("denied".to_string()));
+        assert!(Errors::usage("no command").source().is_none());
+    }
+}
@@ -0,0 +1,356 @@
+use std::fs;
+
+use crate::{Errors, Profile, URLTitlePair};
+
+//
+//
+//Turns PROFILES into documents other tools (or people) can read
+//
+//
+
+
+
+const EXPORT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+const CSV_HEADER: [&str; 4] = ["profile", "title", "url", "t_created"];
+
+const READING_LIST_TITLE: &str = "Reading List";
+
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// full fidelity, including timestamps and sort mode
+    Json,
+    /// one row per tab, with the name of the profile it belongs to
+    Csv,
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" | "htm" => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
+
+/// What goes into an export: one profile, or every profile in the store
+#[derive(Debug, Clone, Copy)]
+pub enum ExportScope<'a> {
+    Profile(&'a Profile),
+    All(&'a [Profile]),
+}
+
+impl<'a> ExportScope<'a> {
+    fn profiles(&self) -> &'a [Profile] {
+        match self {
+            Self::Profile(prfl) => std::slice::from_ref(*prfl),
+            Self::All(prfls) => prfls,
+        }
+    }
+}
+
+
+
+
+
+///
+///Renders the profile(s) in `scope` as a `format` document.
+///
+///A single profile is exported as a JSON object,
+///all profiles as a JSON array of such objects.
+///
+pub fn export(scope: ExportScope, format: ExportFormat) -> Result<String, Errors> {
+    match format {
+        ExportFormat::Json => export_json(scope),
+        ExportFormat::Csv => Ok(export_csv(scope)),
+        ExportFormat::Markdown => Ok(export_markdown(scope)),
+        ExportFormat::Html => Ok(export_html(scope)),
+    }
+}
+
+pub fn export_to_file(scope: ExportScope, format: ExportFormat, path: &str) -> Result<(), Errors> {
+    let doc = export(scope, format)?;
+
+    fs::write(path, doc).map_err(|source| Errors::FSWriteError { path: path.to_string(), source })
+}
+
+
+
+fn export_json(scope: ExportScope) -> Result<String, Errors> {
+    match scope {
+        ExportScope::Profile(prfl) => serde_json::to_string_pretty(prfl),
+        ExportScope::All(prfls) => serde_json::to_string_pretty(prfls),
+    }
+    .map_err(|source| Errors::SerializeJSONError { source })
+}
+
+
+
+fn export_csv(scope: ExportScope) -> String {
+    let mut out = csv_row(&CSV_HEADER);
+
+    for prfl in scope.profiles() {
+        for pair in prfl.get_pairs() {
+            let t_created = format_time(pair);
+
+            out.push_str(&csv_row(&[prfl.get_name(), &pair.title, &pair.url, &t_created]));
+        }
+    }
+
+    out
+}
+
+fn csv_row(fields: &[&str]) -> String {
+    let mut row = fields
+        .iter()
+        .map(|field| csv_escape(field))
+        .collect::<Vec<_>>()
+        .join(",");
+    row.push_str("\r\n");
+
+    row
+}
+
+/// RFC 4180: quote a field if it contains a separator, a quote or a line break
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+
+
+fn export_markdown(scope: ExportScope) -> String {
+    let mut out = String::new();
+
+    if let ExportScope::All(_) = scope {
+        out.push_str(&format!("# {}\n\n", READING_LIST_TITLE));
+    }
+
+    for prfl in scope.profiles() {
+        let heading = match scope {
+            ExportScope::Profile(_) => "#",
+            ExportScope::All(_) => "##",
+        };
+        out.push_str(&format!("{} {}\n\n", heading, markdown_escape(prfl.get_name())));
+        if !prfl.get_tags().is_empty() {
+            out.push_str(&format!("Tagged {}\n\n", markdown_tags(prfl.get_tags())));
+        }
+        if !prfl.get_note().is_empty() {
+            out.push_str(&format!("{}\n\n", markdown_note(prfl.get_note(), "")));
+        }
+
+        let mut section = "";
+        for pair in prfl.get_pairs() {
+            // the pairs are in the order of their sections
+            if pair.get_section() != section {
+                section = pair.get_section();
+                out.push_str(&format!("\n{}# {}\n\n", heading, markdown_escape(section)));
+            }
+
+            let tags = match pair.get_tags().is_empty() {
+                true => String::new(),
+                false => format!(", tagged {}", markdown_tags(pair.get_tags())),
+            };
+
+            out.push_str(&format!(
+                "- [{}](<{}>) — added {}{}\n",
+                markdown_escape(&pair.title),
+                pair.url.replace('>', "%3E"),
+                format_time(pair),
+                tags
+            ));
+            if !pair.get_note().is_empty() {
+                out.push_str(&format!("{}\n", markdown_note(pair.get_note(), "  ")));
+            }
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+/// `note` as a block quote, every line behind `indent` (to keep it inside a list item)
+fn markdown_note(note: &str, indent: &str) -> String {
+    note.lines()
+        .map(|line| format!("{}> {}", indent, markdown_escape(line)).trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn markdown_tags(tags: &[String]) -> String {
+    tags.iter()
+        .map(|tag| markdown_escape(tag))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Escapes what would end a link text or start emphasis, code, HTML or a table cell; the rest stays readable
+fn markdown_escape(text: &str) -> String {
+    text.chars().fold(String::with_capacity(text.len()), |mut acc, c| {
+        if "\\`*_[]()<>|".contains(c) {
+            acc.push('\\');
+        }
+        acc.push(c);
+        acc
+    })
+}
+
+
+
+fn export_html(scope: ExportScope) -> String {
+    let title = match scope {
+        ExportScope::Profile(prfl) => html_escape(prfl.get_name()),
+        ExportScope::All(_) => READING_LIST_TITLE.to_string(),
+    };
+
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n<h1>{}</h1>\n",
+        title, title
+    );
+
+    for prfl in scope.profiles() {
+        if let ExportScope::All(_) = scope {
+            out.push_str(&format!("<h2>{}</h2>\n", html_escape(prfl.get_name())));
+        }
+
+        out.push_str("<ul>\n");
+        for pair in prfl.get_pairs() {
+            out.push_str(&format!(
+                "<li><a href=\"{}\">{}</a> <small>added {}</small></li>\n",
+                html_escape(&pair.url),
+                html_escape(&pair.title),
+                format_time(pair)
+            ));
+        }
+        out.push_str("</ul>\n");
+    }
+
+    out.push_str("</body>\n</html>\n");
+
+    out
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+
+
+#[inline(always)]
+fn format_time(pair: &URLTitlePair) -> String {
+    pair.get_time_created().format(EXPORT_TIME_FORMAT).to_string()
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+
+    use super::*;
+
+    fn at(time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time, EXPORT_TIME_FORMAT).unwrap()
+    }
+
+    fn profile(name: &str, tabs: &[(&str, &str)]) -> Profile {
+        let pairs = tabs
+            .iter()
+            .map(|(url, title)| URLTitlePair::from_save(url.to_string(), title.to_string(), at("2024-01-02 03:04:05")))
+            .collect();
+
+        Profile::builder().add_name(name).add_many_title_url_pairs(pairs).build()
+    }
+
+    #[test]
+    fn formats_are_named_by_extension() {
+        assert_eq!(ExportFormat::from_name("MD"), Some(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::from_name("htm"), Some(ExportFormat::Html));
+        assert_eq!(ExportFormat::from_name("xml"), None);
+        assert_eq!(ExportFormat::Json.extension(), "json");
+    }
+
+    #[test]
+    fn json_keeps_one_profile_or_all() {
+        let prfls = [profile("Work", &[("https://a.com", "A")]), profile("Home", &[])];
+
+        let one: serde_json::Value = serde_json::from_str(&export(ExportScope::Profile(&prfls[0]), ExportFormat::Json).unwrap()).unwrap();
+        assert_eq!(one["name"], "Work");
+        assert_eq!(one["pairs"][0]["url"], "https://a.com");
+        assert_eq!(one["pairs"][0]["t_created"], "2024-01-02T03:04:05");
+        assert!(one["pairs"][0].get("is_highlighted").is_none());
+
+        let all: serde_json::Value = serde_json::from_str(&export(ExportScope::All(&prfls), ExportFormat::Json).unwrap()).unwrap();
+        assert_eq!(all.as_array().map(Vec::len), Some(2));
+        assert_eq!(all[1]["name"], "Home");
+    }
+
+    #[test]
+    fn csv_quotes_what_needs_it() {
+        let prfl = profile("Work", &[("https://a.com/?q=1,2", "Say \"hi\"")]);
+
+        assert_eq!(
+            export(ExportScope::Profile(&prfl), ExportFormat::Csv).unwrap(),
+            "profile,title,url,t_created\r\nWork,\"Say \"\"hi\"\"\",\"https://a.com/?q=1,2\",2024-01-02 03:04:05\r\n"
+        );
+    }
+
+    #[test]
+    fn markdown_and_html_escape_titles() {
+        let prfl = profile("Work", &[("https://a.com/<x>", "[draft] <b>")]);
+
+        assert_eq!(
+            export(ExportScope::Profile(&prfl), ExportFormat::Markdown).unwrap(),
+            "# Work\n\n- [\\[draft\\] \\<b\\>](<https://a.com/<x%3E>) — added 2024-01-02 03:04:05\n\n"
+        );
+
+        // punctuation that cannot break the link stays as it is
+        let plain = profile("v1.2 - foo!", &[("https://a.com", "v1.2 - foo! #1 {x} + *y*")]);
+        assert_eq!(
+            export(ExportScope::Profile(&plain), ExportFormat::Markdown).unwrap(),
+            "# v1.2 - foo!\n\n- [v1.2 - foo! #1 {x} + \\*y\\*](<https://a.com>) — added 2024-01-02 03:04:05\n\n"
+        );
+
+        let html = export(ExportScope::All(std::slice::from_ref(&prfl)), ExportFormat::Html).unwrap();
+        assert!(html.contains("<title>Reading List</title>"));
+        assert!(html.contains("<h2>Work</h2>"));
+        assert!(html.contains("<li><a href=\"https://a.com/&lt;x&gt;\">[draft] &lt;b&gt;</a> <small>added 2024-01-02 03:04:05</small></li>"));
+    }
+
+    #[test]
+    fn notes_become_block_quotes() {
+        let mut prfl = profile("Work", &[("https://a.com", "A")]);
+        prfl.set_note(String::from("Read first\n\nthen *this*"));
+        prfl.set_pair_note(0, String::from("later"));
+
+        assert_eq!(
+            export(ExportScope::Profile(&prfl), ExportFormat::Markdown).unwrap(),
+            "# Work\n\n> Read first\n>\n> then \\*this\\*\n\n- [A](<https://a.com>) — added 2024-01-02 03:04:05\n  > later\n\n"
+        );
+    }
+}
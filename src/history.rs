@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+
+use crate::Profile;
+
+//
+//
+//The edits made to the PROFILES in the TUI, to undo and redo them.
+//
+//An edit is kept as the profiles it touched, as they were before and after it,
+//so that every command (renaming, deleting, merging...) is undone the same way.
+//Opening tabs is no edit: what it changes is kept when an edit is undone.
+//
+//
+
+
+
+/// How many edits can be undone, the oldest are forgotten first
+pub const HISTORY_LIMIT: usize = 100;
+
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    /// the profiles it changed or deleted, as they were
+    pub before: Vec<Profile>,
+    /// the profiles it changed or created, as they became
+    pub after: Vec<Profile>,
+}
+
+impl Edit {
+    ///
+    ///What changed from `before` to `after` (the profiles touched, as they were and as they are,
+    ///in any order), with profiles told apart by their id; [None] if nothing did.
+    ///
+    pub fn between(before: &[Profile], after: &[Profile]) -> Option<Self> {
+        let changed = |from: &[Profile], to: &[Profile]| -> Vec<Profile> {
+            from.iter()
+                .filter(|prfl| to.iter().find(|other| other.get_id() == prfl.get_id()) != Some(*prfl))
+                .cloned()
+                .collect()
+        };
+
+        let edit = Self { before: changed(before, after), after: changed(after, before) };
+        match edit.before.is_empty() && edit.after.is_empty() {
+            true => None,
+            false => Some(edit),
+        }
+    }
+
+    /// The edit that takes it back
+    pub fn inverse(self) -> Self {
+        Self { before: self.after, after: self.before }
+    }
+
+    /// `"Reading"`, `"Reading" and "Watch Later"` or `"Reading" and 2 other profiles`
+    pub fn describe(&self) -> String {
+        let mut names: Vec<&str> = Vec::new();
+        for prfl in self.after.iter().chain(&self.before) {
+            if !names.contains(&prfl.get_name().as_str()) {
+                names.push(prfl.get_name());
+            }
+        }
+
+        match names.as_slice() {
+            [] => String::from("no profile"),
+            [name] => format!("\"{}\"", name),
+            [first, second] => format!("\"{}\" and \"{}\"", first, second),
+            [first, rest @ ..] => format!("\"{}\" and {} other profiles", first, rest.len()),
+        }
+    }
+}
+
+
+/// The edits to undo, newest last, and the undone ones to redo
+#[derive(Debug, Default)]
+pub struct History {
+    undo: VecDeque<Edit>,
+    redo: Vec<Edit>,
+}
+
+impl History {
+    /// Remembers `edit` as the newest one, which makes the undone edits impossible to redo
+    pub fn record(&mut self, edit: Edit) {
+        self.redo.clear();
+        self.undo.push_back(edit);
+
+        while self.undo.len() > HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+    }
+
+    /// The newest edit, taken back: what has to be applied to undo it
+    pub fn undo(&mut self) -> Option<Edit> {
+        let edit = self.undo.pop_back()?;
+        self.redo.push(edit.clone());
+
+        Some(edit.inverse())
+    }
+
+    /// The newest undone edit, which has to be applied again
+    pub fn redo(&mut self) -> Option<Edit> {
+        let edit = self.redo.pop()?;
+        self.undo.push_back(edit.clone());
+
+        Some(edit)
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::URLTitlePair;
+
+    fn profile(name: &str) -> Profile {
+        Profile::builder()
+            .add_name(name)
+            .add_title_url_pair("https://a.com", "A")
+            .build()
+    }
+
+    fn with_tab(prfl: &Profile, url: &str) -> Profile {
+        let mut prfl = prfl.clone();
+        prfl.add_pair(URLTitlePair::new(url, "B")).unwrap();
+        prfl
+    }
+
+    #[test]
+    fn between_keeps_only_what_changed() {
+        let reading = profile("Reading");
+        let music = profile("Music");
+        let changed = with_tab(&reading, "https://b.com");
+
+        assert_eq!(Edit::between(&[reading.clone(), music.clone()], &[music.clone(), reading.clone()]), None);
+
+        let edit = Edit::between(&[reading.clone(), music.clone()], &[changed.clone(), music.clone()]).unwrap();
+        assert_eq!(edit, Edit { before: vec![reading.clone()], after: vec![changed] });
+        assert_eq!(edit.describe(), "\"Reading\"");
+
+        // created and deleted profiles are only on one side
+        let edit = Edit::between(std::slice::from_ref(&reading), std::slice::from_ref(&music)).unwrap();
+        assert_eq!(edit, Edit { before: vec![reading], after: vec![music] });
+        assert_eq!(edit.describe(), "\"Music\" and \"Reading\"");
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let before = profile("Reading");
+        let after = with_tab(&before, "https://b.com");
+        let edit = Edit { before: vec![before.clone()], after: vec![after.clone()] };
+
+        let mut history = History::default();
+        assert_eq!(history.undo(), None);
+        history.record(edit.clone());
+
+        assert_eq!(history.undo(), Some(edit.clone().inverse()));
+        assert_eq!((history.undo_len(), history.redo_len()), (0, 1));
+        assert_eq!(history.redo(), Some(edit.clone()));
+        assert_eq!(history.redo(), None);
+
+        // a new edit cannot be followed by redoing an undone one
+        history.undo();
+        history.record(edit.clone().inverse());
+        assert_eq!(history.redo(), None);
+    }
+
+    #[test]
+    fn forgets_the_oldest_edits() {
+        let mut history = History::default();
+        for n in 0..HISTORY_LIMIT + 5 {
+            history.record(Edit { before: Vec::new(), after: vec![profile(&n.to_string())] });
+        }
+
+        assert_eq!(history.undo_len(), HISTORY_LIMIT);
+        let oldest = std::iter::from_fn(|| history.undo()).last().unwrap();
+        assert_eq!(oldest.before[0].get_name(), "5");
+    }
+}
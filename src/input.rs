@@ -0,0 +1,263 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+//
+//
+//A line of text being typed in the TUI, and several lines of it
+//
+//
+
+
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TextInput {
+    text: String,
+    /// in chars, not bytes; `0..=text.chars().count()`
+    cursor: usize,
+}
+
+impl TextInput {
+    /// Starts with `text` and the cursor behind it
+    pub fn new(text: &str) -> Self {
+        Self { text: text.to_string(), cursor: text.chars().count() }
+    }
+
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+    pub fn get_cursor(&self) -> usize {
+        self.cursor
+    }
+
+    fn byte_idx(&self, char_idx: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(char_idx)
+            .map(|(idx, _)| idx)
+            .unwrap_or(self.text.len())
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let idx = self.byte_idx(self.cursor);
+        self.text.insert(idx, c);
+        self.cursor += 1;
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        self.cursor -= 1;
+        let idx = self.byte_idx(self.cursor);
+        self.text.remove(idx);
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.text.chars().count() {
+            let idx = self.byte_idx(self.cursor);
+            self.text.remove(idx);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    /// Cuts off the text behind the cursor and returns it, with the cursor at its start
+    fn split_off(&mut self) -> Self {
+        let idx = self.byte_idx(self.cursor);
+
+        Self { text: self.text.split_off(idx), cursor: 0 }
+    }
+
+    /// Appends `text`, leaving the cursor where it is
+    fn append(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+
+    ///
+    ///Applies an editing key (characters, BACKSPACE, DELETE, LEFT / RIGHT, HOME / END)
+    ///and returns whether `key` was one.
+    ///
+    pub fn handle_key(&mut self, key: &KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.text.chars().count()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.text.chars().count(),
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+
+
+///
+///Several lines of text being typed, e.g. a note;
+///ENTER starts a new line instead of accepting the text.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextArea {
+    /// never empty
+    lines: Vec<TextInput>,
+    /// the line with the cursor
+    row: usize,
+}
+
+impl Default for TextArea {
+    fn default() -> Self {
+        Self { lines: vec![TextInput::default()], row: 0 }
+    }
+}
+
+impl TextArea {
+    /// Starts with `text` and the cursor behind it
+    pub fn new(text: &str) -> Self {
+        let lines: Vec<TextInput> = text.split('\n').map(TextInput::new).collect();
+        let row = lines.len() - 1;
+
+        Self { lines, row }
+    }
+
+    /// The lines joined with `\n`, without empty lines at the end
+    pub fn get_text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.get_text())
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim_end()
+            .to_string()
+    }
+    pub fn get_lines(&self) -> Vec<&str> {
+        self.lines.iter().map(|line| line.get_text()).collect()
+    }
+    /// `(line, char in the line)`
+    pub fn get_cursor(&self) -> (usize, usize) {
+        (self.row, self.lines[self.row].get_cursor())
+    }
+
+    /// Puts the cursor on the line at `row`, as close to the same column as that line allows
+    fn go_to_row(&mut self, row: usize) {
+        let column = self.lines[self.row].cursor;
+        self.row = row;
+        self.lines[row].cursor = column.min(self.lines[row].len());
+    }
+
+    ///
+    ///Applies an editing key (characters, ENTER, BACKSPACE, DELETE, the ARROWS, HOME / END)
+    ///and returns whether `key` was one.
+    ///
+    pub fn handle_key(&mut self, key: &KeyEvent) -> bool {
+        let (column, len) = (self.lines[self.row].cursor, self.lines[self.row].len());
+        let is_last = self.row + 1 == self.lines.len();
+
+        match key.code {
+            KeyCode::Enter => {
+                let rest = self.lines[self.row].split_off();
+                self.row += 1;
+                self.lines.insert(self.row, rest);
+            },
+            KeyCode::Backspace if column == 0 && self.row > 0 => {
+                let line = self.lines.remove(self.row);
+                self.row -= 1;
+                let prev = &mut self.lines[self.row];
+                prev.cursor = prev.len();
+                prev.append(line.get_text());
+            },
+            KeyCode::Delete if column == len && !is_last => {
+                let next = self.lines.remove(self.row + 1);
+                self.lines[self.row].append(next.get_text());
+            },
+            KeyCode::Left if column == 0 && self.row > 0 => {
+                self.row -= 1;
+                self.lines[self.row].cursor = self.lines[self.row].len();
+            },
+            KeyCode::Right if column == len && !is_last => {
+                self.row += 1;
+                self.lines[self.row].cursor = 0;
+            },
+            KeyCode::Up if self.row > 0 => self.go_to_row(self.row - 1),
+            KeyCode::Down if !is_last => self.go_to_row(self.row + 1),
+            KeyCode::Up | KeyCode::Down => {},
+            _ => return self.lines[self.row].handle_key(key),
+        }
+
+        true
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+
+    use super::*;
+
+    fn keys(area: &mut TextArea, codes: &[KeyCode]) {
+        for &code in codes {
+            area.handle_key(&KeyEvent::new(code, KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn a_line_edits_by_chars() {
+        let mut input = TextInput::new("naïve");
+        input.handle_key(&KeyEvent::new(KeyCode::Left, KeyModifiers::NONE));
+        input.backspace();
+        input.insert('ï');
+        assert_eq!((input.get_text(), input.get_cursor()), ("naïïe", 4));
+
+        input.handle_key(&KeyEvent::new(KeyCode::Home, KeyModifiers::NONE));
+        input.delete();
+        input.backspace();
+        assert_eq!((input.get_text(), input.get_cursor()), ("aïïe", 0));
+        assert!(!input.handle_key(&KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn enter_splits_lines_and_backspace_joins_them() {
+        let mut area = TextArea::new("first\nsecond");
+        assert_eq!(area.get_cursor(), (1, 6));
+
+        keys(&mut area, &[KeyCode::Left, KeyCode::Left, KeyCode::Enter]);
+        assert_eq!(area.get_lines(), ["first", "seco", "nd"]);
+        assert_eq!(area.get_cursor(), (2, 0));
+
+        keys(&mut area, &[KeyCode::Backspace, KeyCode::Up, KeyCode::End, KeyCode::Delete]);
+        assert_eq!(area.get_lines(), ["firstsecond"]);
+        assert_eq!(area.get_cursor(), (0, 5));
+    }
+
+    #[test]
+    fn up_and_down_keep_the_column_if_they_can() {
+        let mut area = TextArea::new("a long line\nab\nanother line");
+
+        keys(&mut area, &[KeyCode::Up]);
+        assert_eq!(area.get_cursor(), (1, 2));
+        keys(&mut area, &[KeyCode::Up, KeyCode::Up]);
+        assert_eq!(area.get_cursor(), (0, 2));
+        keys(&mut area, &[KeyCode::Right, KeyCode::Down, KeyCode::Down]);
+        assert_eq!(area.get_cursor(), (2, 2));
+
+        // LEFT and RIGHT go across lines
+        keys(&mut area, &[KeyCode::Home, KeyCode::Left]);
+        assert_eq!(area.get_cursor(), (1, 2));
+        keys(&mut area, &[KeyCode::Right]);
+        assert_eq!(area.get_cursor(), (2, 0));
+    }
+
+    #[test]
+    fn empty_lines_at_the_end_are_dropped() {
+        let mut area = TextArea::default();
+        keys(&mut area, &[KeyCode::Char('x'), KeyCode::Enter, KeyCode::Enter]);
+        assert_eq!(area.get_lines(), ["x", "", ""]);
+        assert_eq!(area.get_text(), "x");
+    }
+}
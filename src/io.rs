@@ -1,17 +1,18 @@
-use std::fs;
+use std::{collections::BTreeMap, fs, path::Path};
 
 use chrono::{NaiveDateTime, NaiveDate};
 use itertools::Itertools;
+use serde::Serialize;
 
-use crate::{debug_println, Deserialize, Errors, Profile, URLTitlePair};
+use crate::{debug_println, urls::validate_url, Deserialize, Errors, Profile, ProfileSortMode, SortMode, TabKind, URLTitlePair};
 
-///
-///
-///Saves PROFILES and reads from them
-///
-///
-///
-///
+//
+//
+//Saves PROFILES and reads from them
+//
+//
+//
+//
 
 /// The default number of initial pre-allocation
 /// for reading profile files.
@@ -29,8 +30,13 @@ const DEFAULT_LIMIT: usize = 30;
 const FILE_PREFIX: &str = "ITR_PRFL_";
 const FILE_EXTSN: &str = ".toml";
 
+/// next to the profile files, but not one of them
+const SETTINGS_FILE: &str = "ITR_SETTINGS.toml";
+
 const TIME_SEPARATOR: &str = "::";
 
+const DEFAULT_TITLE: &str = "No Title Given";
+
 #[allow(dead_code)] const TOML_GEN_HEADER: &str = "General";
 #[allow(dead_code)] const TOML_GEN_PRFL_NAME: &str = "name";
 #[allow(dead_code)] const TOML_GEN_PRFL_ID: &str = "id";
@@ -55,25 +61,63 @@ fn is_file_pattern_correct(file_name: &str) -> bool {
 
 
 #[allow(non_snake_case)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct Config {
     General: General,
+    #[serde(default)]
     BrowserTab: Vec<BrowserTab>
 }
 
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct General {
     name: String,
-    id: u16,
+    id: usize,
     time_created: String,
+    /// missing in files written before it was saved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_last_modified: Option<String>,
+    #[serde(default)]
+    sort: SortMode,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    note: String,
+    /// e.g. `work/rust`, see [crate::normalize_group]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    group: String,
+    /// in the order they are shown and opened in
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sections: Vec<String>,
+    /// see [Profile::stale_pairs]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expire_after_days: Option<u32>,
+    /// see [Profile::from_template]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    template: bool,
 }
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct BrowserTab {
     url: String,
     title: String,
 
-    t_created: String
+    t_created: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    t_opened: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    note: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    section: String,
+    /// the section an archived tab goes back to
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    archived_from: String,
+    #[serde(default, skip_serializing_if = "TabKind::is_page")]
+    kind: TabKind,
+    /// the values the placeholders of a [TabKind::Template] were opened with, newest first
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    recent: BTreeMap<String, Vec<String>>,
 }
 
 
@@ -104,14 +148,40 @@ fn parse_into_naivedatetime(t: String, separator: &str) -> Option<NaiveDateTime>
         )
 }
 
+/// The inverse of [parse_into_naivedatetime]
+#[inline(always)]
+fn format_naivedatetime(t: &NaiveDateTime, separator: &str) -> String {
+    let fmt = ["%Y", "%m", "%d", "%H", "%M", "%S"].join(separator);
 
-fn parse_toml(toml_file_name: &str) -> Option<Profile> {
+    t.format(&fmt).to_string()
+}
+
+
+/// The line (starting from 1) `toml_file` is broken at, according to `err`
+fn toml_error_line(toml_file: &str, err: &toml::de::Error) -> Option<usize> {
+    let span = err.span()?;
+
+    Some(toml_file.get(..span.start)?.lines().count().max(1))
+}
+
+
+fn parse_toml(toml_file_name: &str, errors: &mut Vec<Errors>) -> Result<Profile, Errors> {
     
 
-    let toml_file = fs::read_to_string(toml_file_name).ok()?;
+    let toml_file = fs::read_to_string(toml_file_name)
+        .map_err(|source| Errors::FSReadError { path: toml_file_name.to_string(), source })?;
     debug_println!("TOML FILE Opened.");
 
-    let config: Config = toml::from_str(&toml_file).unwrap();
+    if toml_file.trim().is_empty() {
+        return Err(Errors::TOMLFileIsEmpty { path: toml_file_name.to_string() });
+    }
+
+    let config: Config = toml::from_str(&toml_file)
+        .map_err(|source| Errors::ParseTOMLFilError {
+            path: toml_file_name.to_string(),
+            line: toml_error_line(&toml_file, &source),
+            source: Box::new(source),
+        })?;
     debug_println!("TOML FILE into str.");
 
     // init Profile
@@ -129,40 +199,75 @@ fn parse_toml(toml_file_name: &str) -> Option<Profile> {
     debug_println!("TOML FILE id got: {}.", last_id);
 
 
+    let parse_time = |t: String| {
+        parse_into_naivedatetime(t.clone(), TIME_SEPARATOR)
+            .ok_or(Errors::ParseTimestampError { path: toml_file_name.to_string(), value: t, url: None })
+    };
+
     // toml format: yyyy:: mm:: dd:: hh:: mm:: ss
-    let t_created: NaiveDateTime = parse_into_naivedatetime(general.time_created, TIME_SEPARATOR)?;
+    let t_created: NaiveDateTime = parse_time(general.time_created)?;
     debug_println!("TOML FILE t created parsed: {}.", t_created);
+    let t_last_modified = general.time_last_modified.map(parse_time).transpose()?.unwrap_or(t_created);
 
 
     // read browser tabs
     let pairs = config
         .BrowserTab
         .into_iter()
-        .filter_map(|tab| {
-            let url = if tab.url.is_empty() {
-                return None;
-            } else {
-                tab.url
-            };
-
+        .filter(|tab| !tab.url.is_empty())
+        .map(|tab| {
             let title = if tab.title.is_empty() {
-                String::from("No Title Given")
+                String::from(DEFAULT_TITLE)
             } else {
                 tab.title
             };
 
-            let t_created: NaiveDateTime = parse_into_naivedatetime(tab.t_created, TIME_SEPARATOR)?;
+            // one bad time must not cost the whole file: the tab is kept, the time is reported
+            let mut parse_tab_time = |t: String| {
+                parse_time(t)
+                    .map_err(|e| match e {
+                        Errors::ParseTimestampError { path, value, .. } => {
+                            errors.push(Errors::ParseTimestampError { path, value, url: Some(tab.url.clone()) })
+                        },
+                        e => errors.push(e),
+                    })
+                    .ok()
+            };
 
-            Some(URLTitlePair::from_save(url, title, t_created))
+            let t_created_tab: NaiveDateTime = parse_tab_time(tab.t_created).unwrap_or(t_created);
+            let t_opened = tab.t_opened
+                .into_iter()
+                .filter_map(&mut parse_tab_time)
+                .collect::<Vec<_>>();
+
+            URLTitlePair::from_save(tab.url, title, t_created_tab)
+                .with_times_opened(t_opened)
+                .with_tags(tab.tags)
+                .with_note(tab.note)
+                .with_section(tab.section)
+                .with_archived_from(tab.archived_from)
+                .with_kind(tab.kind)
+                .with_recent_values(tab.recent)
         })
-        .collect_vec();
+        .collect::<Vec<_>>();
     
-    Some(
-        Profile::builder()
-            .add_name(&name)
-            .add_many_title_url_pairs(pairs)
-            .build()
-        )
+    let mut profile = Profile::builder()
+        .set_id(last_id)
+        .add_name(&name)
+        .add_many_title_url_pairs(pairs)
+        .set_sort_mode(general.sort)
+        .set_tags(general.tags)
+        .set_note(general.note)
+        .set_group(&general.group)
+        .set_sections(general.sections)
+        .set_expire_after(general.expire_after_days)
+        .set_template(general.template)
+        .set_time_created(t_created)
+        .set_time_last_modified(t_last_modified)
+        .build();
+    profile.set_file_name(toml_file_name);
+
+    Ok(profile)
 }
 
 
@@ -171,19 +276,40 @@ fn parse_toml(toml_file_name: &str) -> Option<Profile> {
 
 ///
 ///Reads all profile files in the current folder for this pattern:
-///```
+///```ignore
 /// file_name.starts_with("ITR_PRFL")
 /// & file_name.ends_with(".toml")
 ///```
 ///and returns an [Ok(Vec<Profile>)] if any found,
-///or [Err(())] if none exists
+///or [Err(Errors::NoTOMLFilesFoundError)] if none exists
 ///(OR, rarely, if [```fs::read_dir(".")```] somehow fails).
 ///
+///Files that cannot be read are skipped,
+///use [read_profiles_with_errors] to find out which and why.
+///
 #[inline(always)]
 pub fn read_profiles() -> Result<Vec<Profile>, Errors> {
+    let (profiles, _errors) = read_profiles_with_errors()?;
+
+    if !profiles.is_empty() {
+        Ok(profiles)
+    } else {
+        Err(Errors::NoTOMLFilesFoundError)
+    }
+}
+
+
+///
+///Like [read_profiles], but also returns an error for every profile file
+///that could not be read and for every tab time that had to be repaired,
+///and an empty list (not an error) if there are no profiles.
+///
+pub fn read_profiles_with_errors() -> Result<(Vec<Profile>, Vec<Errors>), Errors> {
+
+    let mut errors = Vec::new();
 
     let profiles = fs::read_dir(".")
-        .map_err(|_| Errors::FSReadError)?
+        .map_err(|source| Errors::FSReadError { path: String::from("."), source })?
         .filter_map(|file| {
             match file {
                 Ok(file) => {
@@ -194,7 +320,9 @@ pub fn read_profiles() -> Result<Vec<Profile>, Errors> {
                     let file_name = fname.to_str()?;
                     match is_file_pattern_correct(file_name) {
                         true => {
-                            parse_toml(file_name)
+                            parse_toml(file_name, &mut errors)
+                                .map_err(|e| errors.push(e))
+                                .ok()
                         },
                         false => None,
                     }
@@ -205,14 +333,359 @@ pub fn read_profiles() -> Result<Vec<Profile>, Errors> {
                 },
             }
         })
-        .fold(Vec::with_capacity(DEFAULT_LIMIT), |mut acc, prfl| {
-            acc.push(prfl);
+        .fold(Vec::with_capacity(DEFAULT_LIMIT), |mut acc: Vec<Profile>, prfl| {
+            // a copied file keeps the id of the original, the copy gets one of its own
+            match acc.iter().any(|other| other.get_id() == prfl.get_id()) {
+                true => acc.push(prfl.with_new_id()),
+                false => acc.push(prfl),
+            }
             acc
         });
 
-    if profiles.len() > 0 {
-        Ok(profiles)
+    Ok((profiles, errors))
+}
+
+
+
+// ============== FOR TOML SAVING
+
+
+
+///
+///Picks a file name for a profile that has never been saved:
+///```ignore
+/// "ITR_PRFL_" + (name without anything but letters & digits) + ".toml"
+///```
+///with the profile id appended if that file already exists.
+///
+fn new_file_name(prfl: &Profile) -> String {
+    let stem: String = prfl
+        .get_name()
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect();
+
+    let file_name = format!("{}{}{}", FILE_PREFIX, stem, FILE_EXTSN);
+    if stem.is_empty() || Path::new(&file_name).exists() {
+        format!("{}{}_{}{}", FILE_PREFIX, stem, prfl.get_id(), FILE_EXTSN)
     } else {
-        Err(Errors::NoTOMLFilesFoundError)
+        file_name
+    }
+}
+
+
+///
+///Writes `prfl` to its ITR_PRFL_*.toml file,
+///choosing (and remembering) a new file name if it was never saved before.
+///
+pub fn save_profile(prfl: &mut Profile) -> Result<(), Errors> {
+    let file_name = match prfl.get_file_name() {
+        Some(file_name) => file_name.to_string(),
+        None => new_file_name(prfl),
+    };
+
+    let config = Config {
+        General: General {
+            name: prfl.get_name().clone(),
+            id: prfl.get_id(),
+            time_created: format_naivedatetime(&prfl.get_time_created(), TIME_SEPARATOR),
+            time_last_modified: Some(format_naivedatetime(&prfl.get_time_last_visited(), TIME_SEPARATOR)),
+            sort: *prfl.get_sort_mode(),
+            tags: prfl.get_tags().to_vec(),
+            note: prfl.get_note().to_string(),
+            group: prfl.get_group().to_string(),
+            sections: prfl.get_sections().to_vec(),
+            expire_after_days: prfl.get_expire_after(),
+            template: prfl.is_template(),
+        },
+        BrowserTab: prfl
+            .get_pairs()
+            .iter()
+            .map(|pair| BrowserTab {
+                url: pair.url.clone(),
+                title: pair.title.clone(),
+                t_created: format_naivedatetime(&pair.get_time_created(), TIME_SEPARATOR),
+                t_opened: pair
+                    .get_times_opened()
+                    .iter()
+                    .map(|t| format_naivedatetime(t, TIME_SEPARATOR))
+                    .collect_vec(),
+                tags: pair.get_tags().to_vec(),
+                note: pair.get_note().to_string(),
+                section: pair.get_section().to_string(),
+                archived_from: pair.get_archived_from().to_string(),
+                kind: pair.get_kind(),
+                recent: pair.get_all_recent_values().clone(),
+            })
+            .collect_vec(),
+    };
+
+    let toml_file = toml::to_string(&config)
+        .map_err(|source| Errors::SerializeTOMLError { path: file_name.clone(), source: Box::new(source) })?;
+    fs::write(&file_name, toml_file)
+        .map_err(|source| Errors::FSWriteError { path: file_name.clone(), source })?;
+    debug_println!("TOML FILE saved: {}.", file_name);
+
+    prfl.set_file_name(&file_name);
+
+    Ok(())
+}
+
+
+/// Removes the ITR_PRFL_*.toml file of `prfl`, if it has one
+pub fn delete_profile(prfl: &Profile) -> Result<(), Errors> {
+    match prfl.get_file_name() {
+        Some(file_name) => delete_profile_file(file_name),
+        None => Ok(()),
+    }
+}
+
+/// Removes the profile file `file_name`, see [Profile::get_file_name]
+pub fn delete_profile_file(file_name: &str) -> Result<(), Errors> {
+    fs::remove_file(file_name)
+        .map_err(|source| Errors::FSWriteError { path: file_name.to_string(), source })
+}
+
+
+
+
+// ============== FOR SETTINGS
+
+
+
+/// What the TUI remembers between runs that belongs to no single profile
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Settings {
+    #[serde(default)]
+    pub profile_sort: ProfileSortMode,
+}
+
+
+/// Reads the settings file, or gives the defaults if there is none
+pub fn read_settings() -> Result<Settings, Errors> {
+    let toml_file = match fs::read_to_string(SETTINGS_FILE) {
+        Ok(toml_file) => toml_file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Settings::default()),
+        Err(source) => return Err(Errors::FSReadError { path: SETTINGS_FILE.to_string(), source }),
+    };
+
+    toml::from_str(&toml_file)
+        .map_err(|source| Errors::ParseTOMLFilError {
+            path: SETTINGS_FILE.to_string(),
+            line: toml_error_line(&toml_file, &source),
+            source: Box::new(source),
+        })
+}
+
+pub fn save_settings(settings: &Settings) -> Result<(), Errors> {
+    let toml_file = toml::to_string(settings)
+        .map_err(|source| Errors::SerializeTOMLError { path: SETTINGS_FILE.to_string(), source: Box::new(source) })?;
+
+    fs::write(SETTINGS_FILE, toml_file)
+        .map_err(|source| Errors::FSWriteError { path: SETTINGS_FILE.to_string(), source })
+}
+
+
+
+
+// ============== FOR IMPORTING
+
+
+
+///
+///Parses newline-separated URLs, each optionally followed by a TAB and a title:
+///```ignore
+/// https://example.com/page<TAB>Some Page
+///```
+///Empty lines and lines starting with `#` are skipped.
+///Returns the pairs in input order, plus an [Errors::URLParseError] for every line that is not a valid URL.
+///
+pub fn parse_url_lines(text: &str) -> (Vec<URLTitlePair>, Vec<Errors>) {
+    let mut pairs = Vec::new();
+    let mut invalid = Vec::new();
+
+    let lines = text
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+    for (line_num, line) in lines {
+        let (url, title) = match line.split_once('\t') {
+            Some((url, title)) => (url.trim(), title.trim()),
+            None => (line, ""),
+        };
+
+        match validate_url(url) {
+            Ok(url) => {
+                let title = if title.is_empty() { DEFAULT_TITLE } else { title };
+                pairs.push(URLTitlePair::new(&url, title));
+            },
+            Err(_) => invalid.push(Errors::URLParseError { url: url.to_string(), line: Some(line_num) }),
+        }
+    }
+
+    (pairs, invalid)
+}
+
+
+///
+///Reads profiles back from a JSON export (one profile, or an array of them).
+///Every imported profile gets a fresh id and is not yet tied to any file;
+///see [crate::Profile::merge_profile] to take one into a profile of the same name instead.
+///
+pub fn import_json(file_name: &str) -> Result<Vec<Profile>, Errors> {
+    let json = fs::read_to_string(file_name)
+        .map_err(|source| Errors::FSReadError { path: file_name.to_string(), source })?;
+
+    // the first character tells which of the two it is, so the error is about the right one
+    let parse_error = |source| Errors::ParseJSONError { path: file_name.to_string(), source };
+    let profiles = match json.trim_start().starts_with('[') {
+        true => serde_json::from_str::<Vec<Profile>>(&json).map_err(parse_error)?,
+        false => vec![serde_json::from_str::<Profile>(&json).map_err(parse_error)?],
+    };
+
+    Ok(profiles
+        .into_iter()
+        .map(Profile::with_new_id)
+        .collect_vec())
+}
+
+
+///
+///Reads a profile to compare one called `name` with (see [crate::diff]) from `file_name`:
+///a profile file or a backup of one (`.toml`), a JSON export (`.json`, the profile called `name` if it holds several),
+///or else a list of URLs as for [parse_url_lines], whose invalid lines are skipped.
+///
+pub fn read_profile_to_compare(file_name: &str, name: &str) -> Result<Profile, Errors> {
+    let path = Path::new(file_name);
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match extension.as_str() {
+        // a repaired time does not change what is compared
+        "toml" => parse_toml(file_name, &mut Vec::new()).map(Profile::with_new_id),
+        "json" => {
+            let mut profiles = import_json(file_name)?;
+            match profiles.iter().position(|prfl| prfl.get_name() == name) {
+                Some(idx) => Ok(profiles.swap_remove(idx)),
+                None if profiles.len() == 1 => Ok(profiles.remove(0)),
+                None => Err(Errors::not_found(format!("profile \"{}\" in {}", name, file_name))),
+            }
+        },
+        _ => {
+            let text = fs::read_to_string(file_name)
+                .map_err(|source| Errors::FSReadError { path: file_name.to_string(), source })?;
+            let (pairs, _invalid) = parse_url_lines(&text);
+            let stem = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or(file_name);
+
+            Ok(Profile::builder()
+                .add_name(stem)
+                .add_many_title_url_pairs(pairs)
+                .build())
+        },
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_lines_take_an_optional_title() {
+        let text = "\n# saved on Monday\nhttps://a.com/page\tSome Page\n  example.com/docs  \nnot a url\nhttps://b.com\t \n";
+
+        let (pairs, invalid) = parse_url_lines(text);
+        let pairs: Vec<(&str, &str)> = pairs.iter().map(|pair| (pair.url.as_str(), pair.title.as_str())).collect();
+        assert_eq!(pairs, vec![
+            ("https://a.com/page", "Some Page"),
+            ("https://example.com/docs", DEFAULT_TITLE),
+            ("https://b.com", DEFAULT_TITLE),
+        ]);
+        let invalid: Vec<String> = invalid.iter().map(Errors::to_string).collect();
+        assert_eq!(invalid, vec!["line 5: \"not a url\" is not a valid URL"]);
+    }
+
+    #[test]
+    fn url_lines_accept_windows_line_ends() {
+        let (pairs, invalid) = parse_url_lines("https://a.com\r\nhttps://b.com\tB\r\n");
+
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[1].title, "B");
+        assert!(invalid.is_empty());
     }
-}
\ No newline at end of file
+
+    /// Parses `toml` as if read from a file called `name`, with the warnings it gave
+    fn parse_str_with_errors(name: &str, toml: &str) -> (Result<Profile, Errors>, Vec<Errors>) {
+        let path = std::env::temp_dir().join(format!("ITR_PRFL_test_{}_{}.toml", name, std::process::id()));
+        fs::write(&path, toml).unwrap();
+        let mut errors = Vec::new();
+        let parsed = parse_toml(path.to_str().unwrap(), &mut errors);
+        fs::remove_file(&path).unwrap();
+
+        (parsed, errors)
+    }
+
+    fn parse_str(name: &str, toml: &str) -> Result<Profile, Errors> {
+        parse_str_with_errors(name, toml).0
+    }
+
+    #[test]
+    fn bad_profile_files_say_where() {
+        let general = "[General]\nname = \"Work\"\nid = 1\ntime_created = \"2024::01::02::03::04::05\"\n";
+
+        let profile = parse_str("ok", general).unwrap();
+        assert_eq!(profile.get_name(), "Work");
+
+        let err = parse_str("broken", &format!("{}\n[[BrowserTab]]\nurl = https://a.com\n", general)).unwrap_err();
+        assert!(matches!(err, Errors::ParseTOMLFilError { line: Some(7), .. }), "{:?}", err);
+
+        let err = parse_str("time", &general.replace("2024::01::02", "2024::13::02")).unwrap_err();
+        assert!(err.to_string().ends_with("\"2024::13::02::03::04::05\" is not a time of the form yyyy::mm::dd::hh::mm::ss"));
+
+        assert!(matches!(parse_str("empty", " \n"), Err(Errors::TOMLFileIsEmpty { .. })));
+    }
+
+    #[test]
+    fn tabs_with_bad_times_are_kept() {
+        let toml = "[General]\nname = \"Work\"\nid = 1\ntime_created = \"2024::01::02::03::04::05\"\n\n\
+            [[BrowserTab]]\nurl = \"https://a.com\"\ntitle = \"A\"\nt_created = \"yesterday\"\n\
+            t_opened = [\"2024::01::03::00::00::00\", \"2024::02::30::00::00::00\"]\n";
+
+        let (profile, errors) = parse_str_with_errors("tab_time", toml);
+        let profile = profile.unwrap();
+        let pair = &profile.get_pairs()[0];
+        assert_eq!(pair.get_time_created(), profile.get_time_created());
+        assert_eq!(pair.get_times_opened().len(), 1);
+
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].to_string().contains("\"yesterday\" of the tab https://a.com is not a time"));
+        assert!(matches!(&errors[1], Errors::ParseTimestampError { value, url: Some(_), .. } if value == "2024::02::30::00::00::00"));
+    }
+
+    #[test]
+    fn json_errors_come_from_the_shape_of_the_file() {
+        let path = std::env::temp_dir().join(format!("ITR_PRFL_test_import_{}.json", std::process::id()));
+        let import = |json: &str| {
+            fs::write(&path, json).unwrap();
+            let imported = import_json(path.to_str().unwrap());
+            fs::remove_file(&path).unwrap();
+            imported
+        };
+
+        let err = import(" \n[{\"name\": 1}]").unwrap_err();
+        assert!(err.to_string().contains("invalid type: integer `1`, expected a string"), "{}", err);
+        let err = import("{\"name\": 1}").unwrap_err();
+        assert!(err.to_string().contains("invalid type: integer `1`, expected a string"), "{}", err);
+        assert!(matches!(import("[]"), Ok(profiles) if profiles.is_empty()));
+    }
+}
@@ -0,0 +1,176 @@
+use std::{env, path::Path, process::{Command, Stdio}};
+
+use crate::{debug_println, Errors, Profile};
+
+//
+//
+//Opens the tabs of a PROFILE in a browser
+//
+//
+
+
+
+/// Set this to a browser name (`firefox`, `chrome`, ...) or the path to a browser executable
+/// to change which browser is used when none is given explicitly
+pub const BROWSER_ENV_VAR: &str = "ITR_BROWSER";
+
+
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchMode {
+    /// Incognito / PrivateBrowsing
+    #[default] Private,
+    Normal,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Browser {
+    Firefox,
+    Chrome,
+    Chromium,
+    Brave,
+    Edge,
+
+    /// path to (or name of) any other executable
+    Other(String),
+}
+
+impl Default for Browser {
+    fn default() -> Self {
+        match env::var(BROWSER_ENV_VAR) {
+            Ok(name) if !name.trim().is_empty() => Self::from_name(name.trim()),
+            _ => Self::Firefox,
+        }
+    }
+}
+
+impl Browser {
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "firefox" | "ff" => Self::Firefox,
+            "chrome" | "google-chrome" => Self::Chrome,
+            "chromium" => Self::Chromium,
+            "brave" => Self::Brave,
+            "edge" | "msedge" => Self::Edge,
+            _ => Self::Other(name.to_string()),
+        }
+    }
+
+    fn command(&self) -> &str {
+        match self {
+            Self::Firefox => "firefox",
+            Self::Chrome => "google-chrome",
+            Self::Chromium => "chromium",
+            Self::Brave => "brave-browser",
+            Self::Edge => "microsoft-edge",
+            Self::Other(cmd) => cmd,
+        }
+    }
+
+    /// Firefox and its forks take different flags than everything Chromium-based
+    fn is_firefox_like(&self) -> bool {
+        match self {
+            Self::Firefox => true,
+            Self::Other(cmd) => {
+                let exe = Path::new(cmd)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_lowercase())
+                    .unwrap_or_default();
+
+                ["firefox", "librewolf", "waterfox", "floorp", "zen"]
+                    .iter()
+                    .any(|fork| exe.contains(fork))
+            },
+            _ => false,
+        }
+    }
+
+    fn window_flag(&self, mode: LaunchMode) -> &'static str {
+        match (self.is_firefox_like(), mode) {
+            (true, LaunchMode::Private) => "--private-window",
+            (false, LaunchMode::Private) => "--incognito",
+            (_, LaunchMode::Normal) => "--new-window",
+        }
+    }
+}
+
+
+
+
+///
+///Opens all `urls` as tabs of one new browser window,
+///using a single invocation of `browser`.
+///
+///Does not wait for the browser to exit.
+///
+pub fn launch(urls: &[&str], browser: &Browser, mode: LaunchMode) -> Result<(), Errors> {
+    if urls.is_empty() {
+        return Ok(());
+    }
+
+    debug_println!("LAUNCH: {} {} {:?}", browser.command(), browser.window_flag(mode), urls);
+
+    Command::new(browser.command())
+        .arg(browser.window_flag(mode))
+        .args(urls)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+        .map_err(|source| Errors::BrowserLaunchError { browser: browser.command().to_string(), source })
+}
+
+
+///
+///Opens the `tabs` (a profile and the index of one of its pairs each):
+///every section of a profile in a window of its own, after one window with all the tabs in no section,
+///using one invocation of `browser` per window.
+///The placeholders of [crate::TabKind::Template] tabs are filled with `values`.
+///
+pub fn launch_tabs(tabs: &[(&Profile, usize)], values: &[(String, String)], browser: &Browser, mode: LaunchMode) -> Result<(), Errors> {
+    let mut by_profile: Vec<(&Profile, Vec<usize>)> = Vec::new();
+    for &(prfl, idx) in tabs {
+        match by_profile.iter_mut().find(|(other, _)| other.get_id() == prfl.get_id()) {
+            Some((_, idxs)) => idxs.push(idx),
+            None => by_profile.push((prfl, vec![idx])),
+        }
+    }
+
+    let mut unsectioned: Vec<String> = Vec::new();
+    let mut sections: Vec<Vec<String>> = Vec::new();
+    for (prfl, idxs) in by_profile {
+        for window in prfl.windows(&idxs) {
+            let pairs: Vec<_> = window.iter().map(|&idx| &prfl.get_pairs()[idx]).collect();
+            let urls = pairs.iter().map(|pair| pair.url_to_open(values));
+
+            match pairs[0].get_section().is_empty() {
+                true => unsectioned.extend(urls),
+                false => sections.push(urls.collect()),
+            }
+        }
+    }
+
+    for urls in std::iter::once(unsectioned).chain(sections) {
+        let urls: Vec<&str> = urls.iter().map(String::as_str).collect();
+        launch(&urls, browser, mode)?;
+    }
+
+    Ok(())
+}
+
+
+///
+///Opens every tab of `prfl` but the archived ones (see [Profile::active_pairs]), in the order they are stored,
+///one window per section; see [launch_tabs] for `values`.
+///
+pub fn launch_profile(prfl: &Profile, values: &[(String, String)], browser: &Browser, mode: LaunchMode) -> Result<(), Errors> {
+    let tabs: Vec<(&Profile, usize)> = prfl
+        .active_pairs()
+        .into_iter()
+        .map(|idx| (prfl, idx))
+        .collect();
+
+    launch_tabs(&tabs, values, browser, mode)
+}
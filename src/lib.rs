@@ -1,11 +1,24 @@
 pub mod errors;
 pub mod renderer;
+pub mod backend;
+pub mod app;
+pub mod input;
+pub mod search;
+pub mod query;
 pub mod profile;
 pub mod io;
+pub mod export;
+pub mod urls;
+pub mod launcher;
+pub mod cli;
+pub mod clipboard;
+pub mod diff;
+pub mod history;
 
 
 pub use errors::*;
 pub use renderer::*;
+pub use backend::*;
 pub use profile::*;
 
 use serde::Deserialize;
@@ -37,6 +50,6 @@ macro_rules! write_stdout {
         crossterm::execute!(
             $stdout,
             $($item),+
-        ).map_err(|_| Errors::WriteToStdoutError)
+        ).map_err(|source| Errors::WriteToStdoutError { source })
     };
 }
\ No newline at end of file
@@ -1,49 +1,29 @@
-#![feature(const_trait_impl)]
+use std::{io::{stdout, Stdout}, process::ExitCode};
 
+use firefox_resumer::{*, app::{update, view, App, AppEvent, Effect}, cli, launcher, clipboard::read_clipboard, io::{delete_profile_file, read_profile_to_compare, read_profiles_with_errors, read_settings, save_profile, save_settings}};
 
-use std::io::stdout;
+use crossterm::{self, cursor::MoveTo, event, style::ResetColor, terminal::{self, Clear, ClearType}};
 
-use firefox_resumer::{*, io::read_profiles};
 
-use crossterm::{self, cursor::MoveTo, event::{self, Event, KeyCode}, style::ResetColor, terminal::{self, Clear, ClearType}};
-use once_cell::sync::Lazy;
 
 
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
 
+    let result = match cli::parse_args(&args) {
+        Ok(Some((cmd, opts))) => cli::run(cmd, &opts).map_err(|e| (e, opts)),
+        Ok(None) => main_impl().map_err(|e| (e, cli::Options::default())),
+        Err(e) => Err((e, cli::Options::default())),
+    };
 
-static MOVEMENTS: Lazy<[Event; 2]> = Lazy::new(|| {
-    [Event::Key(KeyCode::Up.into()), Event::Key(KeyCode::Down.into())]
-});
-
-
-fn main() -> Result<(), Errors> {
-    main_impl()?;
-
-    // test();
-    Ok(())
-}
-
-fn test() {
-    let s = chrono::Utc::now().naive_utc().to_string();
-    let want = s
-        .split_once(".")
-        .unwrap().0
-        .to_string()
-        .replace(":", "::")
-        .replace(" ", "::")
-        .replace("-", "::");
-    
-    println!("{}", want);
+    match result {
+        Ok(()) => ExitCode::from(exit_code::SUCCESS),
+        Err((e, opts)) => ExitCode::from(cli::report_error(&e, &opts)),
+    }
 }
 
 
 
-enum WhatToRender<'a> {
-    ListProfiles,
-    Profile(&'a Profile),
-
-    BrowserTab,
-}
 
 fn main_impl() -> Result<(), Errors> {
     let mut stdout = stdout();
@@ -57,155 +37,107 @@ fn main_impl() -> Result<(), Errors> {
 
 
     let _raw = terminal::enable_raw_mode();
-    let profiles = read_profiles();
-
-
-    let prfls = profiles.unwrap_or_default();
-
-    // if prfls.is_empty() {
-    //     render_line(
-    //         &mut stdout,
-    //         " == You currently do not have any profiles, or the reading failed for some reason.",
-    //         Some(COLOR_FG_DECLARE)
-    //     )?;
-    // } else {
-    //     render_list_of_profiles(&mut stdout, &prfls, STATIC_INFO_MAINMENU_LEN, 0, 0)?;
-    // }
-
-
-    /* event loop */
-
-    let mut highlight_idx: Option<usize> = None;
-
-    let mut curr_prfl_idx: Option<usize> = None;
-
-    let mut profile_count = 0usize;
-    let mut render_what = WhatToRender::ListProfiles;
-    loop {
-
-        let event = event::read().map_err(|_| Errors::EventReadFailedError)?;
-
-        // we are currently in the MAIN MENU (choose / add / delete profiles)
-        match render_what {
-            WhatToRender::ListProfiles => {
-                render_beginning(&mut stdout)?;
-
-                if prfls.is_empty() {
-                    render_line(
-                        &mut stdout,
-                        " == You currently do not have any profiles, or the reading failed for some reason.",
-                        Some(COLOR_FG_DECLARE)
-                    )?;
-                } else {
-                    if highlight_idx.is_none() { highlight_idx = Some(0usize); }
-                    render_list_of_profiles(&mut stdout, &prfls, STATIC_INFO_MAINMENU_LEN, 0, highlight_idx.clone().unwrap())?;
-                    profile_count = prfls.len();
-                }
-        
-                match event {
-                    /* ESC */
-                    _ if event == Event::Key(KeyCode::Esc.into()) => {
-                        write_stdout!(
-                            stdout,
-                            Clear(ClearType::All),
-            
-                            MoveTo(0, 0),
-                            ResetColor
-                        )?;
-                        break;
-                    }
-                    
-                    
-                    /* UP AND DOWN */
-                    _ if event == Event::Key(KeyCode::Up.into()) => {
-                        if let Some(ref mut idx) = highlight_idx {
-                            *idx = match idx.checked_sub(1) {
-                                Some(idx) => idx,
-                                None => profile_count - 1,
-                            };
-        
-                        }
-                    }
-                    _ if event == Event::Key(KeyCode::Down.into()) => {
-                        if let Some(ref mut idx) = highlight_idx {
-                            match *idx + 1 < profile_count {
-                                true => {
-                                    *idx += 1;
-                                },
-                                false => {
-                                    *idx = 0;
-                                },
-                            }
-        
-                        }
-                    }
-        
-                    /* SELECTING CURR PROFILE */
-                    _ if event == Event::Key(KeyCode::Enter.into()) => {
-                        if let Some(ref idx) = highlight_idx {
-                            if let Some(prfl) = prfls.get(*idx) {
-                                render_what = WhatToRender::Profile(prfl);
-                                highlight_idx = match prfl.get_pairs().len() > 0 {
-                                    true => Some(0),
-                                    false => None,
-                                };
-                            }
-                        }
-                    }
-        
-        
-                    /* EVERYTHING ELSE: === DO NOTHING === */
-                    _ => {       
-                    }
-                }
-            },
-            WhatToRender::Profile(prfl) => {
-                render_one_profile(&mut stdout, prfl, STATIC_INFO_MAINMENU_LEN, 0, highlight_idx)?;
-
-                match event {
-                    /* ESC -> go back to previous level */
-                    _ if event == Event::Key(KeyCode::Esc.into()) => {
-                        render_what = WhatToRender::ListProfiles;
-                        highlight_idx = curr_prfl_idx;
-                    }
-                    
-                    
-                    /* UP AND DOWN */
-                    _ if event == Event::Key(KeyCode::Up.into()) => {
-                        if let Some(ref mut idx) = highlight_idx {
-                            *idx = match idx.checked_sub(1) {
-                                Some(idx) => idx,
-                                None => profile_count - 1,
-                            };
-        
-                        }
-                    }
-                    _ if event == Event::Key(KeyCode::Down.into()) => {
-                        if let Some(ref mut idx) = highlight_idx {
-                            match *idx + 1 < profile_count {
-                                true => {
-                                    *idx += 1;
-                                },
-                                false => {
-                                    *idx = 0;
-                                },
-                            }
-        
-                        }
-                    }
-
-                    _ => {}
-                }
-
-                
-            },
-            WhatToRender::BrowserTab => {
-
-            },
+
+    let result = event_loop(&mut stdout);
+
+    /* leave the terminal the way we found it, whether or not something failed */
+    let _raw = terminal::disable_raw_mode();
+    write_stdout!(
+        stdout,
+        Clear(ClearType::All),
+        MoveTo(0, 0),
+        ResetColor
+    )?;
+
+    result
+}
+
+///
+///Runs the TUI until ESC is pressed in the main menu.
+///
+///Only failures of the terminal itself end it early,
+///everything else is shown in the status line.
+///
+fn event_loop(stdout: &mut Stdout) -> Result<(), Errors> {
+
+    let mut status: Option<String> = None;
+
+    let prfls = match read_profiles_with_errors() {
+        Ok((prfls, errors)) => {
+            if let Some(err) = errors.first() {
+                status = Some(format!(" == {} problem(s) reading your profiles: {}", errors.len(), err));
+            }
+            prfls
+        },
+        Err(e) => {
+            status = Some(format!(" == Could not read your profiles: {}", e));
+            Vec::new()
+        },
+    };
+
+    let settings = read_settings().unwrap_or_else(|e| {
+        status = Some(format!(" == Could not read your settings: {}", e));
+        Default::default()
+    });
+
+    let mut app = App::new(prfls, stdout.size()?)
+        .with_profile_sort(settings.profile_sort)
+        .with_status(status);
+
+    /* event loop: draw what is, then wait for what changes it */
+    while app.is_running() {
+        view(&app, stdout)?;
+
+        let event = event::read().map_err(|source| Errors::EventReadFailedError { source })?;
+        app = update(app, AppEvent::Terminal(event));
+
+        /* effects may queue more effects (pasting queues saving) */
+        let mut effects = app.take_effects();
+        while !effects.is_empty() {
+            for effect in effects {
+                let outcome = run_effect(&mut app, effect);
+                app = update(app, outcome);
+            }
+            effects = app.take_effects();
         }
-        
-        
-        
     }
+
     Ok(())
 }
+
+
+/// Does what `effect` asks for, and returns how it went
+fn run_effect(app: &mut App, effect: Effect) -> AppEvent {
+    match effect {
+        Effect::ReadClipboard { prfl_idx } => AppEvent::Pasted { prfl_idx, text: read_clipboard() },
+        Effect::Save { prfl_idx } => {
+            let result = match app.get_mut_profile(prfl_idx) {
+                Some(prfl) => save_profile(prfl),
+                None => Ok(()),
+            };
+            AppEvent::Saved { prfl_idx, result }
+        },
+        Effect::Launch { tabs, values, mode } => {
+            let to_open: Vec<(&Profile, usize)> = tabs
+                .iter()
+                .filter_map(|&(prfl_idx, tab_idx)| {
+                    let prfl = app.get_profiles().get(prfl_idx)?;
+                    (tab_idx < prfl.get_pairs().len()).then_some((prfl, tab_idx))
+                })
+                .collect();
+
+            let result = launcher::launch_tabs(&to_open, &values, &launcher::Browser::default(), mode);
+            AppEvent::Launched { tabs, values, at: chrono::Utc::now().naive_utc(), result }
+        },
+        Effect::SaveSettings { settings } => AppEvent::SettingsSaved { result: save_settings(&settings) },
+        Effect::Delete { file_name } => {
+            let result = delete_profile_file(&file_name);
+            AppEvent::Deleted { file_name, result }
+        },
+        Effect::ReadToCompare { prfl_idx, path } => {
+            let name = app.get_profiles().get(prfl_idx).map(|prfl| prfl.get_name().clone()).unwrap_or_default();
+            let result = read_profile_to_compare(&path, &name);
+            AppEvent::ReadToCompare { prfl_idx, path, result }
+        },
+    }
+}
@@ -10,20 +10,104 @@ use std::sync::{
 };
 
 
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
 use chrono::{self, NaiveDateTime};
 use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 
 
-use crate::Errors;
+use crate::{urls::{fill_placeholders, fill_url_placeholders, host_of, normalize_url, placeholders, DATE_PLACEHOLDER}, Errors};
 
-/// The value is read from the TOML file, OR it is 0 by default
+/// Starts at 0 and is kept above every id read from a TOML file
 static PROFILE_ID_COUNTER: Lazy<AtomicUsize> = Lazy::new(|| {
     AtomicUsize::new(0)
 });
 
+/// How many launches of a tab are remembered
+pub const LAUNCH_HISTORY_LIMIT: usize = 20;
+/// How many values of a placeholder of a [TabKind::Template] are remembered
+pub const RECENT_VALUES_LIMIT: usize = 10;
+
+///
+///The section stale pairs are archived in (see [Profile::stale_pairs]):
+///always the last one, and left out when the whole profile is opened.
+///Pairs only get there by being archived, see [is_reserved_section].
+///
+pub const ARCHIVE_SECTION: &str = "Archive";
+
+/// Whether `section` is the [ARCHIVE_SECTION] (ignoring case), which no pair can be put in by name
+pub fn is_reserved_section(section: &str) -> bool {
+    section.trim().eq_ignore_ascii_case(ARCHIVE_SECTION)
+}
+
+
+///
+///Splits what was typed into tags: separated by commas or whitespace,
+///a leading `#` dropped, and every tag only once (ignoring case).
+///
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+
+    for tag in text.split(|c: char| c == ',' || c.is_whitespace()) {
+        let tag = tag.trim_start_matches('#');
+        if !tag.is_empty() && !has_tag(&tags, tag) {
+            tags.push(tag.to_string());
+        }
+    }
+
+    tags
+}
+
+fn note_contains(note: &str, text: &str) -> bool {
+    !note.is_empty() && note.to_lowercase().contains(&text.to_lowercase())
+}
+
+fn has_tag(tags: &[String], tag: &str) -> bool {
+    tags.iter().any(|other| other.to_lowercase() == tag.to_lowercase())
+}
+
+/// Adds the `tags` missing in `into`
+fn add_tags(into: &mut Vec<String>, tags: &[String]) {
+    for tag in tags {
+        if !has_tag(into, tag) {
+            into.push(tag.clone());
+        }
+    }
+}
+
+/// Both notes, separated by an empty line, unless one of them says it all already
+fn join_notes(note: &str, other: &str) -> String {
+    match (note.is_empty(), other.is_empty()) {
+        (_, true) => note.to_string(),
+        (true, false) => other.to_string(),
+        (false, false) if note.contains(other) => note.to_string(),
+        (false, false) => format!("{}\n\n{}", note, other),
+    }
+}
+
+
+
+/// What the URL of a tab is
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TabKind {
+    /// a page, opened as it is
+    #[default] Page,
+    /// with placeholders like `{q}` (see [placeholders]), filled with values asked for whenever it is opened
+    Template,
+}
+
+impl TabKind {
+    pub fn is_page(&self) -> bool {
+        *self == Self::Page
+    }
+}
 
 
-#[derive(Debug, Default, PartialEq, Eq)]
+/// The order of the tabs of a profile
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SortMode {
     ByTitle,
     ByTitleRev,
@@ -32,11 +116,282 @@ pub enum SortMode {
     ByURLRev,
 
     #[default] ByDateCreation,
-    ByDateCreationRev
+    ByDateCreationRev,
+
+    /// the order they are stored in, as arranged by the user; new tabs go to the end
+    Manual,
+}
+
+impl SortMode {
+    /// In the order the sort key cycles through them
+    pub const ALL: [SortMode; 7] = [
+        SortMode::ByDateCreation,
+        SortMode::ByDateCreationRev,
+        SortMode::ByTitle,
+        SortMode::ByTitleRev,
+        SortMode::ByURL,
+        SortMode::ByURLRev,
+        SortMode::Manual,
+    ];
+
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|&mode| mode == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    /// For the header of the TUI
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::ByTitle => "title A-Z",
+            SortMode::ByTitleRev => "title Z-A",
+            SortMode::ByURL => "URL A-Z",
+            SortMode::ByURLRev => "URL Z-A",
+            SortMode::ByDateCreation => "oldest first",
+            SortMode::ByDateCreationRev => "newest first",
+            SortMode::Manual => "manual order",
+        }
+    }
+
+    /// Whether `a` comes before `b`; titles ignore case
+    pub fn compare(self, a: &URLTitlePair, b: &URLTitlePair) -> Ordering {
+        match self {
+            SortMode::ByTitle => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+            SortMode::ByTitleRev => b.title.to_lowercase().cmp(&a.title.to_lowercase()),
+            SortMode::ByURL => a.url.cmp(&b.url),
+            SortMode::ByURLRev => b.url.cmp(&a.url),
+            SortMode::ByDateCreation => a.t_created.cmp(&b.t_created),
+            SortMode::ByDateCreationRev => b.t_created.cmp(&a.t_created),
+            SortMode::Manual => Ordering::Equal,
+        }
+    }
+}
+
+
+/// Where [Profile::move_pairs] moves tabs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabMove {
+    Up,
+    Down,
+    Top,
+    Bottom,
+}
+
+
+/// What [Profile::split_plan] splits by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitBy {
+    Host,
+    /// the first tag of a pair itself
+    Tag,
+}
+
+impl SplitBy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "host" => Some(Self::Host),
+            "tag" => Some(Self::Tag),
+            _ => None,
+        }
+    }
+}
+
+/// How many pairs [Profile::merge_pairs] added, and how many it merged into ones that were there already
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MergeCount {
+    pub added: usize,
+    pub merged: usize,
+}
+
+
+/// A row of the profile view, see [Profile::rows]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PairRow {
+    /// `count` is how many pairs are in it
+    Section { name: String, collapsed: bool, count: usize },
+    Pair { tab_idx: usize },
+}
+
+/// 0 for no section, so those pairs come first
+fn section_rank(sections: &[String], section: &str) -> usize {
+    sections
+        .iter()
+        .position(|other| other == section)
+        .map_or(0, |idx| idx + 1)
+}
+
+fn compare_pairs(sections: &[String], mode: SortMode, a: &URLTitlePair, b: &URLTitlePair) -> Ordering {
+    section_rank(sections, &a.section)
+        .cmp(&section_rank(sections, &b.section))
+        .then_with(|| mode.compare(a, b))
+}
+
+
+/// The order of the list of profiles
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProfileSortMode {
+    #[default] ByName,
+    /// most tabs first
+    ByTabCount,
+    /// most recently changed first
+    ByLastModified,
+    /// most recently opened first, never opened ones last
+    ByLastOpened,
+}
+
+impl ProfileSortMode {
+    /// In the order the sort key cycles through them
+    pub const ALL: [ProfileSortMode; 4] = [
+        ProfileSortMode::ByName,
+        ProfileSortMode::ByTabCount,
+        ProfileSortMode::ByLastModified,
+        ProfileSortMode::ByLastOpened,
+    ];
+
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|&mode| mode == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    /// For the header of the TUI
+    pub fn label(self) -> &'static str {
+        match self {
+            ProfileSortMode::ByName => "name",
+            ProfileSortMode::ByTabCount => "most tabs",
+            ProfileSortMode::ByLastModified => "last modified",
+            ProfileSortMode::ByLastOpened => "last opened",
+        }
+    }
+
+    /// Whether `a` comes before `b`; names ignore case
+    pub fn compare(self, a: &Profile, b: &Profile) -> Ordering {
+        match self {
+            ProfileSortMode::ByName => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            ProfileSortMode::ByTabCount => b.pairs.len().cmp(&a.pairs.len()),
+            ProfileSortMode::ByLastModified => b.t_last_modified.cmp(&a.t_last_modified),
+            ProfileSortMode::ByLastOpened => b.get_time_last_opened().cmp(&a.get_time_last_opened()),
+        }
+    }
+}
+
+///
+///Every tag in `prfls`, with how many tabs have it (themselves or through their profile),
+///sorted by name (ignoring case).
+///
+pub fn tag_counts(prfls: &[Profile]) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+
+    for prfl in prfls {
+        let tags = prfl.tags.iter().chain(prfl.pairs.iter().flat_map(|pair| pair.tags.iter()));
+        for tag in tags {
+            if !counts.iter().any(|(other, _)| other.to_lowercase() == tag.to_lowercase()) {
+                counts.push((tag.clone(), 0));
+            }
+        }
+    }
+    for (tag, count) in &mut counts {
+        *count = prfls
+            .iter()
+            .map(|prfl| (0..prfl.pairs.len()).filter(|&idx| prfl.pair_has_tag(idx, tag)).count())
+            .sum();
+    }
+    counts.sort_by_key(|(tag, _)| tag.to_lowercase());
+
+    counts
+}
+
+/// Sorts `prfls` by `mode`, keeping the order of equal ones
+pub fn sort_profiles(prfls: &mut [Profile], mode: ProfileSortMode) {
+    prfls.sort_by(|a, b| mode.compare(a, b));
+}
+
+
+///
+///`work / rust/` as `work/rust`: the groups a profile is in, outermost first,
+///separated by `/`; empty if it is in none.
+///
+pub fn normalize_group(group: &str) -> String {
+    group
+        .split('/')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
 }
 
+/// Whether `group` is `outer` or one of the groups inside it; everything is inside the empty group
+pub fn group_is_in(group: &str, outer: &str) -> bool {
+    outer.is_empty() || group == outer || group.strip_prefix(outer).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// A row of the main menu, see [profile_tree]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeRow {
+    /// the whole `path` of the group; `depth` is how many groups it is in
+    Group { path: String, depth: usize, collapsed: bool },
+    Profile { prfl_idx: usize, depth: usize },
+}
+
+///
+///`prfls` as a tree of their groups: every group is followed by the groups inside it
+///(by name, ignoring case) and then by its profiles (in the order of `prfls`),
+///unless its path is one of `collapsed`. The profiles in no group come last.
+///
+pub fn profile_tree(prfls: &[Profile], collapsed: &[String]) -> Vec<TreeRow> {
+    let mut rows = Vec::new();
+    add_tree_level(prfls, collapsed, "", 0, &mut rows);
+
+    rows
+}
+
+/// The indices of `prfls` in the order of [profile_tree] with no group collapsed, i.e. by group
+pub fn profile_order(prfls: &[Profile]) -> Vec<usize> {
+    profile_tree(prfls, &[])
+        .into_iter()
+        .filter_map(|row| match row {
+            TreeRow::Profile { prfl_idx, .. } => Some(prfl_idx),
+            TreeRow::Group { .. } => None,
+        })
+        .collect()
+}
+
+fn add_tree_level(prfls: &[Profile], collapsed: &[String], parent: &str, depth: usize, rows: &mut Vec<TreeRow>) {
+    let mut children: Vec<String> = prfls
+        .iter()
+        .filter(|prfl| prfl.group != parent && group_is_in(&prfl.group, parent))
+        .filter_map(|prfl| {
+            let rest = match parent.is_empty() {
+                true => prfl.group.as_str(),
+                false => &prfl.group[parent.len() + 1..],
+            };
+            let child = rest.split('/').next()?;
+            Some(match parent.is_empty() {
+                true => child.to_string(),
+                false => format!("{}/{}", parent, child),
+            })
+        })
+        .collect();
+    children.sort_by_key(|path| path.to_lowercase());
+    children.dedup();
+
+    for path in children {
+        let is_collapsed = collapsed.contains(&path);
+        rows.push(TreeRow::Group { path: path.clone(), depth, collapsed: is_collapsed });
+        if !is_collapsed {
+            add_tree_level(prfls, collapsed, &path, depth + 1, rows);
+        }
+    }
+
+    rows.extend(
+        prfls
+            .iter()
+            .enumerate()
+            .filter(|(_, prfl)| prfl.group == parent)
+            .map(|(prfl_idx, _)| TreeRow::Profile { prfl_idx, depth })
+    );
+}
 
-#[derive(Debug, Default, Clone)]
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct URLTitlePair {
     // pub url: Arc<Mutex<String>>,
     // pub title: Arc<Mutex<String>>,
@@ -45,7 +400,29 @@ pub struct URLTitlePair {
 
 
     t_created: chrono::NaiveDateTime,
+    /// when the tab was opened from here, oldest first, at most [LAUNCH_HISTORY_LIMIT]
+    #[serde(default)]
+    t_opened: Vec<chrono::NaiveDateTime>,
+
+    /// see [parse_tags]
+    #[serde(default)]
+    tags: Vec<String>,
+    /// free text, possibly several lines; empty if there is none
+    #[serde(default)]
+    note: String,
+    /// one of the sections of its profile, or empty if it is in none
+    #[serde(default)]
+    section: String,
+    /// the section it was in before it was archived, while it is (see [URLTitlePair::is_archived])
+    #[serde(default)]
+    archived_from: String,
+    #[serde(default)]
+    kind: TabKind,
+    /// for a [TabKind::Template], the values its placeholders were opened with, newest first
+    #[serde(default)]
+    recent: BTreeMap<String, Vec<String>>,
 
+    #[serde(skip)]
     is_highlighted: bool,
 }
 
@@ -58,6 +435,13 @@ impl URLTitlePair {
             // title: Arc::new(Mutex::new(String::from(title))),
 
             t_created: chrono::Utc::now().naive_utc(),
+            t_opened: Vec::new(),
+            tags: Vec::new(),
+            note: String::new(),
+            section: String::new(),
+            archived_from: String::new(),
+            kind: TabKind::Page,
+            recent: BTreeMap::new(),
 
             is_highlighted: false,
         }
@@ -68,10 +452,166 @@ impl URLTitlePair {
             url,
             title,
             t_created,
+            t_opened: Vec::new(),
+            tags: Vec::new(),
+            note: String::new(),
+            section: String::new(),
+            archived_from: String::new(),
+            kind: TabKind::Page,
+            recent: BTreeMap::new(),
             is_highlighted: false,
         }
     }
 
+    /// Restores the launch history read from a save
+    pub fn with_times_opened(mut self, t_opened: Vec<NaiveDateTime>) -> Self {
+        self.t_opened = t_opened;
+        self
+    }
+
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn get_tags(&self) -> &[String] {
+        &self.tags
+    }
+    /// Ignoring case
+    pub fn has_tag(&self, tag: &str) -> bool {
+        has_tag(&self.tags, tag)
+    }
+
+    pub fn with_note(mut self, note: String) -> Self {
+        self.note = note;
+        self
+    }
+
+    pub fn get_note(&self) -> &str {
+        &self.note
+    }
+    /// Whether the note contains `text`, ignoring case
+    pub fn note_contains(&self, text: &str) -> bool {
+        note_contains(&self.note, text)
+    }
+
+    pub fn with_section(mut self, section: String) -> Self {
+        self.section = section.trim().to_string();
+        self
+    }
+
+    pub fn with_archived_from(mut self, section: String) -> Self {
+        self.archived_from = section;
+        self
+    }
+
+    pub fn with_kind(mut self, kind: TabKind) -> Self {
+        self.kind = kind;
+        self
+    }
+    pub fn get_kind(&self) -> TabKind {
+        self.kind
+    }
+    pub fn is_template(&self) -> bool {
+        self.kind == TabKind::Template
+    }
+
+    /// Restores the values remembered by [URLTitlePair::remember_values]
+    pub fn with_recent_values(mut self, recent: BTreeMap<String, Vec<String>>) -> Self {
+        self.recent = recent;
+        self
+    }
+    pub fn get_all_recent_values(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.recent
+    }
+    /// The values the placeholder `name` was opened with, newest first
+    pub fn get_recent_values(&self, name: &str) -> &[String] {
+        self.recent.get(name).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// The names of the placeholders to fill when opening it, none unless it is a [TabKind::Template]
+    pub fn url_placeholders(&self) -> Vec<String> {
+        match self.kind {
+            TabKind::Page => Vec::new(),
+            TabKind::Template => placeholders(&self.url),
+        }
+    }
+    /// The URL to open, with the placeholders of a [TabKind::Template] filled with `values` (see [fill_url_placeholders])
+    pub fn url_to_open(&self, values: &[(String, String)]) -> String {
+        match self.kind {
+            TabKind::Page => self.url.clone(),
+            TabKind::Template => fill_url_placeholders(&self.url, values),
+        }
+    }
+    /// Keeps the `values` of its placeholders as the newest ones, at most [RECENT_VALUES_LIMIT] of each
+    pub fn remember_values(&mut self, values: &[(String, String)]) {
+        for name in self.url_placeholders() {
+            let Some((_, value)) = values.iter().find(|(other, _)| *other == name) else {
+                continue;
+            };
+            let recent = self.recent.entry(name).or_default();
+            recent.retain(|other| other != value);
+            recent.insert(0, value.clone());
+            recent.truncate(RECENT_VALUES_LIMIT);
+        }
+    }
+
+    pub fn get_section(&self) -> &str {
+        &self.section
+    }
+    /// The section it goes back to when it is no longer archived, empty for none
+    pub fn get_archived_from(&self) -> &str {
+        &self.archived_from
+    }
+
+    pub fn get_time_created(&self) -> chrono::NaiveDateTime {
+        self.t_created
+    }
+
+    /// Oldest first
+    pub fn get_times_opened(&self) -> &[NaiveDateTime] {
+        &self.t_opened
+    }
+    pub fn get_time_last_opened(&self) -> Option<NaiveDateTime> {
+        self.t_opened.last().copied()
+    }
+    /// When it was last opened, or else added
+    pub fn get_time_last_used(&self) -> NaiveDateTime {
+        self.get_time_last_opened().unwrap_or(self.t_created)
+    }
+
+    /// Whether it is in the [ARCHIVE_SECTION]
+    pub fn is_archived(&self) -> bool {
+        self.section == ARCHIVE_SECTION
+    }
+    pub fn record_opened(&mut self, at: NaiveDateTime) {
+        self.t_opened.push(at);
+
+        let excess = self.t_opened.len().saturating_sub(LAUNCH_HISTORY_LIMIT);
+        self.t_opened.drain(..excess);
+    }
+
+    ///
+    ///Takes in what `other` (a pair of the same page) knows: the earlier creation time,
+    ///both launch histories, all tags and both notes. Title and URL stay, and so does the section unless there is none.
+    ///
+    pub fn merge(&mut self, other: URLTitlePair) {
+        self.t_created = self.t_created.min(other.t_created);
+
+        self.t_opened.extend(other.t_opened);
+        self.t_opened.sort_unstable();
+        self.t_opened.dedup();
+        let excess = self.t_opened.len().saturating_sub(LAUNCH_HISTORY_LIMIT);
+        self.t_opened.drain(..excess);
+
+        add_tags(&mut self.tags, &other.tags);
+        self.note = join_notes(&self.note, &other.note);
+        if self.section.is_empty() {
+            self.section = other.section;
+            self.archived_from = other.archived_from;
+        }
+    }
+
     pub fn is_highlighted(self) -> bool {
         self.is_highlighted
     }
@@ -110,17 +650,40 @@ impl URLTitlePair {
 }
 
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Profile {
     id: usize,
     name: String,
 
     pairs: Vec<URLTitlePair>,
     sort: SortMode,
+    /// see [parse_tags]; every tab of the profile counts as tagged with them too
+    #[serde(default)]
+    tags: Vec<String>,
+    /// free text, possibly several lines; empty if there is none
+    #[serde(default)]
+    note: String,
+    /// see [normalize_group]
+    #[serde(default)]
+    group: String,
+    /// the names the pairs use as their section, in the order they are shown and opened in;
+    /// the pairs in no section come first
+    #[serde(default)]
+    sections: Vec<String>,
+    /// how many days a pair may go unopened before it is stale, see [Profile::stale_pairs]; never if [None]
+    #[serde(default)]
+    expire_after: Option<u32>,
+    /// a base to create other profiles from, see [Profile::from_template]
+    #[serde(default)]
+    template: bool,
 
 
     t_created: chrono::NaiveDateTime,
-    t_last_modified: chrono::NaiveDateTime
+    t_last_modified: chrono::NaiveDateTime,
+
+    /// the ITR_PRFL_*.toml file this profile was read from / is saved to
+    #[serde(skip)]
+    file_name: Option<String>,
 }
 
 impl Profile {
@@ -128,33 +691,82 @@ impl Profile {
         ProfileBuilder::default()
     }
 
-    pub fn get_sort_mode(self) -> SortMode {
-        self.sort
+    pub fn get_sort_mode(&self) -> &SortMode {
+        &self.sort
     }
     pub fn change_sort_mode(&mut self, mode: SortMode) {
         if self.sort == mode { return; }
         
         self.sort = mode;
+        self.sort_pairs();
+
+        self.last_modified()
+    }
+
+    /// Puts the pairs in the order of their sections and then of the sort mode, keeping the order of equal ones
+    fn sort_pairs(&mut self) {
+        let mode = self.sort;
+        let sections = std::mem::take(&mut self.sections);
+        self.pairs.sort_by(|a, b| compare_pairs(&sections, mode, a, b));
+        self.sections = sections;
+    }
 
-        match self.sort {
-            SortMode::ByTitle => self.pairs.sort_unstable_by(|a, b| a.title.cmp(&b.title)),
-            SortMode::ByTitleRev => self.pairs.sort_unstable_by(|a, b| b.title.cmp(&a.title)),
-            SortMode::ByURL => self.pairs.sort_unstable_by(|a, b| a.url.cmp(&b.url)),
-            SortMode::ByURLRev => self.pairs.sort_unstable_by(|a, b| b.url.cmp(&a.url)),
-            SortMode::ByDateCreation => self.pairs.sort_unstable_by(|a, b| a.t_created.cmp(&b.t_created)),
-            SortMode::ByDateCreationRev => self.pairs.sort_unstable_by(|a, b| b.t_created.cmp(&a.t_created)),
+    ///
+    ///Adds the sections pairs are in but the list is missing, drops the ones no pair is in any more
+    ///and sorts the pairs again.
+    ///
+    fn fix_sections(&mut self) {
+        for pair in &self.pairs {
+            if !pair.section.is_empty() && !self.sections.contains(&pair.section) {
+                self.sections.push(pair.section.clone());
+            }
         }
+        let pairs = &self.pairs;
+        self.sections.retain(|section| pairs.iter().any(|pair| &pair.section == section));
 
-        self.last_modified()
+        self.archive_last();
+        self.sort_pairs()
+    }
+
+    fn archive_last(&mut self) {
+        if let Some(idx) = self.sections.iter().position(|section| section == ARCHIVE_SECTION) {
+            let archive = self.sections.remove(idx);
+            self.sections.push(archive);
+        }
     }
 
-    pub fn get_id(self) -> usize {
+    pub fn get_id(&self) -> usize {
         self.id
     }
 
+    /// Gives a profile that did not come from the builder (e.g. an imported one)
+    /// an id of its own
+    pub(crate) fn with_new_id(mut self) -> Self {
+        self.id = PROFILE_ID_COUNTER.fetch_add(1, AtomicOrdering::SeqCst);
+
+        self
+    }
+
+    pub fn get_file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+    pub(crate) fn set_file_name(&mut self, file_name: &str) {
+        self.file_name = Some(file_name.to_string());
+    }
+
     pub fn get_name(&self) -> &String {
         &self.name
     }
+    pub fn get_group(&self) -> &str {
+        &self.group
+    }
+    /// Moves the profile into `group` (see [normalize_group]), or out of every group if it is empty
+    pub fn set_group(&mut self, group: &str) {
+        self.group = normalize_group(group);
+
+        self.last_modified()
+    }
+
     pub fn change_name(&mut self, new_name: &str) {
         self.name.clear();
         self.name.push_str(new_name);
@@ -162,6 +774,308 @@ impl Profile {
         self.last_modified()
     }
 
+    pub fn get_tags(&self) -> &[String] {
+        &self.tags
+    }
+    /// Ignoring case
+    pub fn has_tag(&self, tag: &str) -> bool {
+        has_tag(&self.tags, tag)
+    }
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = tags;
+
+        self.last_modified()
+    }
+
+    pub fn get_note(&self) -> &str {
+        &self.note
+    }
+    /// Whether the note contains `text`, ignoring case
+    pub fn note_contains(&self, text: &str) -> bool {
+        note_contains(&self.note, text)
+    }
+    pub fn set_note(&mut self, note: String) {
+        self.note = note;
+
+        self.last_modified()
+    }
+    pub fn set_pair_note(&mut self, idx: usize, note: String) {
+        if let Some(pair) = self.pairs.get_mut(idx) {
+            pair.note = note;
+            self.last_modified();
+        }
+    }
+
+    pub fn get_sections(&self) -> &[String] {
+        &self.sections
+    }
+    /// Puts the pairs at `idxs` into `section` (a new one goes last), or out of every section if it is empty
+    pub fn set_pair_section(&mut self, idxs: &[usize], section: &str) {
+        let section = section.trim();
+        for &idx in idxs {
+            if let Some(pair) = self.pairs.get_mut(idx) {
+                pair.section = section.to_string();
+                if section != ARCHIVE_SECTION {
+                    pair.archived_from.clear();
+                }
+            }
+        }
+
+        self.fix_sections();
+        self.last_modified()
+    }
+    ///
+    ///Renames the section `old` to `new`, merging it into `new` if that exists already;
+    ///its pairs end up in no section if `new` is empty.
+    ///
+    pub fn rename_section(&mut self, old: &str, new: &str) {
+        let new = new.trim();
+        if old == new { return; }
+
+        for pair in self.pairs.iter_mut().filter(|pair| pair.section == old) {
+            pair.section = new.to_string();
+            if new != ARCHIVE_SECTION {
+                pair.archived_from.clear();
+            }
+        }
+        if !new.is_empty() && !self.sections.iter().any(|section| section == new) {
+            if let Some(section) = self.sections.iter_mut().find(|section| *section == old) {
+                *section = new.to_string();
+            }
+        }
+
+        self.fix_sections();
+        self.last_modified()
+    }
+    /// Moves the section `name` (with all its pairs) one step or all the way up or down
+    pub fn move_section(&mut self, name: &str, mv: TabMove) {
+        let Some(idx) = self.sections.iter().position(|section| section == name) else { return; };
+
+        let section = self.sections.remove(idx);
+        let to = match mv {
+            TabMove::Up => idx.saturating_sub(1),
+            TabMove::Down => (idx + 1).min(self.sections.len()),
+            TabMove::Top => 0,
+            TabMove::Bottom => self.sections.len(),
+        };
+        self.sections.insert(to, section);
+
+        self.archive_last();
+        self.sort_pairs();
+        self.last_modified()
+    }
+
+    ///
+    ///The rows of the profile view: first the pairs in no section,
+    ///then every section followed by its pairs, unless its name is one of `collapsed`.
+    ///
+    pub fn rows(&self, collapsed: &[String]) -> Vec<PairRow> {
+        let mut rows: Vec<PairRow> = self.pairs
+            .iter()
+            .enumerate()
+            .filter(|(_, pair)| pair.section.is_empty())
+            .map(|(tab_idx, _)| PairRow::Pair { tab_idx })
+            .collect();
+
+        for section in &self.sections {
+            let is_collapsed = collapsed.contains(section);
+            let tab_idxs = self.pairs
+                .iter()
+                .enumerate()
+                .filter(|(_, pair)| &pair.section == section)
+                .map(|(tab_idx, _)| tab_idx);
+
+            rows.push(PairRow::Section { name: section.clone(), collapsed: is_collapsed, count: tab_idxs.clone().count() });
+            if !is_collapsed {
+                rows.extend(tab_idxs.map(|tab_idx| PairRow::Pair { tab_idx }));
+            }
+        }
+
+        rows
+    }
+
+    /// The pairs at `idxs` split by section, in the order of the sections: one browser window each
+    pub fn windows(&self, idxs: &[usize]) -> Vec<Vec<usize>> {
+        let mut windows: Vec<(usize, Vec<usize>)> = Vec::new();
+
+        for &idx in idxs {
+            let Some(pair) = self.pairs.get(idx) else { continue; };
+            let rank = section_rank(&self.sections, &pair.section);
+
+            match windows.iter_mut().find(|(other, _)| *other == rank) {
+                Some((_, window)) => window.push(idx),
+                None => windows.push((rank, vec![idx])),
+            }
+        }
+        windows.sort_by_key(|(rank, _)| *rank);
+
+        windows.into_iter().map(|(_, window)| window).collect()
+    }
+
+    pub fn get_expire_after(&self) -> Option<u32> {
+        self.expire_after
+    }
+    /// `0` days counts as never
+    pub fn set_expire_after(&mut self, days: Option<u32>) {
+        self.expire_after = days.filter(|&days| days > 0);
+        self.last_modified()
+    }
+
+    ///
+    ///The indices of the pairs not opened (or, if never opened, added) for at least [Profile::get_expire_after] days
+    ///before `now`, leaving out the archived ones; none if the profile has no such policy.
+    ///
+    pub fn stale_pairs(&self, now: NaiveDateTime) -> Vec<usize> {
+        let Some(days) = self.expire_after else {
+            return Vec::new();
+        };
+        let limit = now - chrono::Duration::days(i64::from(days));
+
+        self.pairs
+            .iter()
+            .enumerate()
+            .filter(|(_, pair)| !pair.is_archived() && pair.get_time_last_used() <= limit)
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    pub fn is_template(&self) -> bool {
+        self.template
+    }
+    pub fn set_template(&mut self, template: bool) {
+        self.template = template;
+        self.last_modified()
+    }
+
+    ///
+    ///The names of the placeholders (see [placeholders]) in the URLs and titles of the pairs and in the name,
+    ///which [Profile::from_template] asks values for; [DATE_PLACEHOLDER] is left out, it fills itself.
+    ///
+    pub fn template_placeholders(&self) -> Vec<String> {
+        let mut names = placeholders(&self.name);
+        // a template tab keeps its placeholders for when it is opened
+        for pair in self.pairs.iter().filter(|pair| !pair.is_template()) {
+            for name in placeholders(&pair.url).into_iter().chain(placeholders(&pair.title)) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        names.retain(|name| name != DATE_PLACEHOLDER);
+
+        names
+    }
+
+    ///
+    ///A new profile called `name` with the pairs of this one, as if they were just added,
+    ///but with their tags, notes and sections. The placeholders in the name, URLs and titles
+    ///are filled with `values` (percent-encoded in the URLs), see [fill_placeholders].
+    ///It gets the group, sort mode, tags, note, sections and expiry of this one, and is no template.
+    ///
+    pub fn from_template(&self, name: &str, values: &[(String, String)]) -> Profile {
+        let pairs = self.pairs
+            .iter()
+            .map(|pair| {
+                let new_pair = match pair.kind {
+                    TabKind::Page => URLTitlePair::new(&fill_url_placeholders(&pair.url, values), &fill_placeholders(&pair.title, values)),
+                    TabKind::Template => URLTitlePair::new(&pair.url, &pair.title).with_kind(TabKind::Template),
+                };
+                new_pair
+                    .with_tags(pair.tags.clone())
+                    .with_note(pair.note.clone())
+                    .with_section(pair.section.clone())
+            })
+            .collect();
+
+        Profile::builder()
+            .add_name(&fill_placeholders(name, values))
+            .add_many_title_url_pairs(pairs)
+            .set_sort_mode(self.sort)
+            .set_tags(self.tags.clone())
+            .set_note(self.note.clone())
+            .set_group(&self.group)
+            .set_sections(self.sections.clone())
+            .set_expire_after(self.expire_after)
+            .build()
+    }
+
+    /// A copy of everything in this profile, under a new id and `name`
+    pub fn duplicate(&self, name: &str) -> Profile {
+        Profile::builder()
+            .add_name(name)
+            .add_many_title_url_pairs(self.pairs.clone())
+            .set_sort_mode(self.sort)
+            .set_tags(self.tags.clone())
+            .set_note(self.note.clone())
+            .set_group(&self.group)
+            .set_sections(self.sections.clone())
+            .set_expire_after(self.expire_after)
+            .set_template(self.template)
+            .build()
+    }
+
+    /// The indices of the pairs opened with the whole profile: all but the archived ones
+    pub fn active_pairs(&self) -> Vec<usize> {
+        self.pairs
+            .iter()
+            .enumerate()
+            .filter(|(_, pair)| !pair.is_archived())
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Moves the pairs at `idxs` into the [ARCHIVE_SECTION], remembering the sections they were in
+    pub fn archive_pairs(&mut self, idxs: &[usize]) {
+        for &idx in idxs {
+            if let Some(pair) = self.pairs.get_mut(idx).filter(|pair| !pair.is_archived()) {
+                pair.archived_from = pair.section.clone();
+            }
+        }
+
+        self.set_pair_section(idxs, ARCHIVE_SECTION)
+    }
+
+    ///
+    ///Moves the archived pairs among `idxs` back to the sections they were archived from
+    ///(see [Profile::archive_pairs]); returns how many there were.
+    ///
+    pub fn unarchive_pairs(&mut self, idxs: &[usize]) -> usize {
+        let mut count = 0;
+        for &idx in idxs {
+            if let Some(pair) = self.pairs.get_mut(idx).filter(|pair| pair.is_archived()) {
+                pair.section = std::mem::take(&mut pair.archived_from);
+                count += 1;
+            }
+        }
+
+        if count > 0 {
+            self.fix_sections();
+            self.last_modified();
+        }
+        count
+    }
+
+    /// Whether the pair at `idx` has `tag`, itself or through this profile
+    pub fn pair_has_tag(&self, idx: usize, tag: &str) -> bool {
+        self.has_tag(tag) || self.pairs.get(idx).is_some_and(|pair| pair.has_tag(tag))
+    }
+    pub fn set_pair_tags(&mut self, idx: usize, tags: Vec<String>) {
+        if let Some(pair) = self.pairs.get_mut(idx) {
+            pair.tags = tags;
+            self.last_modified();
+        }
+    }
+    /// Adds the `tags` the pairs at `idxs` do not have yet
+    pub fn add_pair_tags(&mut self, idxs: &[usize], tags: &[String]) {
+        for &idx in idxs {
+            if let Some(pair) = self.pairs.get_mut(idx) {
+                add_tags(&mut pair.tags, tags);
+            }
+        }
+
+        self.last_modified()
+    }
+
     pub fn get_pairs(&self) -> &Vec<URLTitlePair> {
         &self.pairs
     }
@@ -169,6 +1083,264 @@ impl Profile {
         &mut self.pairs
     }
 
+    /// Index of the pair pointing to the same page as `url`, if any
+    pub fn find_url(&self, url: &str) -> Option<usize> {
+        let url = normalize_url(url);
+
+        self.pairs
+            .iter()
+            .position(|pair| normalize_url(&pair.url) == url)
+    }
+
+    ///
+    ///Adds `pair` where the sort mode puts it (behind the equal ones),
+    ///unless a pair with the same (normalized) URL already exists.
+    ///
+    pub fn add_pair(&mut self, pair: URLTitlePair) -> Result<(), Errors> {
+        if self.find_url(&pair.url).is_some() {
+            return Err(Errors::PairAlreadyExistsError { url: pair.url, title: pair.title });
+        }
+
+        if !pair.section.is_empty() && !self.sections.contains(&pair.section) {
+            self.sections.push(pair.section.clone());
+        }
+
+        let mode = self.sort;
+        let idx = self.pairs.partition_point(|other| compare_pairs(&self.sections, mode, other, &pair) != Ordering::Greater);
+        self.pairs.insert(idx, pair);
+        self.last_modified();
+
+        Ok(())
+    }
+
+    ///
+    ///Changes URL and title of the pair at `idx`,
+    ///unless another pair already has the (normalized) URL.
+    ///
+    pub fn edit_pair(&mut self, idx: usize, url: &str, title: &str) -> Result<(), Errors> {
+        let normalized = normalize_url(url);
+        let taken = self.pairs
+            .iter()
+            .enumerate()
+            .any(|(i, pair)| i != idx && normalize_url(&pair.url) == normalized);
+        if taken {
+            return Err(Errors::PairAlreadyExistsError { url: url.to_string(), title: title.to_string() });
+        }
+
+        let pair = self.pairs
+            .get_mut(idx)
+            .ok_or_else(|| Errors::not_found(format!("tab {}", idx + 1)))?;
+        pair.update_url(url)?;
+        pair.update_title(title)?;
+
+        self.last_modified();
+        Ok(())
+    }
+
+    ///
+    ///Adds the `pairs` where the sort mode puts them, keeping everything they know;
+    ///a pair whose (normalized) URL is here already is merged into that one instead (see [URLTitlePair::merge]).
+    ///
+    pub fn merge_pairs(&mut self, pairs: Vec<URLTitlePair>) -> MergeCount {
+        let mut count = MergeCount::default();
+
+        for pair in pairs {
+            match self.find_url(&pair.url) {
+                Some(idx) => {
+                    self.pairs[idx].merge(pair);
+                    count.merged += 1;
+                },
+                None => {
+                    let _ = self.add_pair(pair);
+                    count.added += 1;
+                },
+            }
+        }
+
+        // merged pairs may have become older, or gained a section
+        self.fix_sections();
+        self.last_modified();
+
+        count
+    }
+
+    ///
+    ///Takes `other` into this profile: its pairs as in [Profile::merge_pairs],
+    ///plus its tags, its note and the order of its sections. Name and group stay.
+    ///
+    pub fn merge_profile(&mut self, other: Profile) -> MergeCount {
+        add_tags(&mut self.tags, &other.tags);
+        self.note = join_notes(&self.note, &other.note);
+        for section in other.sections {
+            if !self.sections.contains(&section) {
+                self.sections.push(section);
+            }
+        }
+
+        self.merge_pairs(other.pairs)
+    }
+
+    /// Removes the pairs at `idxs` and gives them back, in the order they were in
+    pub fn remove_pairs(&mut self, idxs: &[usize]) -> Vec<URLTitlePair> {
+        let mut idxs: Vec<usize> = idxs.iter().copied().filter(|&idx| idx < self.pairs.len()).collect();
+        idxs.sort_unstable();
+        idxs.dedup();
+
+        let mut removed: Vec<URLTitlePair> = idxs
+            .iter()
+            .rev()
+            .map(|&idx| self.pairs.remove(idx))
+            .collect();
+        removed.reverse();
+
+        self.fix_sections();
+        self.last_modified();
+
+        removed
+    }
+
+    ///
+    ///Moves the pairs at `idxs` into a new profile called `name`,
+    ///which gets the group, sort mode, tags and order of sections of this one.
+    ///
+    pub fn split_off(&mut self, idxs: &[usize], name: &str) -> Profile {
+        let pairs = self.remove_pairs(idxs);
+
+        Profile::builder()
+            .add_name(name)
+            .add_many_title_url_pairs(pairs)
+            .set_sort_mode(self.sort)
+            .set_tags(self.tags.clone())
+            .set_group(&self.group)
+            .set_sections(self.sections.clone())
+            .build()
+    }
+
+    ///
+    ///The indices of the pairs for every profile [Profile::split_off] should make when splitting `by`,
+    ///with what they have in common, ordered by that (ignoring case).
+    ///Pairs without a host (or, by tag, without a tag of their own) stay.
+    ///
+    pub fn split_plan(&self, by: SplitBy) -> Vec<(String, Vec<usize>)> {
+        let mut plan: Vec<(String, Vec<usize>)> = Vec::new();
+
+        for (idx, pair) in self.pairs.iter().enumerate() {
+            let key = match by {
+                SplitBy::Host => host_of(&pair.url),
+                SplitBy::Tag => pair.tags.first().cloned(),
+            };
+            let Some(key) = key else { continue; };
+
+            match plan.iter_mut().find(|(other, _)| other.to_lowercase() == key.to_lowercase()) {
+                Some((_, idxs)) => idxs.push(idx),
+                None => plan.push((key, vec![idx])),
+            }
+        }
+        plan.sort_by_key(|(key, _)| key.to_lowercase());
+
+        plan
+    }
+
+    /// Remembers that the pairs at `idxs` were opened `at` that time
+    pub fn record_opened(&mut self, idxs: &[usize], at: NaiveDateTime) {
+        for &idx in idxs {
+            if let Some(pair) = self.pairs.get_mut(idx) {
+                pair.record_opened(at);
+            }
+        }
+    }
+
+    ///
+    ///Takes the launch history and the remembered values of every pair from the pair of the same page
+    ///in `current`, this profile as it is now: undoing an edit does not undo opening its tabs.
+    ///
+    pub fn keep_launches(&mut self, current: &Profile) {
+        for pair in &mut self.pairs {
+            if let Some(idx) = current.find_url(&pair.url) {
+                let launched = &current.pairs[idx];
+                pair.t_opened = launched.t_opened.clone();
+                pair.recent = launched.recent.clone();
+            }
+        }
+    }
+
+    /// The pairs at `idxs` were opened with `values` for their placeholders, see [URLTitlePair::remember_values]
+    pub fn remember_values(&mut self, idxs: &[usize], values: &[(String, String)]) {
+        for &idx in idxs {
+            if let Some(pair) = self.pairs.get_mut(idx) {
+                pair.remember_values(values);
+            }
+        }
+    }
+
+    ///
+    ///Makes the pair at `idx` a [TabKind::Template] or a page again;
+    ///fails for a template without placeholders, which would be no different from a page.
+    ///
+    pub fn set_pair_kind(&mut self, idx: usize, kind: TabKind) -> Result<(), Errors> {
+        let Some(pair) = self.pairs.get_mut(idx) else {
+            return Ok(());
+        };
+        if kind == TabKind::Template && placeholders(&pair.url).is_empty() {
+            return Err(Errors::NoPlaceholderError { url: pair.url.clone() });
+        }
+
+        pair.kind = kind;
+        self.last_modified();
+
+        Ok(())
+    }
+
+    ///
+    ///Moves the pairs at `idxs` one step or all the way up or down, keeping their order among each other;
+    ///a pair moving by one step skips over the whole block of other moving pairs it is part of.
+    ///
+    ///The profile is switched to [SortMode::Manual], since the order is now up to the user.
+    ///Pairs stay in their section.
+    ///
+    pub fn move_pairs(&mut self, idxs: &[usize], mv: TabMove) {
+        let moving = |idx: &usize| idxs.contains(idx);
+        let mut order: Vec<usize> = (0..self.pairs.len()).collect();
+
+        match mv {
+            TabMove::Up => for i in 1..order.len() {
+                if moving(&order[i]) && !moving(&order[i - 1]) {
+                    order.swap(i - 1, i);
+                }
+            },
+            TabMove::Down => for i in (0..order.len().saturating_sub(1)).rev() {
+                if moving(&order[i]) && !moving(&order[i + 1]) {
+                    order.swap(i, i + 1);
+                }
+            },
+            // stable, so both groups keep their order
+            TabMove::Top => order.sort_by_key(|idx| !moving(idx)),
+            TabMove::Bottom => order.sort_by_key(moving),
+        }
+
+        let mut pairs: Vec<Option<URLTitlePair>> = std::mem::take(&mut self.pairs).into_iter().map(Some).collect();
+        self.pairs = order
+            .into_iter()
+            .filter_map(|idx| pairs[idx].take())
+            .collect();
+
+        self.sort = SortMode::Manual;
+        self.sort_pairs();
+        self.last_modified();
+    }
+
+    pub fn remove_pair(&mut self, idx: usize) -> Option<URLTitlePair> {
+        if idx >= self.pairs.len() {
+            return None;
+        }
+
+        let pair = self.pairs.remove(idx);
+        self.fix_sections();
+        self.last_modified();
+
+        Some(pair)
+    }
+
 
     pub fn get_time_created(&self) -> chrono::NaiveDateTime {
         self.t_created
@@ -177,6 +1349,15 @@ impl Profile {
         self.t_last_modified
     }
 
+    /// When any of the tabs was last opened, if ever
+    pub fn get_time_last_opened(&self) -> Option<NaiveDateTime> {
+        self.pairs
+            .iter()
+            .filter_map(|pair| pair.t_opened.last())
+            .max()
+            .copied()
+    }
+
     #[inline(always)]
     fn last_modified(&mut self) {
         self.t_last_modified = chrono::Utc::now().naive_utc()
@@ -189,7 +1370,16 @@ pub struct ProfileBuilder {
     name: Option<String>,
 
     pairs: Option<Vec<URLTitlePair>>,
-    sort: SortMode
+    sort: SortMode,
+    tags: Vec<String>,
+    note: String,
+    group: String,
+    sections: Vec<String>,
+    expire_after: Option<u32>,
+    template: bool,
+
+    t_created: Option<chrono::NaiveDateTime>,
+    t_last_modified: Option<chrono::NaiveDateTime>,
 }
 
 impl Default for ProfileBuilder {
@@ -200,12 +1390,29 @@ impl Default for ProfileBuilder {
             name: None,
             pairs: None,
             sort: SortMode::default(),
+            tags: Vec::new(),
+            note: String::new(),
+            group: String::new(),
+            sections: Vec::new(),
+            expire_after: None,
+            template: false,
+
+            t_created: None,
+            t_last_modified: None,
         }
     }
 }
 
 
 impl ProfileBuilder {
+    /// The id read from a save; the ids given out later are all greater
+    pub(crate) fn set_id(mut self, id: usize) -> Self {
+        self.id = id;
+        PROFILE_ID_COUNTER.fetch_max(id + 1, AtomicOrdering::SeqCst);
+
+        self
+    }
+
     pub fn add_name(mut self, name: &str) -> Self {
         self.name = Some(name.to_string());
 
@@ -237,20 +1444,482 @@ impl ProfileBuilder {
         self
     }
 
+    pub fn set_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+
+        self
+    }
+
+    pub fn set_note(mut self, note: String) -> Self {
+        self.note = note;
+
+        self
+    }
+
+    pub fn set_group(mut self, group: &str) -> Self {
+        self.group = normalize_group(group);
+
+        self
+    }
+
+    /// The order of the sections; the ones the pairs are in but missing here go last
+    pub fn set_sections(mut self, sections: Vec<String>) -> Self {
+        self.sections = sections;
+
+        self
+    }
+
+    /// See [Profile::set_expire_after]
+    pub fn set_expire_after(mut self, days: Option<u32>) -> Self {
+        self.expire_after = days.filter(|&days| days > 0);
+
+        self
+    }
+
+    pub fn set_template(mut self, template: bool) -> Self {
+        self.template = template;
+
+        self
+    }
+
+    pub fn set_time_created(mut self, t_created: chrono::NaiveDateTime) -> Self {
+        self.t_created = Some(t_created);
+
+        self
+    }
+
+    /// Defaults to the time created
+    pub fn set_time_last_modified(mut self, t_last_modified: chrono::NaiveDateTime) -> Self {
+        self.t_last_modified = Some(t_last_modified);
+
+        self
+    }
+
+    /// The pairs end up in the order of their sections and the sort mode
     pub fn build(self) -> Profile {
 
-        let t_created = chrono::Utc::now().naive_utc();
+        let t_created = self.t_created.unwrap_or(chrono::Utc::now().naive_utc());
 
         
-        Profile {
+        let mut prfl = Profile {
             id: self.id,
             name: self.name.unwrap_or(format!("# Unnamed Profile No. {}", self.id)),
             
             pairs: self.pairs.unwrap_or_default(),
             sort: self.sort,
+            tags: self.tags,
+            note: self.note,
+            group: self.group,
+            sections: self.sections,
+            expire_after: self.expire_after,
+            template: self.template,
 
             t_created,
-            t_last_modified: t_created
+            t_last_modified: self.t_last_modified.unwrap_or(t_created),
+
+            file_name: None,
+        };
+        prfl.fix_sections();
+
+        prfl
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32) -> NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(2024, 1, day).unwrap().and_hms_opt(12, 0, 0).unwrap()
+    }
+
+    fn pair(url: &str, title: &str, day: u32) -> URLTitlePair {
+        URLTitlePair::from_save(url.to_string(), title.to_string(), at(day))
+    }
+
+    fn titles(prfl: &Profile) -> Vec<&str> {
+        prfl.get_pairs().iter().map(|pair| pair.title.as_str()).collect()
+    }
+
+    fn sectioned(url: &str, section: &str) -> URLTitlePair {
+        URLTitlePair::new(url, url).with_section(section.to_string())
+    }
+
+    fn sections_of(prfl: &Profile) -> Vec<(&str, &str)> {
+        prfl.get_pairs()
+            .iter()
+            .map(|pair| (pair.url.as_str(), pair.get_section()))
+            .collect()
+    }
+
+    #[test]
+    fn sort_modes_cycle_through_all() {
+        let mut mode = SortMode::default();
+        for _ in 0..SortMode::ALL.len() {
+            mode = mode.next();
+        }
+        assert_eq!(mode, SortMode::default());
+        assert_eq!(SortMode::ByDateCreation.next(), SortMode::ByDateCreationRev);
+        assert_eq!(ProfileSortMode::ByLastOpened.next(), ProfileSortMode::ByName);
+    }
+
+    #[test]
+    fn tabs_stay_in_the_order_of_the_sort_mode() {
+        let mut prfl = Profile::builder()
+            .add_many_title_url_pairs(vec![
+                pair("https://b.com", "beta", 2),
+                pair("https://c.com", "Alpha", 3),
+                pair("https://a.com", "gamma", 1),
+            ])
+            .build();
+        assert_eq!(titles(&prfl), ["gamma", "beta", "Alpha"]);
+
+        prfl.change_sort_mode(SortMode::ByTitle);
+        assert_eq!(titles(&prfl), ["Alpha", "beta", "gamma"]);
+        prfl.change_sort_mode(SortMode::ByURLRev);
+        assert_eq!(titles(&prfl), ["Alpha", "beta", "gamma"]);
+        prfl.change_sort_mode(SortMode::ByDateCreationRev);
+        assert_eq!(titles(&prfl), ["Alpha", "beta", "gamma"]);
+
+        // new tabs go where the mode puts them
+        prfl.change_sort_mode(SortMode::ByTitleRev);
+        prfl.add_pair(pair("https://d.com", "delta", 4)).unwrap();
+        assert_eq!(titles(&prfl), ["gamma", "delta", "beta", "Alpha"]);
+    }
+
+    #[test]
+    fn profiles_sort_by_name_size_or_time() {
+        let prfl = |name: &str, tabs: usize, day: u32| {
+            let pairs = (0..tabs).map(|n| pair(&format!("https://{}.com/{}", name, n), "", 1)).collect();
+            Profile::builder().add_name(name).add_many_title_url_pairs(pairs).set_time_last_modified(at(day)).build()
+        };
+        let mut prfls = vec![prfl("b", 1, 3), prfl("C", 3, 1), prfl("a", 2, 2)];
+        let names = |prfls: &[Profile]| prfls.iter().map(|prfl| prfl.get_name().clone()).collect::<Vec<_>>();
+
+        sort_profiles(&mut prfls, ProfileSortMode::ByName);
+        assert_eq!(names(&prfls), ["a", "b", "C"]);
+        sort_profiles(&mut prfls, ProfileSortMode::ByTabCount);
+        assert_eq!(names(&prfls), ["C", "a", "b"]);
+        sort_profiles(&mut prfls, ProfileSortMode::ByLastModified);
+        assert_eq!(names(&prfls), ["b", "a", "C"]);
+
+        // never opened ones go last, keeping their order
+        prfls[2].record_opened(&[0], at(5));
+        sort_profiles(&mut prfls, ProfileSortMode::ByLastOpened);
+        assert_eq!(names(&prfls), ["C", "b", "a"]);
+    }
+
+    #[test]
+    fn moved_tabs_keep_their_order_and_skip_each_other() {
+        let letters = |prfl: &Profile| prfl.get_pairs().iter().map(|pair| pair.title.clone()).collect::<String>();
+        let mut prfl = Profile::builder()
+            .add_many_title_url_pairs((1..=6).map(|n| pair(&format!("https://{}.com", n), &"abcdef"[n as usize - 1..n as usize], n)).collect())
+            .build();
+
+        prfl.move_pairs(&[1, 3], TabMove::Up);
+        assert_eq!(letters(&prfl), "badcef");
+        assert_eq!(*prfl.get_sort_mode(), SortMode::Manual);
+
+        prfl.move_pairs(&[0, 1], TabMove::Down);
+        assert_eq!(letters(&prfl), "dbacef");
+        prfl.move_pairs(&[4, 1], TabMove::Top);
+        assert_eq!(letters(&prfl), "bedacf");
+        prfl.move_pairs(&[0, 5], TabMove::Bottom);
+        assert_eq!(letters(&prfl), "edacbf");
+
+        // nothing moves past the ends
+        prfl.move_pairs(&[0], TabMove::Up);
+        prfl.move_pairs(&[5], TabMove::Down);
+        assert_eq!(letters(&prfl), "edacbf");
+
+        // new tabs go to the end in manual order
+        prfl.add_pair(pair("https://0.com", "z", 7)).unwrap();
+        assert_eq!(letters(&prfl), "edacbfz");
+    }
+
+    #[test]
+    fn tags_are_split_and_kept_once() {
+        assert_eq!(parse_tags("#work, Rust  rust,#,todo"), ["work", "Rust", "todo"]);
+        assert_eq!(parse_tags(" , "), Vec::<String>::new());
+    }
+
+    #[test]
+    fn tags_are_counted_per_tab() {
+        let mut work = Profile::builder()
+            .add_name("Work")
+            .add_many_title_url_pairs(vec![pair("https://a.com", "a", 1), pair("https://b.com", "b", 2)])
+            .set_tags(vec![String::from("job")])
+            .build();
+        work.set_pair_tags(0, parse_tags("rust"));
+        work.add_pair_tags(&[0, 1], &parse_tags("Rust, docs"));
+        assert_eq!(work.get_pairs()[0].get_tags(), ["rust", "docs"]);
+        assert_eq!(work.get_pairs()[1].get_tags(), ["Rust", "docs"]);
+
+        let mut home = Profile::builder()
+            .add_name("Home")
+            .add_many_title_url_pairs(vec![pair("https://c.com", "c", 3)])
+            .build();
+        home.set_pair_tags(0, parse_tags("JOB"));
+
+        // the tabs of a tagged profile have its tags too
+        assert!(work.pair_has_tag(1, "JOB"));
+        assert!(!home.pair_has_tag(0, "rust"));
+        assert_eq!(
+            tag_counts(&[work, home]),
+            [(String::from("docs"), 2), (String::from("job"), 3), (String::from("rust"), 2)]
+        );
+    }
+
+    #[test]
+    fn notes_are_searched_ignoring_case() {
+        let mut prfl = Profile::builder()
+            .add_many_title_url_pairs(vec![pair("https://a.com", "a", 1), pair("https://b.com", "b", 2)])
+            .set_note(String::from("Ask Kim\nabout the LAUNCH"))
+            .build();
+        prfl.set_pair_note(1, String::from("Überblick"));
+
+        assert!(prfl.note_contains("launch"));
+        assert!(prfl.get_pairs()[1].note_contains("üBER"));
+        // an empty note contains nothing, not even ""
+        assert!(!prfl.get_pairs()[0].note_contains(""));
+        assert!(prfl.get_pairs()[0].get_note().is_empty());
+    }
+
+    #[test]
+    fn group_paths_are_normalized_and_nested() {
+        assert_eq!(normalize_group(" work / rust/ "), "work/rust");
+        assert_eq!(normalize_group("//"), "");
+
+        assert!(group_is_in("work/rust", "work"));
+        assert!(group_is_in("work", "work"));
+        assert!(group_is_in("work", ""));
+        assert!(!group_is_in("workshop", "work"));
+        assert!(!group_is_in("work", "work/rust"));
+    }
+
+    #[test]
+    fn groups_come_before_their_profiles() {
+        let prfl = |name: &str, group: &str| Profile::builder().add_name(name).set_group(group).build();
+        let prfls = [prfl("A", "work/rust"), prfl("B", "work"), prfl("C", ""), prfl("D", "Home")];
+        let group = |path: &str, depth: usize, collapsed: bool| TreeRow::Group { path: path.to_string(), depth, collapsed };
+
+        assert_eq!(
+            profile_tree(&prfls, &[]),
+            [
+                group("Home", 0, false),
+                TreeRow::Profile { prfl_idx: 3, depth: 1 },
+                group("work", 0, false),
+                group("work/rust", 1, false),
+                TreeRow::Profile { prfl_idx: 0, depth: 2 },
+                TreeRow::Profile { prfl_idx: 1, depth: 1 },
+                TreeRow::Profile { prfl_idx: 2, depth: 0 },
+            ]
+        );
+        assert_eq!(
+            profile_tree(&prfls, &[String::from("work")]),
+            [
+                group("Home", 0, false),
+                TreeRow::Profile { prfl_idx: 3, depth: 1 },
+                group("work", 0, true),
+                TreeRow::Profile { prfl_idx: 2, depth: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn sections_keep_their_tabs_together() {
+        let letters = |prfl: &Profile| prfl.get_pairs().iter().map(|pair| pair.title.clone()).collect::<String>();
+        let mut prfl = Profile::builder()
+            .add_many_title_url_pairs((1..=5).map(|n| pair(&format!("https://{}.com", n), &"abcde"[n as usize - 1..n as usize], n)).collect())
+            .build();
+
+        prfl.set_pair_section(&[1, 3], " Later ");
+        assert_eq!(letters(&prfl), "acebd");
+        assert_eq!(
+            prfl.rows(&[]),
+            [
+                PairRow::Pair { tab_idx: 0 },
+                PairRow::Pair { tab_idx: 1 },
+                PairRow::Pair { tab_idx: 2 },
+                PairRow::Section { name: String::from("Later"), collapsed: false, count: 2 },
+                PairRow::Pair { tab_idx: 3 },
+                PairRow::Pair { tab_idx: 4 },
+            ]
+        );
+
+        prfl.set_pair_section(&[0], "Now");
+        prfl.move_section("Now", TabMove::Top);
+        assert_eq!(prfl.get_sections(), ["Now", "Later"]);
+        assert_eq!(letters(&prfl), "ceabd");
+        // one window for the tabs in no section, then one per section
+        assert_eq!(prfl.windows(&[4, 0, 2, 3, 1]), [vec![0, 1], vec![2], vec![4, 3]]);
+
+        // renaming onto an existing section merges them
+        prfl.rename_section("Later", "Now");
+        assert_eq!(prfl.get_sections(), ["Now"]);
+        assert_eq!(letters(&prfl), "ceabd");
+        assert_eq!(
+            prfl.rows(&[String::from("Now")]),
+            [
+                PairRow::Pair { tab_idx: 0 },
+                PairRow::Pair { tab_idx: 1 },
+                PairRow::Section { name: String::from("Now"), collapsed: true, count: 3 },
+            ]
+        );
+
+        prfl.rename_section("Now", "");
+        assert!(prfl.get_sections().is_empty());
+        assert_eq!(letters(&prfl), "abcde");
+    }
+
+    #[test]
+    fn merging_keeps_the_oldest_and_joins_the_rest() {
+        let mut prfl = Profile::builder()
+            .add_name("Reading")
+            .add_many_title_url_pairs(vec![
+                URLTitlePair::from_save("https://a.com/".to_string(), "A".to_string(), at(3))
+                    .with_tags(vec!["rust".to_string()])
+                    .with_note("first".to_string()),
+            ])
+            .build();
+
+        let count = prfl.merge_pairs(vec![
+            URLTitlePair::from_save("https://A.com#top".to_string(), "A again".to_string(), at(1))
+                .with_times_opened(vec![at(2)])
+                .with_tags(vec!["books".to_string(), "Rust".to_string()])
+                .with_note("second".to_string())
+                .with_section("Later".to_string()),
+            URLTitlePair::from_save("https://b.com".to_string(), "B".to_string(), at(5)),
+        ]);
+        assert_eq!(count, MergeCount { added: 1, merged: 1 });
+
+        let merged = &prfl.get_pairs()[prfl.find_url("https://a.com").unwrap()];
+        assert_eq!((merged.url.as_str(), merged.title.as_str()), ("https://a.com/", "A"));
+        assert_eq!(merged.t_created, at(1));
+        assert_eq!(merged.get_times_opened(), &[at(2)]);
+        assert_eq!(merged.get_tags(), &["rust", "books"]);
+        assert_eq!(merged.get_note(), "first\n\nsecond");
+        assert_eq!(merged.get_section(), "Later");
+        assert_eq!(prfl.get_sections(), &["Later"]);
+        assert!(prfl.find_url("https://b.com/").is_some());
+    }
+
+    #[test]
+    fn splitting_groups_pairs_by_host_or_tag() {
+        let prfl = Profile::builder()
+            .add_name("Reading")
+            .add_many_title_url_pairs(vec![
+                URLTitlePair::new("https://www.rust-lang.org/learn", "Learn").with_tags(vec!["Rust".to_string()]),
+                URLTitlePair::new("about:blank", "Blank").with_tags(vec!["rust".to_string(), "misc".to_string()]),
+                URLTitlePair::new("https://docs.rs/chrono", "Chrono").with_tags(vec!["crates".to_string()]),
+                URLTitlePair::new("https://rust-lang.org/tools", "Tools"),
+            ])
+            .build();
+        let idxs = |url: &str| prfl.find_url(url).unwrap();
+
+        assert_eq!(prfl.split_plan(SplitBy::Host), vec![
+            ("docs.rs".to_string(), vec![idxs("https://docs.rs/chrono")]),
+            ("rust-lang.org".to_string(), vec![idxs("https://www.rust-lang.org/learn"), idxs("https://rust-lang.org/tools")]),
+        ]);
+
+        let mut by_tag = prfl.split_plan(SplitBy::Tag);
+        for (_, pair_idxs) in by_tag.iter_mut() {
+            pair_idxs.sort_unstable();
         }
+        let mut rust = vec![idxs("https://www.rust-lang.org/learn"), idxs("about:blank")];
+        rust.sort_unstable();
+        assert_eq!(by_tag, vec![
+            ("crates".to_string(), vec![idxs("https://docs.rs/chrono")]),
+            ("Rust".to_string(), rust),
+        ]);
+    }
+
+    #[test]
+    fn templates_fill_their_placeholders() {
+        let template = Profile::builder()
+            .add_name("Search {q}")
+            .add_many_title_url_pairs(vec![
+                pair("https://www.youtube.com/results?search_query={q}", "{q} on YouTube", 1)
+                    .with_tags(vec![String::from("video")]),
+                pair("https://news.example.com/{date}/{page}", "News of {date}", 2),
+            ])
+            .set_group("search")
+            .set_template(true)
+            .build();
+        assert_eq!(template.template_placeholders(), ["q", "page"]);
+
+        let values = [(String::from("q"), String::from("rust & c")), (String::from("page"), String::from("2"))];
+        let prfl = template.from_template("Rust {q}", &values);
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+        assert_eq!(prfl.get_name(), "Rust rust & c");
+        assert!(!prfl.is_template());
+        assert_ne!(prfl.get_id(), template.get_id());
+        assert_eq!(prfl.get_group(), "search");
+        assert_eq!(prfl.get_pairs()[0].url, "https://www.youtube.com/results?search_query=rust%20%26%20c");
+        assert_eq!(prfl.get_pairs()[0].title, "rust & c on YouTube");
+        assert_eq!(prfl.get_pairs()[0].get_tags(), ["video"]);
+        assert_eq!(prfl.get_pairs()[1].url, format!("https://news.example.com/{}/2", today));
+        // they are new tabs, not the ones of the template
+        assert!(prfl.get_pairs()[0].get_time_created() > at(1));
+    }
+
+    #[test]
+    fn clones_keep_everything_but_the_id() {
+        let mut prfl = Profile::builder()
+            .add_name("Work")
+            .add_many_title_url_pairs(vec![pair("https://a.com", "a", 1)])
+            .set_tags(vec![String::from("job")])
+            .set_template(true)
+            .build();
+        prfl.set_pair_section(&[0], "Later");
+
+        let copy = prfl.duplicate("Work 2");
+        assert_eq!(copy.get_name(), "Work 2");
+        assert_ne!(copy.get_id(), prfl.get_id());
+        assert!(copy.is_template());
+        assert_eq!(copy.get_tags(), ["job"]);
+        assert_eq!(copy.get_sections(), ["Later"]);
+        assert_eq!(copy.get_pairs()[0].get_time_created(), at(1));
+    }
+
+    #[test]
+    fn archived_pairs_go_back_to_their_sections() {
+        let mut prfl = Profile::builder()
+            .add_name("Reading")
+            .add_many_title_url_pairs(vec![sectioned("https://a.com", "Work"), sectioned("https://b.com", ""), sectioned("https://c.com", "Work")])
+            .build();
+
+        prfl.archive_pairs(&[0, 1]);
+        assert_eq!(prfl.get_pairs().iter().filter(|pair| pair.is_archived()).count(), 2);
+        assert_eq!(prfl.get_sections(), &["Work", ARCHIVE_SECTION]);
+        assert_eq!(prfl.active_pairs().len(), 1);
+
+        // archiving again does not forget where they came from
+        let archived: Vec<usize> = (0..3).filter(|&idx| prfl.get_pairs()[idx].is_archived()).collect();
+        prfl.archive_pairs(&archived);
+        assert_eq!(prfl.unarchive_pairs(&[0, 1, 2]), 2);
+
+        let mut sections = sections_of(&prfl);
+        sections.sort_unstable();
+        assert_eq!(sections, vec![("https://a.com", "Work"), ("https://b.com", ""), ("https://c.com", "Work")]);
+        assert_eq!(prfl.get_sections(), &["Work"]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn moving_out_of_the_archive_forgets_the_section() {
+        let mut prfl = Profile::builder()
+            .add_name("Reading")
+            .add_many_title_url_pairs(vec![sectioned("https://a.com", "Work")])
+            .build();
+
+        prfl.archive_pairs(&[0]);
+        prfl.set_pair_section(&[0], "Later");
+        assert_eq!(prfl.get_pairs()[0].get_archived_from(), "");
+        assert_eq!(prfl.unarchive_pairs(&[0]), 0);
+        assert!(is_reserved_section(" archive "));
+    }
+}
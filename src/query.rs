@@ -0,0 +1,378 @@
+use chrono::NaiveDate;
+
+use crate::{
+    search::{fuzzy_match, score_pair, PairMatch},
+    urls::host_of,
+    Errors,
+    Profile,
+    URLTitlePair,
+};
+
+//
+//
+//Structured queries for tabs, e.g.
+//    host:youtube.com tag:music added:>2024-01-01 -title:live
+//Every term has to match; `-` in front of a term negates it,
+//and terms without a field are matched fuzzily (see the search module)
+//or found in the note of the tab.
+//
+//
+
+
+
+pub const QUERY_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// `field:` prefixes that make a term a field; anything else before a `:` is text
+const FIELD_NAMES: [&str; 8] = ["host", "url", "title", "tag", "note", "profile", "added", "opened"];
+
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextField {
+    /// the host, or any of its subdomains
+    Host,
+    URL,
+    Title,
+    /// one of the tags of the tab or its profile, exactly (ignoring case)
+    Tag,
+    /// the note of the tab or its profile
+    Note,
+    /// the name of the profile the tab is in
+    Profile,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateField {
+    /// when the tab was added
+    Added,
+    /// when the tab was last opened; never opened tabs only match `opened:never`
+    Opened,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Before,
+    BeforeOrOn,
+    On,
+    OnOrAfter,
+    After,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    /// fuzzy, on title, URL and host, or a substring of the note of the tab
+    Text(String),
+    /// substring (or host / tag, see [TextField]), ignoring case
+    Field(TextField, String),
+    Date(DateField, Comparison, NaiveDate),
+    NeverOpened,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    pub negated: bool,
+    pub condition: Condition,
+}
+
+
+/// A parsed query, see [Query::parse]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+
+
+
+/// Splits `query` at whitespace outside of double quotes, returning each word with its column
+fn tokenize(query: &str) -> Result<Vec<(usize, String)>, Errors> {
+    let mut tokens = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    let mut quote_start = None;
+
+    for (column, c) in query.chars().enumerate() {
+        match c {
+            '"' => quote_start = match quote_start {
+                Some(_) => None,
+                None => {
+                    current.get_or_insert_with(|| (column, String::new()));
+                    Some(column)
+                },
+            },
+            c if c.is_whitespace() && quote_start.is_none() => tokens.extend(current.take()),
+            c => current.get_or_insert_with(|| (column, String::new())).1.push(c),
+        }
+    }
+
+    if let Some(column) = quote_start {
+        return Err(syntax_error(query, column, "this quote is never closed"));
+    }
+    tokens.extend(current);
+
+    Ok(tokens)
+}
+
+fn syntax_error(query: &str, column: usize, message: &str) -> Errors {
+    Errors::QuerySyntaxError { query: query.to_string(), column: column + 1, message: message.to_string() }
+}
+
+
+fn parse_date(query: &str, column: usize, value: &str) -> Result<(Comparison, NaiveDate), Errors> {
+    let (comparison, date) = [
+        (">=", Comparison::OnOrAfter),
+        ("<=", Comparison::BeforeOrOn),
+        (">", Comparison::After),
+        ("<", Comparison::Before),
+        ("=", Comparison::On),
+    ]
+        .into_iter()
+        .find_map(|(op, comparison)| Some((comparison, value.strip_prefix(op)?)))
+        .unwrap_or((Comparison::On, value));
+
+    let date = NaiveDate::parse_from_str(date, QUERY_DATE_FORMAT)
+        .map_err(|_| syntax_error(query, column, &format!("\"{}\" is not a date like 2024-01-31", date)))?;
+
+    Ok((comparison, date))
+}
+
+
+impl Query {
+    ///
+    ///Parses whitespace separated terms:
+    ///`text`, `"more text"`, `field:value`, `field:"value with spaces"`, `added:>2024-01-01`,
+    ///each optionally negated with a leading `-`.
+    ///
+    pub fn parse(query: &str) -> Result<Self, Errors> {
+        let mut terms = Vec::new();
+
+        for (column, token) in tokenize(query)? {
+            let (negated, token) = match token.strip_prefix('-') {
+                Some(rest) if !rest.is_empty() => (true, rest),
+                _ => (false, token.as_str()),
+            };
+            let term_column = column + usize::from(negated);
+
+            let condition = match token.split_once(':') {
+                // `https://...`, `localhost:8080` or `10:30` are text, only known fields are fields
+                Some((field, _)) if !FIELD_NAMES.contains(&field.to_lowercase().as_str()) => Condition::Text(token.to_string()),
+                Some((_, value)) if value.starts_with("//") => Condition::Text(token.to_string()),
+
+                Some((field, value)) => {
+                    let value_column = term_column + field.chars().count() + 1;
+                    if value.is_empty() {
+                        return Err(syntax_error(query, value_column, &format!("\"{}:\" needs a value", field)));
+                    }
+
+                    let text = |field| Condition::Field(field, value.to_lowercase());
+                    match field.to_lowercase().as_str() {
+                        "host" => text(TextField::Host),
+                        "url" => text(TextField::URL),
+                        "title" => text(TextField::Title),
+                        "tag" => text(TextField::Tag),
+                        "note" => text(TextField::Note),
+                        "profile" => text(TextField::Profile),
+                        "opened" if value.eq_ignore_ascii_case("never") => Condition::NeverOpened,
+                        "added" => {
+                            let (comparison, date) = parse_date(query, value_column, value)?;
+                            Condition::Date(DateField::Added, comparison, date)
+                        },
+                        _ => {
+                            let (comparison, date) = parse_date(query, value_column, value)?;
+                            Condition::Date(DateField::Opened, comparison, date)
+                        },
+                    }
+                },
+
+                None => Condition::Text(token.to_string()),
+            };
+
+            terms.push(Term { negated, condition });
+        }
+
+        Ok(Self { terms })
+    }
+
+    /// The tabs tagged `tag`, like `tag:<tag>`
+    pub fn tag(tag: &str) -> Self {
+        Self { terms: vec![Term { negated: false, condition: Condition::Field(TextField::Tag, tag.to_lowercase()) }] }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn get_terms(&self) -> &[Term] {
+        &self.terms
+    }
+
+    /// The terms without a field that are not negated, joined: what to match fuzzily and mark
+    pub fn free_text(&self) -> String {
+        self.terms
+            .iter()
+            .filter_map(|term| match (&term.condition, term.negated) {
+                (Condition::Text(text), false) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+
+    fn condition_holds(condition: &Condition, prfl: &Profile, pair: &URLTitlePair) -> bool {
+        match condition {
+            Condition::Text(text) => score_pair(pair, text).is_some() || pair.note_contains(text),
+
+            Condition::Field(TextField::Host, value) => host_of(&pair.url)
+                .map(|host| host.to_lowercase())
+                .is_some_and(|host| {
+                    let value = value.strip_prefix("www.").unwrap_or(value);
+                    host == value || host.ends_with(&format!(".{}", value))
+                }),
+            Condition::Field(TextField::URL, value) => pair.url.to_lowercase().contains(value),
+            Condition::Field(TextField::Title, value) => pair.title.to_lowercase().contains(value),
+            Condition::Field(TextField::Tag, value) => prfl.has_tag(value) || pair.has_tag(value),
+            Condition::Field(TextField::Note, value) => prfl.note_contains(value) || pair.note_contains(value),
+            Condition::Field(TextField::Profile, value) => prfl.get_name().to_lowercase().contains(value),
+
+            Condition::Date(field, comparison, date) => {
+                let day = match field {
+                    DateField::Added => Some(pair.get_time_created().date()),
+                    DateField::Opened => pair.get_times_opened().last().map(|t| t.date()),
+                };
+
+                day.is_some_and(|day| match comparison {
+                    Comparison::Before => day < *date,
+                    Comparison::BeforeOrOn => day <= *date,
+                    Comparison::On => day == *date,
+                    Comparison::OnOrAfter => day >= *date,
+                    Comparison::After => day > *date,
+                })
+            },
+            Condition::NeverOpened => pair.get_times_opened().is_empty(),
+        }
+    }
+
+    /// Whether every term holds for `pair` (a tab of `prfl`)
+    pub fn matches(&self, prfl: &Profile, pair: &URLTitlePair) -> bool {
+        self.terms
+            .iter()
+            .all(|term| Self::condition_holds(&term.condition, prfl, pair) != term.negated)
+    }
+
+    ///
+    ///How well `pair` (a tab of `prfl`) matches, if it does at all:
+    ///the fuzzy score of [Query::free_text], with the positions to mark.
+    ///
+    ///A tab found only through its note scores 0, with nothing to mark.
+    ///
+    pub fn score(&self, prfl: &Profile, pair: &URLTitlePair) -> Option<PairMatch> {
+        if !self.matches(prfl, pair) {
+            return None;
+        }
+
+        score_pair(pair, &self.free_text()).or_else(|| Some(PairMatch::default()))
+    }
+
+    ///
+    ///How well `prfl` matches: the best of its tabs,
+    ///or, for a query of plain text, its name (or its note, scoring 0).
+    ///
+    pub fn score_profile(&self, prfl: &Profile) -> Option<i64> {
+        let by_tab = prfl
+            .get_pairs()
+            .iter()
+            .filter_map(|pair| self.score(prfl, pair))
+            .map(|found| found.score)
+            .max();
+
+        let plain_text = self.terms
+            .iter()
+            .all(|term| !term.negated && matches!(term.condition, Condition::Text(_)));
+        let by_name = match plain_text {
+            true => fuzzy_match(prfl.get_name(), &self.free_text())
+                .map(|found| found.score)
+                .or_else(|| prfl.note_contains(&self.free_text()).then_some(0)),
+            false => None,
+        };
+
+        by_tab.max(by_name)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conditions(query: &str) -> Vec<Condition> {
+        Query::parse(query)
+            .unwrap()
+            .get_terms()
+            .iter()
+            .map(|term| term.condition.clone())
+            .collect()
+    }
+
+    fn date(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, QUERY_DATE_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn parses_fields_dates_and_negation() {
+        let query = Query::parse("host:YouTube.com -tag:music added:>=2024-01-31 opened:never rust").unwrap();
+
+        assert_eq!(query.get_terms(), &[
+            Term { negated: false, condition: Condition::Field(TextField::Host, String::from("youtube.com")) },
+            Term { negated: true, condition: Condition::Field(TextField::Tag, String::from("music")) },
+            Term { negated: false, condition: Condition::Date(DateField::Added, Comparison::OnOrAfter, date("2024-01-31")) },
+            Term { negated: false, condition: Condition::NeverOpened },
+            Term { negated: false, condition: Condition::Text(String::from("rust")) },
+        ]);
+        assert_eq!(query.free_text(), "rust");
+    }
+
+    #[test]
+    fn quotes_keep_spaces() {
+        assert_eq!(conditions("title:\"live stream\" \"two words\""), vec![
+            Condition::Field(TextField::Title, String::from("live stream")),
+            Condition::Text(String::from("two words")),
+        ]);
+    }
+
+    #[test]
+    fn unknown_prefixes_are_text() {
+        for token in ["localhost:8080", "10:30", "mailto:x", "https://a.com", "unknown:"] {
+            assert_eq!(conditions(token), vec![Condition::Text(String::from(token))]);
+        }
+    }
+
+    #[test]
+    fn reports_the_column_of_errors() {
+        let column = |query| match Query::parse(query) {
+            Err(Errors::QuerySyntaxError { column, .. }) => column,
+            other => panic!("{:?} should be a syntax error, not {:?}", query, other),
+        };
+
+        assert_eq!(column("a title:"), 9);
+        assert_eq!(column("-added:2024-13-01"), 8);
+        assert_eq!(column("note:\"open"), 6);
+    }
+
+    #[test]
+    fn matches_tabs() {
+        let pair = URLTitlePair::new("https://music.youtube.com/watch?v=1", "Live at the Garden")
+            .with_tags(vec![String::from("Music")]);
+        let prfl = Profile::builder()
+            .add_name("Videos")
+            .add_many_title_url_pairs(vec![pair.clone()])
+            .build();
+
+        let matches = |query| Query::parse(query).unwrap().matches(&prfl, &pair);
+        assert!(matches("host:youtube.com tag:music"));
+        assert!(matches("profile:vid -title:studio"));
+        assert!(matches("garden"));
+        assert!(!matches("host:tube.com"));
+        assert!(!matches("-tag:MUSIC"));
+        assert!(!matches("opened:<2020-01-01"));
+    }
+}
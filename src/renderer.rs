@@ -1,6 +1,7 @@
-use std::io::Stdout;
-use crossterm::{cursor::MoveTo, style::{Print, Color, SetBackgroundColor, SetForegroundColor, ResetColor}, terminal::{Clear, ClearType}};
-use crate::{Profile, Errors, write_stdout, debug_println};
+use std::ops::Range;
+use crossterm::{cursor::MoveTo, style::{Attribute, Print, Color, SetAttribute, SetBackgroundColor, SetForegroundColor, ResetColor}, terminal::{Clear, ClearType}};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use crate::{diff::{DiffKind, DiffRow}, input::TextArea, search::fuzzy_positions, urls::url_parts, group_is_in, profile_order, PairRow, Profile, TreeRow, ProfileSortMode, URLTitlePair, Errors, Backend, write_stdout, debug_println};
 
 
 
@@ -30,98 +31,1219 @@ macro_rules! format_pair {
                 // .unwrap_or(&mut String::from("### FETCH FAILED"))
                 .clone();
 
-            String::from_iter([">> Title: ", &title, " | URL: ", &url, "\n"])
+            String::from_iter([">> Title: ", &title, " | URL: ", &url])
         }
     };
 }
-macro_rules! format_profile {
-    ($prfl: ident) => {
-        {
-            let name = &$prfl.get_name();
-            let len = $prfl.get_pairs().len();
-            let t_last = $prfl.get_time_last_visited();
-            format!(">> {} | {} | {}\n", name, len, t_last)
+
+
+
+
+
+
+
+const SEP: &str = "======================================";
+
+const STATIC_INFO_MAINMENU: [&str; 8] = [
+    "=== Firefox Tab Resumer ===",
+    "Author: Lucius Y. Men, Written in Rust",
+    "Latest Version: v0.1, Updated: 2 Jan 2024",
+    SEP,
+    ">> Below is the list of all existing profiles.",
+    ">> UP / DOWN to navigate, ENTER to select, I to INITIALIZE, N for a NEW profile",
+    ">> Press ? to see every key",
+    SEP,
+];
+/// the line of [STATIC_INFO_MAINMENU] that tells how the profiles are sorted
+const MAINMENU_SORT_LINE: usize = 4;
+pub const STATIC_INFO_MAINMENU_LEN: u16 = STATIC_INFO_MAINMENU.len() as u16;
+
+/// every key of the main menu, shown by ?
+pub const HELP_MAINMENU: [&str; 14] = [
+    ">> Use UP / DOWN ARROWS (or PAGE UP / PAGE DOWN / HOME / END) to navigate, ENTER to select a profile",
+    ">> Press I to INITIALIZE (start) the highlighted profile, or every profile of the highlighted group",
+    ">> Press E to EDIT the profile",
+    ">> Press D to DELETE the profile",
+    ">> If you want a new profile, press N to enter its name",
+    ">> Press / to filter the profiles, F to search the tabs of all profiles, S to see the STALE tabs of all profiles",
+    ">> Press r to sort the profiles differently, R to choose how",
+    ">> Press T to TAG the profile, W to WRITE a note on it, # to see the tabs of a tag across all profiles",
+    ">> Press G to put the profile in a group (or rename the group), ENTER / LEFT / RIGHT to open / close a group",
+    ">> Press M to MERGE the profile into another one",
+    ">> Press A to mark the profile as a template (⧉) or not, P to create a new PROFILE from a template, Y to copy the profile",
+    ">> Press c to COMPARE the profile with another one, C with a file (e.g. a backup or an export)",
+    ">> Press u to UNDO the last change to the profiles (on any screen), CTRL+R to redo it",
+    ">> Press ESC to quit",
+];
+
+const STATIC_INFO_PROFILE: [&str; 4] = [
+    SEP,
+    ">> UP / DOWN to navigate, ENTER to see a tab, I / O to open it, ESC to go back",
+    ">> Press ? to see every key",
+    SEP,
+];
+/// the profile name comes first
+pub const STATIC_INFO_PROFILE_LEN: u16 = STATIC_INFO_PROFILE.len() as u16 + 1;
+
+/// every key of a profile, shown by ?
+pub const HELP_PROFILE: [&str; 14] = [
+    ">> Use UP / DOWN ARROWS to navigate, PAGE UP / PAGE DOWN / HOME / END to scroll",
+    ">> Press P to PASTE URLs from the clipboard into this profile",
+    ">> Press SPACE to select a tab, S to select the tabs matching a query; I / O open, D deletes them",
+    ">> Press T to TAG the highlighted tab (or add tags to the selection), W to WRITE a note on it",
+    ">> Press G to put the tab (or the selection) in a section, or to rename the highlighted section",
+    ">> Press C to COPY or M to MOVE the tab (or the selection) to another profile, X to split the profile",
+    ">> Press u to UNDO the last change, CTRL+R to redo it, U to cut long URLs in the middle instead of at the end",
+    ">> Press ENTER to see everything about the highlighted tab, or to open / close the highlighted section",
+    ">> Press / to search, then n / N for the next / previous hit",
+    ">> Press r to sort the tabs differently, R to choose how",
+    ">> Press L to set after how many days unopened tabs go stale (to be ARCHIVED), B to bring archived tabs back",
+    ">> Press Q to make the tab a URL template (⌕) whose {placeholders} are asked for whenever it is opened",
+    ">> Press SHIFT + UP / DOWN / HOME / END (or < / >) to move the tab, the selection or the section",
+    ">> Press ESC to go back to the list of profiles",
+];
+
+const STATIC_INFO_HELP: [&str; 3] = [
+    SEP,
+    ">> Use UP / DOWN ARROWS to scroll, ESC to go back",
+    SEP,
+];
+/// what the keys are for comes first
+pub const STATIC_INFO_HELP_LEN: u16 = STATIC_INFO_HELP.len() as u16 + 1;
+
+const STATIC_INFO_TAB: [&str; 6] = [
+    SEP,
+    ">> Press I to open this tab in a private window, O in a normal one",
+    ">> Press E to EDIT title and URL, T to TAG it, W to WRITE a note, D to DELETE the tab",
+    ">> Press C to COPY or M to MOVE the tab to another profile",
+    ">> Press ESC to go back to the profile",
+    SEP,
+];
+/// the tab title comes first
+pub const STATIC_INFO_TAB_LEN: u16 = STATIC_INFO_TAB.len() as u16 + 1;
+
+const STATIC_INFO_PICK: [&str; 3] = [
+    SEP,
+    ">> Use UP / DOWN ARROWS to choose a profile, ENTER to pick it, ESC to cancel",
+    SEP,
+];
+/// the question comes first
+pub const STATIC_INFO_PICK_LEN: u16 = STATIC_INFO_PICK.len() as u16 + 1;
+
+const STATIC_INFO_SORT: [&str; 3] = [
+    SEP,
+    ">> Use UP / DOWN ARROWS to choose an order, ENTER to sort by it, ESC to cancel",
+    SEP,
+];
+/// the question comes first
+pub const STATIC_INFO_SORT_LEN: u16 = STATIC_INFO_SORT.len() as u16 + 1;
+
+const STATIC_INFO_SEARCH: [&str; 4] = [
+    SEP,
+    ">> Type to search the tabs of all profiles, e.g. rust host:youtube.com -title:live added:>2024-01-01",
+    ">> Use UP / DOWN ARROWS to choose a tab, ENTER to go to it, ESC to go back",
+    SEP,
+];
+/// the number of hits comes first
+pub const STATIC_INFO_SEARCH_LEN: u16 = STATIC_INFO_SEARCH.len() as u16 + 1;
+
+const STATIC_INFO_TAGS: [&str; 3] = [
+    SEP,
+    ">> Use UP / DOWN ARROWS to choose a tag, ENTER to see its tabs, ESC to go back",
+    SEP,
+];
+/// the number of tags comes first
+pub const STATIC_INFO_TAGS_LEN: u16 = STATIC_INFO_TAGS.len() as u16 + 1;
+
+const STATIC_INFO_TAG_VIEW: [&str; 4] = [
+    SEP,
+    ">> Press I to open all these tabs in a private window, O in a normal one",
+    ">> Use UP / DOWN ARROWS to choose a tab, ENTER to go to it, ESC to go back",
+    SEP,
+];
+/// the tag comes first
+pub const STATIC_INFO_TAG_VIEW_LEN: u16 = STATIC_INFO_TAG_VIEW.len() as u16 + 1;
+
+const STATIC_INFO_NOTE: [&str; 4] = [
+    SEP,
+    ">> Type the note, ENTER starts a new line",
+    ">> Press ESC to save it and go back, CTRL+Q to go back without saving",
+    SEP,
+];
+/// what the note is on comes first
+pub const STATIC_INFO_NOTE_LEN: u16 = STATIC_INFO_NOTE.len() as u16 + 1;
+
+const STATIC_INFO_STALE: [&str; 5] = [
+    SEP,
+    ">> The tabs not opened for longer than their profile allows (L in a profile sets how long)",
+    ">> Press A to move all of them to the ARCHIVE section of their profile, where they are kept but not opened",
+    ">> Use UP / DOWN ARROWS to choose a tab, ENTER to go to it, ESC to go back",
+    SEP,
+];
+/// how many there are comes first
+pub const STATIC_INFO_STALE_LEN: u16 = STATIC_INFO_STALE.len() as u16 + 1;
+
+const STATIC_INFO_DIFF: [&str; 5] = [
+    SEP,
+    ">> + is only on the right, - only on the left, ~ has another title on the right",
+    ">> Press ENTER to TAKE the highlighted change into the profile on the left, A to take ALL of them",
+    ">> Press C to show only the CHANGES (or every tab again), ESC to go back",
+    SEP,
+];
+/// the two profiles come first
+pub const STATIC_INFO_DIFF_LEN: u16 = STATIC_INFO_DIFF.len() as u16 + 1;
+
+/// how many of the latest launches of a tab are listed
+const OPENED_SHOWN: usize = 5;
+
+/// below every list: the scroll indicator, then the status line
+const LIST_FOOTER_LEN: u16 = 2;
+
+
+const ROW_PREFIX: &str = ">> ";
+const ROW_PREFIX_SELECTED: &str = "*> ";
+const ROW_PREFIX_HEADER: &str = "   ";
+const COLUMN_SEP: &str = " | ";
+const ELLIPSIS: char = '…';
+
+const ROW_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+const PROFILE_COLUMNS: [Column; 3] = [
+    Column { width: ColumnWidth::Flex(1), align: Align::Left, elide: Elide::End },
+    Column { width: ColumnWidth::Fixed(5), align: Align::Right, elide: Elide::End },
+    Column { width: ColumnWidth::Fixed(16), align: Align::Left, elide: Elide::End },
+];
+const PROFILE_COLUMN_TITLES: [&str; 3] = ["Name", "Tabs", "Last modified"];
+
+const PAIR_COLUMN_TITLES: [&str; 2] = ["Title", "URL"];
+
+/// added behind the others if anything in the list is tagged
+const TAGS_COLUMN: Column = Column { width: ColumnWidth::Flex(1), align: Align::Left, elide: Elide::End };
+const TAGS_COLUMN_TITLE: &str = "Tags";
+const TAG_CHIP_PREFIX: char = '#';
+
+/// in front of the title of a tab (or the name of a profile) with a note
+const NOTE_MARK: &str = "✎ ";
+/// in front of the name of a template, see [Profile::from_template]
+const TEMPLATE_MARK: &str = "⧉ ";
+/// in front of the title of a [crate::TabKind::Template] tab
+const TAB_TEMPLATE_MARK: &str = "⌕ ";
+
+/// per group a row is in, see [TreeRow]
+const TREE_INDENT: &str = "  ";
+/// in front of a group of profiles or a section of tabs, telling whether what is in it is shown
+const GROUP_MARK_EXPANDED: &str = "▾ ";
+const GROUP_MARK_COLLAPSED: &str = "▸ ";
+
+const SEARCH_COLUMNS: [Column; 3] = [
+    Column { width: ColumnWidth::Flex(1), align: Align::Left, elide: Elide::End },
+    Column { width: ColumnWidth::Flex(2), align: Align::Left, elide: Elide::End },
+    Column { width: ColumnWidth::Flex(2), align: Align::Left, elide: Elide::End },
+];
+const SEARCH_COLUMN_TITLES: [&str; 3] = ["Profile", "Title", "URL"];
+
+const TAG_COLUMNS: [Column; 2] = [
+    Column { width: ColumnWidth::Flex(1), align: Align::Left, elide: Elide::End },
+    Column { width: ColumnWidth::Fixed(5), align: Align::Right, elide: Elide::End },
+];
+const TAG_COLUMN_TITLES: [&str; 2] = ["Tag", "Tabs"];
+
+const STALE_COLUMNS: [Column; 3] = [
+    Column { width: ColumnWidth::Flex(1), align: Align::Left, elide: Elide::End },
+    Column { width: ColumnWidth::Flex(2), align: Align::Left, elide: Elide::End },
+    Column { width: ColumnWidth::Fixed(16), align: Align::Left, elide: Elide::End },
+];
+const STALE_COLUMN_TITLES: [&str; 3] = ["Profile", "Title", "Last opened"];
+
+/// the title on the left, the title on the right, the URL
+const DIFF_COLUMNS: [Column; 3] = [
+    Column { width: ColumnWidth::Flex(2), align: Align::Left, elide: Elide::End },
+    Column { width: ColumnWidth::Flex(2), align: Align::Left, elide: Elide::End },
+    Column { width: ColumnWidth::Flex(3), align: Align::Left, elide: Elide::Middle },
+];
+
+
+
+
+
+pub const COLOR_FG_DECLARE: Color = Color::Green;
+pub const COLOR_FG_DEFAULT: Color = Color::White;
+pub const COLOR_FG_HILIT: Color = Color::Cyan;
+
+pub const COLOR_BG_HILIT: Color = Color::White;
+
+pub const COLOR_FG_SCROLL: Color = Color::DarkGrey;
+pub const COLOR_FG_COLUMNS: Color = Color::DarkGrey;
+pub const COLOR_FG_MATCH: Color = Color::Yellow;
+pub const COLOR_FG_ERROR: Color = Color::Red;
+
+/// the rows of a diff by [DiffKind], see [render_diff]
+pub const COLOR_FG_ADDED: Color = Color::Green;
+pub const COLOR_FG_REMOVED: Color = Color::Red;
+pub const COLOR_FG_RETITLED: Color = Color::Yellow;
+
+/// a tag always gets the same one, see [tag_color]
+const COLOR_FG_TAGS: [Color; 6] = [
+    Color::Magenta,
+    Color::Blue,
+    Color::DarkYellow,
+    Color::DarkCyan,
+    Color::DarkMagenta,
+    Color::DarkGreen,
+];
+
+
+
+
+// ============== ROW LAYOUT
+
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// Where to cut text that is too wide for its column
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Elide {
+    /// `Some very long ti…`
+    #[default] End,
+    /// `https://example.com/…/page.html`
+    Middle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnWidth {
+    /// this many terminal cells
+    Fixed(usize),
+    /// a share (by weight) of what the fixed columns leave over
+    Flex(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    pub width: ColumnWidth,
+    pub align: Align,
+    pub elide: Elide,
+}
+
+
+/// How many terminal cells `text` takes up (2 for most CJK characters and emoji)
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+#[inline(always)]
+fn char_width(c: char) -> usize {
+    UnicodeWidthChar::width(c).unwrap_or(0)
+}
+
+/// Tabs and line breaks would move the cursor, so they become spaces
+fn sanitize(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+/// Cuts `text` to at most `width` cells, ending with `…` if anything was cut
+pub fn truncate_end(text: &str, width: usize) -> String {
+    if display_width(text) <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        if used + char_width(c) > width - 1 {
+            break;
         }
+        used += char_width(c);
+        out.push(c);
+    }
+    out.push(ELLIPSIS);
+
+    out
+}
+
+/// Cuts `text` to at most `width` cells by replacing its middle with `…`
+pub fn elide_middle(text: &str, width: usize) -> String {
+    if display_width(text) <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    let tail_width = (width - 1) / 2;
+    let head_width = width - 1 - tail_width;
+
+    let mut head = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        if used + char_width(c) > head_width {
+            break;
+        }
+        used += char_width(c);
+        head.push(c);
+    }
+
+    let mut tail = Vec::new();
+    let mut used = 0;
+    for c in text.chars().rev() {
+        if used + char_width(c) > tail_width {
+            break;
+        }
+        used += char_width(c);
+        tail.push(c);
+    }
+
+    head.push(ELLIPSIS);
+    head.extend(tail.into_iter().rev());
+
+    head
+}
+
+/// `text` cut (see [Elide]) or padded with spaces to exactly `width` cells
+fn fit(text: &str, width: usize, column: &Column) -> String {
+    let text = sanitize(text);
+    let text = match column.elide {
+        Elide::End => truncate_end(&text, width),
+        Elide::Middle => elide_middle(&text, width),
     };
+
+    // a wide character may not fit in the last cell, hence the padding is measured, not assumed
+    let pad = " ".repeat(width.saturating_sub(display_width(&text)));
+    match column.align {
+        Align::Left => text + &pad,
+        Align::Right => pad + &text,
+    }
+}
+
+/// Splits `total_width` cells among `columns` (the separators between them not included)
+fn column_widths(columns: &[Column], total_width: usize) -> Vec<usize> {
+    let fixed: usize = columns
+        .iter()
+        .map(|col| match col.width {
+            ColumnWidth::Fixed(width) => width,
+            ColumnWidth::Flex(_) => 0,
+        })
+        .sum();
+    let weights: usize = columns
+        .iter()
+        .map(|col| match col.width {
+            ColumnWidth::Fixed(_) => 0,
+            ColumnWidth::Flex(weight) => weight,
+        })
+        .sum();
+
+    let mut flex_left = total_width.saturating_sub(fixed);
+    let mut weights_left = weights;
+
+    columns
+        .iter()
+        .map(|col| match col.width {
+            ColumnWidth::Fixed(width) => width,
+            ColumnWidth::Flex(weight) => {
+                // the last flexible column gets whatever rounding left over
+                let width = match weights_left {
+                    0 => 0,
+                    _ => flex_left * weight / weights_left,
+                };
+                flex_left -= width;
+                weights_left -= weight;
+                width
+            },
+        })
+        .collect()
+}
+
+///
+///Lays out `cells` in `columns`, behind `prefix`, so that the row is exactly `total_width` cells wide
+///(unless even the fixed columns do not fit).
+///
+pub fn layout_row(prefix: &str, cells: &[&str], columns: &[Column], total_width: usize) -> String {
+    let seps = display_width(COLUMN_SEP) * columns.len().saturating_sub(1);
+    let available = total_width.saturating_sub(display_width(prefix) + seps);
+
+    let row = cells
+        .iter()
+        .zip(columns)
+        .zip(column_widths(columns, available))
+        .map(|((cell, column), width)| fit(cell, width, column))
+        .collect::<Vec<_>>()
+        .join(COLUMN_SEP);
+
+    truncate_end(&format!("{}{}", prefix, row), total_width)
+}
+
+
+///
+///Which rows of a list are shown, and what is marked in them.
+///
+///The default shows every row and marks nothing.
+///
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RowFilter<'a> {
+    /// indices of the rows shown, in this order; all rows if [None]
+    pub shown: Option<&'a [usize]>,
+    /// marked wherever it occurs (ignoring case)
+    pub needle: Option<&'a str>,
+    /// indices of the rows picked for what is done to several at once, ascending
+    pub selected: &'a [usize],
+}
+
+impl<'a> RowFilter<'a> {
+    /// The items of `all` that are shown
+    pub fn apply<'b, T>(&self, all: &'b [T]) -> Vec<&'b T> {
+        match self.shown {
+            Some(shown) => shown.iter().filter_map(|&idx| all.get(idx)).collect(),
+            None => all.iter().collect(),
+        }
+    }
+
+    pub fn is_selected(&self, idx: usize) -> bool {
+        self.selected.binary_search(&idx).is_ok()
+    }
+}
+
+
+/// `columns` and their `titles`, plus the tags column if `with_tags`
+fn columns_with_tags<'a>(columns: &[Column], titles: &[&'a str], with_tags: bool) -> (Vec<Column>, Vec<&'a str>) {
+    let mut columns = columns.to_vec();
+    let mut titles = titles.to_vec();
+    if with_tags {
+        columns.push(TAGS_COLUMN);
+        titles.push(TAGS_COLUMN_TITLE);
+    }
+
+    (columns, titles)
+}
+
+fn pair_columns(url_elide: Elide) -> [Column; 2] {
+    [
+        Column { width: ColumnWidth::Flex(2), align: Align::Left, elide: Elide::End },
+        Column { width: ColumnWidth::Flex(3), align: Align::Left, elide: url_elide },
+    ]
+}
+
+/// `#one #two`, how tags are shown in rows
+pub fn format_tags(tags: &[String]) -> String {
+    tags.iter()
+        .map(|tag| format!("{}{}", TAG_CHIP_PREFIX, tag))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The colour of the chip of `tag`, the same every time (ignoring case)
+pub fn tag_color(tag: &str) -> Color {
+    let hash = tag
+        .to_lowercase()
+        .bytes()
+        .fold(0usize, |hash, byte| hash.wrapping_mul(31).wrapping_add(byte as usize));
+
+    COLOR_FG_TAGS[hash % COLOR_FG_TAGS.len()]
+}
+
+/// `text` behind the [NOTE_MARK] if there is a `note`
+fn with_note_mark(text: &str, note: &str) -> String {
+    match note.is_empty() {
+        true => text.to_string(),
+        false => format!("{}{}", NOTE_MARK, text),
+    }
+}
+
+/// `depth` is how many groups the profile is shown in, see [TreeRow]
+pub fn format_profile_row(prfl: &Profile, width: usize, with_tags: bool, depth: usize) -> String {
+    let template = match prfl.is_template() {
+        true => TEMPLATE_MARK,
+        false => "",
+    };
+    let name = format!("{}{}{}", TREE_INDENT.repeat(depth), template, with_note_mark(prfl.get_name(), prfl.get_note()));
+    let tabs = prfl.get_pairs().len().to_string();
+    let t_last = prfl.get_time_last_visited().format(ROW_TIME_FORMAT).to_string();
+    let tags = format_tags(prfl.get_tags());
+    let (columns, _) = columns_with_tags(&PROFILE_COLUMNS, &PROFILE_COLUMN_TITLES, with_tags);
+
+    layout_row(ROW_PREFIX, &[&name, &tabs, &t_last, &tags], &columns, width)
+}
+
+/// A group of [TreeRow::Group], with the tabs of all profiles in it and the latest change to them
+pub fn format_group_row(prfls: &[Profile], path: &str, depth: usize, collapsed: bool, width: usize, with_tags: bool) -> String {
+    let in_group: Vec<&Profile> = prfls
+        .iter()
+        .filter(|prfl| group_is_in(prfl.get_group(), path))
+        .collect();
+
+    let mark = match collapsed {
+        true => GROUP_MARK_COLLAPSED,
+        false => GROUP_MARK_EXPANDED,
+    };
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let label = format!("{}{}{}/ ({} profile(s))", TREE_INDENT.repeat(depth), mark, name, in_group.len());
+    let tabs = in_group.iter().map(|prfl| prfl.get_pairs().len()).sum::<usize>().to_string();
+    let t_last = in_group
+        .iter()
+        .map(|prfl| prfl.get_time_last_visited())
+        .max()
+        .map(|t| t.format(ROW_TIME_FORMAT).to_string())
+        .unwrap_or_default();
+    let (columns, _) = columns_with_tags(&PROFILE_COLUMNS, &PROFILE_COLUMN_TITLES, with_tags);
+
+    layout_row(ROW_PREFIX, &[&label, &tabs, &t_last, ""], &columns, width)
+}
+
+/// A section of [PairRow::Section], laid out in the columns of the tabs
+pub fn format_section_row(name: &str, count: usize, collapsed: bool, width: usize, url_elide: Elide, with_tags: bool) -> String {
+    let mark = match collapsed {
+        true => GROUP_MARK_COLLAPSED,
+        false => GROUP_MARK_EXPANDED,
+    };
+    let label = format!("{}{} ({} tab(s))", mark, name, count);
+    let (columns, _) = columns_with_tags(&pair_columns(url_elide), &PAIR_COLUMN_TITLES, with_tags);
+
+    layout_row(ROW_PREFIX, &[&label, "", ""], &columns, width)
+}
+
+pub fn format_pair_row(pair: &URLTitlePair, width: usize, url_elide: Elide, is_selected: bool, with_tags: bool) -> String {
+    let prefix = match is_selected {
+        true => ROW_PREFIX_SELECTED,
+        false => ROW_PREFIX,
+    };
+    let template = match pair.is_template() {
+        true => TAB_TEMPLATE_MARK,
+        false => "",
+    };
+    let title = format!("{}{}", template, with_note_mark(&pair.title, pair.get_note()));
+    let tags = format_tags(pair.get_tags());
+    let (columns, _) = columns_with_tags(&pair_columns(url_elide), &PAIR_COLUMN_TITLES, with_tags);
+
+    layout_row(prefix, &[&title, &pair.url, &tags], &columns, width)
+}
+
+
+
+
+///
+///The rows of a list that fit on the screen.
+///
+///`offset` is the index of the first visible row, `height` the number of visible rows,
+///`width` the number of cells every row is laid out in.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    offset: usize,
+    height: usize,
+    width: usize,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self { offset: 0, height: 1, width: 80 }
+    }
+}
+
+impl Viewport {
+    ///
+    ///Fits the viewport to a terminal of `term_cols` x `term_rows`,
+    ///with `header_rows` rows above the list (plus the column titles above and the footer below it).
+    ///
+    pub fn resize(&mut self, term_cols: u16, term_rows: u16, header_rows: u16) {
+        let height = term_rows.saturating_sub(header_rows + 1 + LIST_FOOTER_LEN);
+
+        self.height = (height as usize).max(1);
+        self.width = (term_cols as usize).max(1);
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+    pub fn get_offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Scrolls as little as possible to make row `idx` of a `len` rows long list visible
+    pub fn scroll_to(&mut self, idx: usize, len: usize) {
+        if idx < self.offset {
+            self.offset = idx;
+        } else if idx >= self.offset + self.height {
+            self.offset = idx + 1 - self.height;
+        }
+
+        // never leave empty rows at the bottom if there is more above
+        self.offset = self.offset.min(len.saturating_sub(self.height));
+    }
+
+    /// The indices of the rows currently on screen
+    pub fn range(&self, len: usize) -> Range<usize> {
+        self.offset.min(len)..(self.offset + self.height).min(len)
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorMove {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+}
+
+///
+///Where the highlight goes from `idx` in a `len` rows long list.
+///UP / DOWN wrap around, the others stop at the first / last row.
+///
+pub fn move_cursor(idx: usize, len: usize, mv: CursorMove, viewport: &Viewport) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    let last = len - 1;
+
+    match mv {
+        CursorMove::Up => idx.checked_sub(1).unwrap_or(last),
+        CursorMove::Down => if idx < last { idx + 1 } else { 0 },
+        CursorMove::PageUp => idx.saturating_sub(viewport.get_height()),
+        CursorMove::PageDown => (idx + viewport.get_height()).min(last),
+        CursorMove::Home => 0,
+        CursorMove::End => last,
+    }
+}
+
+
+
+
+pub fn render_line(stdout: &mut impl Backend, line: &str, opt_fg_color: Option<Color>) -> Result<(), Errors> {
+    
+    if let Some(color) = opt_fg_color {
+        write_stdout!(
+            stdout,
+            SetForegroundColor(color)
+        )?;
+    }
+    write_stdout!(
+        stdout,
+        Print(line)
+    )?;
+    Ok(())
+}
+
+
+
+/// Prints `msg` on the last row of the terminal, replacing whatever was there
+pub fn render_status_line(stdout: &mut impl Backend, msg: &str) -> Result<(), Errors> {
+    let (_, rows) = stdout.size()?;
+
+    write_stdout!(
+        stdout,
+        MoveTo(0, rows.saturating_sub(1)),
+        Clear(ClearType::CurrentLine),
+        SetForegroundColor(COLOR_FG_DECLARE),
+        Print(msg),
+        ResetColor
+    )?;
+
+    Ok(())
+}
+
+
+
+/// Clears the screen and prints the main menu help, with `sort` telling how the profiles are sorted
+pub fn render_beginning(stdout: &mut impl Backend, sort: ProfileSortMode) -> Result<(), Errors> {
+    let sort_line = format!(">> Below is the list of all existing profiles, sorted by {}.", sort.label());
+    let mut info = STATIC_INFO_MAINMENU;
+    info[MAINMENU_SORT_LINE] = &sort_line;
+
+    write_stdout!(
+        stdout,
+
+        Clear(ClearType::All), // this is necessary
+
+        MoveTo(0, 0),
+        SetForegroundColor(COLOR_FG_DECLARE),
+        Print(info.join("\n\r")),
+        MoveTo(0, STATIC_INFO_MAINMENU_LEN + 1),
+        ResetColor
+    )?;
+
+    Ok(())
+}
+
+
+
+///
+///Prints the line below a list:
+///how many rows are hidden above / below, and which row is highlighted.
+///
+fn render_scroll_indicator(
+    stdout: &mut impl Backend,
+    pos_row: u16,
+    pos_col: u16,
+    viewport: &Viewport,
+    len: usize,
+    highlight_idx: usize
+) -> Result<(), Errors> {
+
+    let shown = viewport.range(len);
+
+    let above = match shown.start {
+        0 => String::new(),
+        n => format!("^ {} more above | ", n),
+    };
+    let below = match len - shown.end {
+        0 => String::new(),
+        n => format!(" | v {} more below", n),
+    };
+
+    write_stdout!(
+        stdout,
+        MoveTo(pos_col, pos_row),
+        Clear(ClearType::CurrentLine),
+        SetForegroundColor(COLOR_FG_SCROLL),
+        Print(format!("-- {}{} of {}{} --", above, highlight_idx + 1, len, below)),
+        ResetColor
+    )?;
+
+    Ok(())
+}
+
+
+///
+///The chars of a laid out `row` that match `query`:
+///each column is matched on its own, so that a match never spans two of them.
+///
+fn row_marks(row: &str, query: &str) -> Vec<usize> {
+    let mut marks = Vec::new();
+    let mut offset = 0;
+
+    for cell in row.split(COLUMN_SEP) {
+        marks.extend(fuzzy_positions(cell, query).into_iter().map(|pos| pos + offset));
+        offset += cell.chars().count() + COLUMN_SEP.chars().count();
+    }
+
+    marks
+}
+
+///
+///The colour of every char of a laid out `row` that belongs to a tag chip
+///in the column at `column` (see [format_tags]).
+///
+fn row_chips(row: &str, column: usize) -> Vec<Option<Color>> {
+    let mut colors = vec![None; row.chars().count()];
+
+    let offset: usize = row
+        .split(COLUMN_SEP)
+        .take(column)
+        .map(|cell| cell.chars().count() + COLUMN_SEP.chars().count())
+        .sum();
+    let Some(cell) = row.split(COLUMN_SEP).nth(column) else {
+        return colors;
+    };
+
+    let mut pos = offset;
+    for word in cell.split(' ') {
+        let len = word.chars().count();
+        if let Some(tag) = word.strip_prefix(TAG_CHIP_PREFIX) {
+            let color = tag_color(tag.trim_end_matches(ELLIPSIS));
+            colors.iter_mut().skip(pos).take(len).for_each(|c| *c = Some(color));
+        }
+        pos += len + 1;
+    }
+
+    colors
+}
+
+///
+///Prints `row`, with the chars matching `needle` underlined
+///(and, outside the highlighted row, coloured),
+///and the tag chips in `chip_column` (if any) in their colours.
+///
+fn render_marked(
+    stdout: &mut impl Backend,
+    row: &str,
+    needle: Option<&str>,
+    is_highlighted: bool,
+    chip_column: Option<usize>
+) -> Result<(), Errors> {
+    let marks = needle.map(|needle| row_marks(row, needle)).unwrap_or_default();
+    let chips = chip_column.map(|column| row_chips(row, column)).unwrap_or_default();
+    if marks.is_empty() && chips.iter().all(Option::is_none) {
+        return write_stdout!(stdout, Print(row));
+    }
+
+    let plain = match is_highlighted {
+        true => COLOR_FG_HILIT,
+        false => COLOR_FG_DEFAULT,
+    };
+
+    for (idx, c) in row.chars().enumerate() {
+        let chip = chips.get(idx).copied().flatten();
+        let is_mark = marks.binary_search(&idx).is_ok();
+
+        if !is_mark {
+            match chip {
+                Some(color) => write_stdout!(stdout, SetForegroundColor(color), Print(c), SetForegroundColor(plain))?,
+                None => write_stdout!(stdout, Print(c))?,
+            }
+            continue;
+        }
+
+        write_stdout!(stdout, SetAttribute(Attribute::Underlined))?;
+        if !is_highlighted {
+            write_stdout!(stdout, SetForegroundColor(COLOR_FG_MATCH))?;
+        }
+        write_stdout!(stdout, Print(c), SetAttribute(Attribute::NoUnderline))?;
+        if !is_highlighted {
+            write_stdout!(stdout, SetForegroundColor(COLOR_FG_DEFAULT))?;
+        }
+    }
+
+    Ok(())
+}
+
+
+///
+///Prints the rows of a list that are inside `viewport`, one per terminal row;
+///`chip_column` is the column with tags, if there is one.
+///
+#[allow(clippy::too_many_arguments)]
+fn render_rows(
+    stdout: &mut impl Backend,
+    rows: &[String],
+    pos_row: u16,
+    pos_col: u16,
+    viewport: &Viewport,
+    highlight_idx: usize,
+    needle: Option<&str>,
+    chip_column: Option<usize>
+) -> Result<(), Errors> {
+
+    for (line, idx) in viewport.range(rows.len()).enumerate() {
+        let row = pos_row
+            .checked_add(line as u16)
+            .ok_or(Errors::CursorPosOverflowError)?;
+
+        write_stdout!(
+            stdout,
+            MoveTo(pos_col, row)
+        )?;
+
+        if idx == highlight_idx {
+            write_stdout!(
+                stdout,
+                SetForegroundColor(COLOR_FG_HILIT),
+                SetBackgroundColor(COLOR_BG_HILIT)
+            )?;
+        }
+
+        render_marked(stdout, &rows[idx], needle, idx == highlight_idx, chip_column)?;
+        write_stdout!(
+            stdout,
+            ResetColor
+        )?;
+    }
+
+    let indicator_row = pos_row
+        .checked_add(viewport.get_height() as u16)
+        .ok_or(Errors::CursorPosOverflowError)?;
+
+    render_scroll_indicator(stdout, indicator_row, pos_col, viewport, rows.len(), highlight_idx)
 }
 
 
 
+///
+///Clears the screen and prints the name of `prfl` (and the first line of its note)
+///with the keys available in the profile view.
+///
+pub fn render_profile_beginning(stdout: &mut impl Backend, prfl: &Profile) -> Result<(), Errors> {
+    let note = match prfl.get_note().lines().next() {
+        Some(line) => format!(" {}{}", NOTE_MARK, sanitize(line)),
+        None => String::new(),
+    };
+    let expiry = match prfl.get_expire_after() {
+        Some(days) => format!(", stale after {} days", days),
+        None => String::new(),
+    };
 
+    write_stdout!(
+        stdout,
 
+        Clear(ClearType::All),
 
+        MoveTo(0, 0),
+        SetForegroundColor(COLOR_FG_DECLARE),
+        Print(format!(
+            "=== Profile: {} ({} tabs, {}{}) ==={}\n\r",
+            prfl.get_name(), prfl.get_pairs().len(), prfl.get_sort_mode().label(), expiry, note
+        )),
+        Print(STATIC_INFO_PROFILE.join("\n\r")),
+        ResetColor
+    )?;
 
-const SEP: &'static str = "======================================";
+    Ok(())
+}
 
-const STATIC_INFO_MAINMENU: [&'static str; 11] = [
-    "=== Firefox Tab Resumer ===",
-    "Author: Lucius Y. Men, Written in Rust",
-    "Latest Version: v0.1, Updated: 2 Jan 2024",
-    SEP,
-    ">> Below is the list of all existing profiles.",
-    ">> Use UP / DOWN ARROWS to navigate and select a profile",
-    ">> Press I to INITIALIZE (start) the highlighted profile",
-    ">> Press E to EDIT the profile,",
-    ">> Press D to DELETE the profile",
-    ">> If you want a new profile, press N to enter its name",
-    SEP,
-];
-pub const STATIC_INFO_MAINMENU_LEN: u16 = STATIC_INFO_MAINMENU.len() as u16;
 
+/// Prints the titles of `columns` dimmed at `pos_row`, aligned with the rows below them
+fn render_column_titles(
+    stdout: &mut impl Backend,
+    titles: &[&str],
+    columns: &[Column],
+    pos_row: u16,
+    pos_col: u16,
+    viewport: &Viewport
+) -> Result<(), Errors> {
+    let columns: Vec<Column> = columns
+        .iter()
+        .map(|col| Column { elide: Elide::End, ..*col })
+        .collect();
+
+    write_stdout!(
+        stdout,
+        MoveTo(pos_col, pos_row),
+        SetForegroundColor(COLOR_FG_COLUMNS),
+        Print(layout_row(ROW_PREFIX_HEADER, titles, &columns, viewport.get_width())),
+        ResetColor
+    )
+}
+
+
+/// Appends `msg` to the prompt in the last row, e.g. why what was typed is not accepted
+pub fn render_prompt_error(stdout: &mut impl Backend, msg: &str) -> Result<(), Errors> {
+    write_stdout!(
+        stdout,
+        SetForegroundColor(COLOR_FG_ERROR),
+        Print(format!("  ({})", sanitize(msg))),
+        ResetColor
+    )?;
 
+    Ok(())
+}
 
 
+///
+///Prints everything stored about `pair` (a tab of `prfl`), one field per row;
+///long values wrap instead of being cut.
+///
+///`also_in` are the names of the other profiles with a tab for the same page.
+///
+pub fn render_browser_tab(
+    stdout: &mut impl Backend,
+    prfl: &Profile,
+    pair: &URLTitlePair,
+    also_in: &[&str]
+) -> Result<(), Errors> {
+    write_stdout!(
+        stdout,
 
-pub const COLOR_FG_DECLARE: Color = Color::Green;
-pub const COLOR_FG_DEFAULT: Color = Color::White;
-pub const COLOR_FG_HILIT: Color = Color::Cyan;
+        Clear(ClearType::All),
 
-pub const COLOR_BG_HILIT: Color = Color::White;
+        MoveTo(0, 0),
+        SetForegroundColor(COLOR_FG_DECLARE),
+        Print(format!("=== Tab: {} ===\n\r", pair.title)),
+        Print(STATIC_INFO_TAB.join("\n\r")),
+        ResetColor,
+        MoveTo(0, STATIC_INFO_TAB_LEN + 1)
+    )?;
 
+    let mut fields = vec![
+        ("Title", pair.title.clone()),
+        ("URL", pair.url.clone()),
+    ];
+
+    // what its placeholders were filled with lately
+    for (idx, name) in pair.url_placeholders().into_iter().enumerate() {
+        let recent = match pair.get_recent_values(&name) {
+            [] => String::from("asked for when opened"),
+            recent => format!("asked for when opened, lately {}", recent.join(", ")),
+        };
+        fields.push((if idx == 0 { "Template" } else { "" }, format!("{{{}}}: {}", name, recent)));
+    }
 
+    if let Some(parts) = url_parts(&pair.url) {
+        fields.push(("Host", parts.host.unwrap_or_default()));
+        fields.push(("Path", parts.path));
+        for (idx, (key, value)) in parts.query.into_iter().enumerate() {
+            let name = if idx == 0 { "Query" } else { "" };
+            fields.push((name, format!("{} = {}", key, value)));
+        }
+    }
 
+    fields.push(("Added", pair.get_time_created().format(ROW_TIME_FORMAT).to_string()));
+
+    let opened = pair.get_times_opened();
+    let opened = match opened.len() {
+        0 => String::from("never"),
+        n => format!(
+            "{} time(s), most recently {}",
+            n,
+            opened
+                .iter()
+                .rev()
+                .take(OPENED_SHOWN)
+                .map(|t| t.format(ROW_TIME_FORMAT).to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    fields.push(("Opened", opened));
+    fields.push(("Tags", match pair.get_tags().is_empty() {
+        true => String::from("none"),
+        false => format_tags(pair.get_tags()),
+    }));
+    match pair.get_note().is_empty() {
+        true => fields.push(("Note", String::from("none, press W to write one"))),
+        false => for (idx, line) in pair.get_note().lines().enumerate() {
+            fields.push((if idx == 0 { "Note" } else { "" }, line.to_string()));
+        },
+    }
 
-pub fn render_line(stdout: &mut Stdout, line: &str, opt_fg_color: Option<Color>) -> Result<(), Errors> {
-    
-    if let Some(color) = opt_fg_color {
+    fields.push(("Profile", prfl.get_name().to_string()));
+    fields.push(("Section", match pair.get_section().is_empty() {
+        true => String::from("none"),
+        false => pair.get_section().to_string(),
+    }));
+    fields.push(("Also in", match also_in.is_empty() {
+        true => String::from("no other profile"),
+        false => also_in.join(", "),
+    }));
+
+    for (name, value) in fields {
         write_stdout!(
             stdout,
-            SetForegroundColor(color)
+            SetForegroundColor(COLOR_FG_COLUMNS),
+            Print(format!("{:>8}{} ", name, if name.is_empty() { " " } else { ":" })),
+            ResetColor,
+            Print(sanitize(&value)),
+            Print("\n\r")
         )?;
     }
+
+    Ok(())
+}
+
+
+/// Clears the screen and asks which profile to pick, see [render_list_of_profiles]
+pub fn render_pick_beginning(stdout: &mut impl Backend, question: &str) -> Result<(), Errors> {
     write_stdout!(
         stdout,
-        Print(line)
+
+        Clear(ClearType::All),
+
+        MoveTo(0, 0),
+        SetForegroundColor(COLOR_FG_DECLARE),
+        Print(format!("=== {} ===\n\r", question)),
+        Print(STATIC_INFO_PICK.join("\n\r")),
+        ResetColor
     )?;
+
     Ok(())
 }
 
 
-
-pub fn render_beginning(stdout: &mut Stdout) -> Result<(), Errors> {
+///
+///Clears the screen and prints `labels` (the orders to choose from) below `question`,
+///with the one in use (at `current_idx`) marked.
+///
+pub fn render_sort_menu(
+    stdout: &mut impl Backend,
+    question: &str,
+    labels: &[&str],
+    current_idx: usize,
+    highlight_idx: usize,
+    viewport: &Viewport
+) -> Result<(), Errors> {
     write_stdout!(
         stdout,
 
-        Clear(ClearType::All), // this is necessary
+        Clear(ClearType::All),
 
         MoveTo(0, 0),
         SetForegroundColor(COLOR_FG_DECLARE),
-        Print(STATIC_INFO_MAINMENU.join("\n\r")),
-        MoveTo(0, STATIC_INFO_MAINMENU_LEN + 1),
+        Print(format!("=== {} ===\n\r", question)),
+        Print(STATIC_INFO_SORT.join("\n\r")),
         ResetColor
     )?;
 
-    Ok(())
+    let rows: Vec<String> = labels
+        .iter()
+        .enumerate()
+        .map(|(idx, label)| match idx == current_idx {
+            true => format!("{}{} (current)", ROW_PREFIX, label),
+            false => format!("{}{}", ROW_PREFIX, label),
+        })
+        .collect();
+
+    render_rows(stdout, &rows, STATIC_INFO_SORT_LEN + 1, 0, viewport, highlight_idx, None, None)
 }
 
 
+///
+///Prints `label` and the text being typed in the last row, instead of the status line,
+///with the character at `cursor` (counted in chars) highlighted.
+///
+///Without a `cursor` only `label` is printed, e.g. for a yes / no question.
+///
+pub fn render_prompt(stdout: &mut impl Backend, label: &str, text: &str, cursor: Option<usize>) -> Result<(), Errors> {
+    let (_, rows) = stdout.size()?;
+
+    write_stdout!(
+        stdout,
+        MoveTo(0, rows.saturating_sub(1)),
+        Clear(ClearType::CurrentLine),
+        SetForegroundColor(COLOR_FG_DECLARE),
+        Print(label),
+        ResetColor
+    )?;
+
+    let Some(cursor) = cursor else {
+        return Ok(());
+    };
+
+    let text = sanitize(text);
+    let before: String = text.chars().take(cursor).collect();
+    let at: String = text.chars().nth(cursor).unwrap_or(' ').to_string();
+    let after: String = text.chars().skip(cursor + 1).collect();
+
+    write_stdout!(
+        stdout,
+        Print(before),
+        SetForegroundColor(COLOR_FG_HILIT),
+        SetBackgroundColor(COLOR_BG_HILIT),
+        Print(at),
+        ResetColor,
+        Print(after)
+    )?;
+
+    Ok(())
+}
+
 
+///
+///Renders the `rows` of `prfl` (see [Profile::rows]) as `Title | URL` rows below `pos_row_last`,
+///only those `filter` shows.
+///
+#[allow(clippy::too_many_arguments)]
 pub fn render_one_profile(
-    stdout: &mut Stdout,
+    stdout: &mut impl Backend,
     prfl: &Profile,
+    rows: &[PairRow],
     pos_row_last: u16,
     pos_col: u16,
-    highlight_idx: Option<usize>
+    highlight_idx: Option<usize>,
+    viewport: &Viewport,
+    url_elide: Elide,
+    filter: &RowFilter
 ) -> Result<(), Errors> {
 
     let pos_row = pos_row_last
@@ -130,53 +1252,120 @@ pub fn render_one_profile(
 
     write_stdout!(
         stdout,
-        MoveTo(pos_row, pos_col)
+        MoveTo(pos_col, pos_row)
     )?;
 
-    if let None = highlight_idx {
-        render_line(stdout, "No URL-Title pairs available in this profile. Press A to ADD a new one.", Some(COLOR_FG_HILIT))?;
+    if highlight_idx.is_none() {
+        render_line(stdout, "No URL-Title pairs available in this profile. Press P to PASTE URLs from the clipboard, or use the add command.", Some(COLOR_FG_HILIT))?;
         return Ok(());
     }
     let idx = highlight_idx.unwrap();
 
-    let pairs: Vec<_> = prfl
-        .get_pairs()
+    let with_tags = prfl.get_pairs().iter().any(|pair| !pair.get_tags().is_empty());
+    let width = viewport.get_width();
+    let pairs: Vec<_> = filter
+        .apply(rows)
         .into_iter()
-        .map(|pair| format_pair!(pair))
+        .map(|row| match row {
+            PairRow::Section { name, collapsed, count } => format_section_row(name, *count, *collapsed, width, url_elide, with_tags),
+            PairRow::Pair { tab_idx } => {
+                let pair = &prfl.get_pairs()[*tab_idx];
+                format_pair_row(pair, width, url_elide, filter.is_selected(*tab_idx), with_tags)
+            },
+        })
         .collect();
+    if pairs.is_empty() {
+        return render_line(stdout, "No tab matches the search.", Some(COLOR_FG_HILIT));
+    }
 
+    let (columns, titles) = columns_with_tags(&pair_columns(url_elide), &PAIR_COLUMN_TITLES, with_tags);
+    render_column_titles(stdout, &titles, &columns, pos_row_last, pos_col, viewport)?;
+    render_rows(stdout, &pairs, pos_row, pos_col, viewport, idx, filter.needle, with_tags.then_some(PAIR_COLUMN_TITLES.len()))
+}
 
+/// Clears the screen and prints how many tabs the search across all profiles found
+pub fn render_search_beginning(stdout: &mut impl Backend, hit_count: usize) -> Result<(), Errors> {
     write_stdout!(
         stdout,
-        Clear(ClearType::All)
+
+        Clear(ClearType::All),
+
+        MoveTo(0, 0),
+        SetForegroundColor(COLOR_FG_DECLARE),
+        Print(format!("=== Search all profiles: {} tab(s) found ===\n\r", hit_count)),
+        Print(STATIC_INFO_SEARCH.join("\n\r")),
+        ResetColor
     )?;
-    for (pair_idx, pair) in pairs.into_iter().enumerate() {
 
-            if idx == pair_idx {
-                write_stdout!(
-                    stdout,
-                    Print(pair)
-                )?;
-            }
-            else {
-                write_stdout!(
-                    stdout,
-                    Print(pair)
-                )?;
-            }
+    Ok(())
+}
+
+/// Renders the tabs found in all profiles as `Profile | Title | URL` rows below `pos_row_last`
+pub fn render_search_results(
+    stdout: &mut impl Backend,
+    hits: &[(&Profile, &URLTitlePair)],
+    pos_row_last: u16,
+    pos_col: u16,
+    highlight_idx: usize,
+    viewport: &Viewport,
+    needle: Option<&str>
+) -> Result<(), Errors> {
+
+    let pos_row = pos_row_last
+        .checked_add(1)
+        .ok_or(Errors::CursorPosOverflowError)?;
+
+    if hits.is_empty() {
+        write_stdout!(stdout, MoveTo(pos_col, pos_row))?;
+        return render_line(stdout, "No tab matches the search.", Some(COLOR_FG_HILIT));
     }
 
+    let rows: Vec<_> = hits
+        .iter()
+        .map(|(prfl, pair)| {
+            layout_row(ROW_PREFIX, &[prfl.get_name(), &pair.title, &pair.url], &SEARCH_COLUMNS, viewport.get_width())
+        })
+        .collect();
 
-    Ok(())
+    render_column_titles(stdout, &SEARCH_COLUMN_TITLES, &SEARCH_COLUMNS, pos_row_last, pos_col, viewport)?;
+    render_rows(stdout, &rows, pos_row, pos_col, viewport, highlight_idx, needle, None)
 }
 
+
+/// Renders all of `prfls` as rows below `pos_row_last` in the order of the main menu (see [profile_order]), without their groups
 pub fn render_list_of_profiles(
-    stdout: &mut Stdout,
-    prfls: &Vec<Profile>,
+    stdout: &mut impl Backend,
+    prfls: &[Profile],
     pos_row_last: u16,
     pos_col: u16,
 
-    highlight_idx: usize
+    highlight_idx: usize,
+    viewport: &Viewport,
+    filter: &RowFilter
+) -> Result<(), Errors> {
+    let tree: Vec<TreeRow> = profile_order(prfls)
+        .into_iter()
+        .map(|prfl_idx| TreeRow::Profile { prfl_idx, depth: 0 })
+        .collect();
+
+    render_profile_tree(stdout, prfls, &tree, pos_row_last, pos_col, highlight_idx, viewport, filter)
+}
+
+///
+///Renders the `tree` of `prfls` (see [crate::profile_tree]) below `pos_row_last`,
+///only the rows `filter` shows.
+///
+#[allow(clippy::too_many_arguments)]
+pub fn render_profile_tree(
+    stdout: &mut impl Backend,
+    prfls: &[Profile],
+    tree: &[TreeRow],
+    pos_row_last: u16,
+    pos_col: u16,
+
+    highlight_idx: usize,
+    viewport: &Viewport,
+    filter: &RowFilter
 ) -> Result<(), Errors> {
 
     let pos_row = pos_row_last
@@ -185,90 +1374,466 @@ pub fn render_list_of_profiles(
 
     debug_println!(">>RLOP: pos row read;");
 
+    debug_println!(">>RLOP: start (idx, prfl) in prfls");
+    let with_tags = prfls.iter().any(|prfl| !prfl.get_tags().is_empty());
+    let width = viewport.get_width();
+    let rows: Vec<_> = filter
+        .apply(tree)
+        .into_iter()
+        .map(|row| match row {
+            TreeRow::Group { path, depth, collapsed } => format_group_row(prfls, path, *depth, *collapsed, width, with_tags),
+            TreeRow::Profile { prfl_idx, depth } => format_profile_row(&prfls[*prfl_idx], width, with_tags, *depth),
+        })
+        .collect();
+    if rows.is_empty() {
+        write_stdout!(stdout, MoveTo(pos_col, pos_row))?;
+        return render_line(stdout, "No profile matches the search.", Some(COLOR_FG_HILIT));
+    }
+
+    let (columns, titles) = columns_with_tags(&PROFILE_COLUMNS, &PROFILE_COLUMN_TITLES, with_tags);
+    render_column_titles(stdout, &titles, &columns, pos_row_last, pos_col, viewport)?;
+    render_rows(stdout, &rows, pos_row, pos_col, viewport, highlight_idx, filter.needle, with_tags.then_some(PROFILE_COLUMNS.len()))?;
+
+    debug_println!(">>RLOP: finish");
+
+    Ok(())
+}
+
 
+///
+///Clears the screen and lists every tag with the number of tabs it is on (see [crate::tag_counts]),
+///the tags as chips.
+///
+pub fn render_tag_list(
+    stdout: &mut impl Backend,
+    tags: &[(String, usize)],
+    highlight_idx: usize,
+    viewport: &Viewport
+) -> Result<(), Errors> {
     write_stdout!(
         stdout,
-        MoveTo(pos_col, pos_row)
+
+        Clear(ClearType::All),
+
+        MoveTo(0, 0),
+        SetForegroundColor(COLOR_FG_DECLARE),
+        Print(format!("=== Tags: {} ===\n\r", tags.len())),
+        Print(STATIC_INFO_TAGS.join("\n\r")),
+        ResetColor
     )?;
 
-    debug_println!(">>RLOP: start (idx, prfl) in prfls");
-    for (idx, prfl) in prfls.iter().enumerate() {
+    let pos_row = STATIC_INFO_TAGS_LEN + 1;
+    if tags.is_empty() {
+        write_stdout!(stdout, MoveTo(0, pos_row))?;
+        return render_line(stdout, "No tab or profile is tagged yet. Press T on one to tag it.", Some(COLOR_FG_HILIT));
+    }
 
-        debug_println!(">>RLOP-Loop : ({}, {:?})", idx, prfl);
+    let rows: Vec<_> = tags
+        .iter()
+        .map(|(tag, count)| {
+            layout_row(ROW_PREFIX, &[&format_tags(std::slice::from_ref(tag)), &count.to_string()], &TAG_COLUMNS, viewport.get_width())
+        })
+        .collect();
 
-        let printstr = format_profile!(prfl);
+    render_column_titles(stdout, &TAG_COLUMN_TITLES, &TAG_COLUMNS, STATIC_INFO_TAGS_LEN, 0, viewport)?;
+    render_rows(stdout, &rows, pos_row, 0, viewport, highlight_idx, None, Some(0))
+}
 
-        if idx == highlight_idx {
-            write_stdout!(
-                stdout,
-                SetForegroundColor(COLOR_FG_HILIT),
-                SetBackgroundColor(COLOR_BG_HILIT)
-            )?;
+/// Clears the screen and prints `tag` with how many tabs of all profiles have it, see [render_search_results]
+pub fn render_tag_view_beginning(stdout: &mut impl Backend, tag: &str, hit_count: usize) -> Result<(), Errors> {
+    write_stdout!(
+        stdout,
+
+        Clear(ClearType::All),
+
+        MoveTo(0, 0),
+        SetForegroundColor(COLOR_FG_DECLARE),
+        Print(format!("=== Tag: {}{} ({} tabs) ===\n\r", TAG_CHIP_PREFIX, tag, hit_count)),
+        Print(STATIC_INFO_TAG_VIEW.join("\n\r")),
+        ResetColor
+    )?;
+
+    Ok(())
+}
+
+
+///
+///Clears the screen and shows the note being written on `what` in `area`,
+///scrolled so that the line with the cursor is on screen.
+///
+pub fn render_note_editor(stdout: &mut impl Backend, what: &str, area: &TextArea) -> Result<(), Errors> {
+    write_stdout!(
+        stdout,
+
+        Clear(ClearType::All),
+
+        MoveTo(0, 0),
+        SetForegroundColor(COLOR_FG_DECLARE),
+        Print(format!("=== Note on {} ===\n\r", what)),
+        Print(STATIC_INFO_NOTE.join("\n\r")),
+        ResetColor
+    )?;
 
+    let (cols, rows) = stdout.size()?;
+    let pos_row = STATIC_INFO_NOTE_LEN + 1;
+    // the status line stays free
+    let height = rows.saturating_sub(pos_row + 1).max(1) as usize;
+    let width = cols.saturating_sub(1).max(1) as usize;
+
+    let (cursor_row, cursor_col) = area.get_cursor();
+    let first = cursor_row.saturating_sub(height - 1);
+
+    for (line_idx, line) in area.get_lines().into_iter().enumerate().skip(first).take(height) {
+        write_stdout!(stdout, MoveTo(0, pos_row + (line_idx - first) as u16))?;
+
+        let line: Vec<char> = sanitize(line).chars().collect();
+        if line_idx != cursor_row {
+            write_stdout!(stdout, Print(truncate_end(&line.iter().collect::<String>(), width)))?;
+            continue;
         }
 
+        // the line with the cursor scrolls sideways instead of being cut
+        let skip = (cursor_col + 1).saturating_sub(width);
+        let before: String = line.iter().skip(skip).take(cursor_col - skip).collect();
+        let at: String = line.get(cursor_col).unwrap_or(&' ').to_string();
+        let after: String = line.iter().skip(cursor_col + 1).take(width.saturating_sub(cursor_col + 1 - skip)).collect();
+
         write_stdout!(
             stdout,
-            Print(printstr),
-            ResetColor
+            Print(before),
+            SetForegroundColor(COLOR_FG_HILIT),
+            SetBackgroundColor(COLOR_BG_HILIT),
+            Print(at),
+            ResetColor,
+            Print(after)
         )?;
     }
 
-    debug_println!(">>RLOP: finish");
-
     Ok(())
 }
 
 
+///
+///Clears the screen and lists the stale `tabs` (see [Profile::stale_pairs]) with their profile,
+///and when they were last opened (or `never`) as of `at`.
+///
+pub fn render_stale_tabs(
+    stdout: &mut impl Backend,
+    tabs: &[(&Profile, &URLTitlePair)],
+    at: chrono::NaiveDateTime,
+    highlight_idx: usize,
+    viewport: &Viewport
+) -> Result<(), Errors> {
+    write_stdout!(
+        stdout,
 
-pub fn render_profile(
-    stdout: &mut Stdout,
-    prfl: &mut Profile,
-    pos_row_last: u16, /* the last row BEFORE we start rendering */
-    pos_col: u16
-) -> Result<(), Errors>
-{
+        Clear(ClearType::All),
+
+        MoveTo(0, 0),
+        SetForegroundColor(COLOR_FG_DECLARE),
+        Print(format!("=== Stale tabs: {} ===\n\r", tabs.len())),
+        Print(STATIC_INFO_STALE.join("\n\r")),
+        ResetColor
+    )?;
+
+    let pos_row = STATIC_INFO_STALE_LEN + 1;
+    if tabs.is_empty() {
+        write_stdout!(stdout, MoveTo(0, pos_row))?;
+        return render_line(stdout, "No tab is stale. Press L in a profile to set when its tabs go stale.", Some(COLOR_FG_HILIT));
+    }
+
+    let rows: Vec<_> = tabs
+        .iter()
+        .map(|(prfl, pair)| {
+            let opened = match pair.get_time_last_opened() {
+                Some(t) => format!("{}d ago", (at - t).num_days()),
+                None => String::from("never"),
+            };
+            layout_row(ROW_PREFIX, &[prfl.get_name(), &pair.title, &opened], &STALE_COLUMNS, viewport.get_width())
+        })
+        .collect();
+
+    render_column_titles(stdout, &STALE_COLUMN_TITLES, &STALE_COLUMNS, STATIC_INFO_STALE_LEN, 0, viewport)?;
+    render_rows(stdout, &rows, pos_row, 0, viewport, highlight_idx, None, None)
+}
 
-    let pos_row = pos_row_last
-        .checked_add(1)
-        .ok_or(Errors::CursorPosOverflowError)?;
 
-    // move cursor to position, then render
+/// Clears the screen and lists `keys`, the keys of the screen called `what`, for [STATIC_INFO_MAINMENU] and the like
+pub fn render_help(
+    stdout: &mut impl Backend,
+    what: &str,
+    keys: &[&str],
+    highlight_idx: usize,
+    viewport: &Viewport
+) -> Result<(), Errors> {
     write_stdout!(
         stdout,
-        MoveTo(
-            pos_col,
-            pos_row
-        )
+
+        Clear(ClearType::All),
+
+        MoveTo(0, 0),
+        SetForegroundColor(COLOR_FG_DECLARE),
+        Print(format!("=== Keys: {} ===\n\r", what)),
+        Print(STATIC_INFO_HELP.join("\n\r")),
+        ResetColor
     )?;
 
-    render_profile_impl(stdout, prfl)
-}
+    let rows: Vec<String> = keys
+        .iter()
+        .map(|line| truncate_end(line, viewport.get_width()))
+        .collect();
 
+    // no column titles, their row stays empty
+    render_rows(stdout, &rows, STATIC_INFO_HELP_LEN + 1, 0, viewport, highlight_idx, None, None)
+}
 
 
+///
+///Clears the screen and shows `rows` of [crate::diff::diff_profiles] of `old` and `new` side by side,
+///each in the colour of its [DiffKind]; `new_label` is the name of `new`, or the file it was read from.
+///
+#[allow(clippy::too_many_arguments)]
+pub fn render_diff(
+    stdout: &mut impl Backend,
+    old: &Profile,
+    new: &Profile,
+    new_label: &str,
+    rows: &[DiffRow],
+    only_changes: bool,
+    highlight_idx: usize,
+    viewport: &Viewport
+) -> Result<(), Errors> {
+    let count = |kind: DiffKind| rows.iter().filter(|row| row.kind == kind).count();
+    let shown = match only_changes {
+        true => "only the changes",
+        false => "every tab",
+    };
 
+    write_stdout!(
+        stdout,
 
-fn render_profile_impl(stdout: &mut Stdout, prfl: &mut Profile) -> Result<(), Errors> {
-    
+        Clear(ClearType::All),
 
-    for pair in prfl.get_pairs().iter() {
+        MoveTo(0, 0),
+        SetForegroundColor(COLOR_FG_DECLARE),
+        Print(format!(
+            "=== Diff: {} <> {} (+{} -{} ~{}, {}) ===\n\r",
+            old.get_name(), new_label, count(DiffKind::Added), count(DiffKind::Removed), count(DiffKind::Retitled), shown
+        )),
+        Print(STATIC_INFO_DIFF.join("\n\r")),
+        ResetColor
+    )?;
 
-        let mut fg_color = COLOR_FG_DEFAULT;
+    let pos_row = STATIC_INFO_DIFF_LEN + 1;
+    if rows.is_empty() {
+        write_stdout!(stdout, MoveTo(0, pos_row))?;
+        return render_line(stdout, "Both have the same tabs with the same titles.", Some(COLOR_FG_HILIT));
+    }
 
-        if pair.clone().is_highlighted() {
-            fg_color = COLOR_FG_HILIT;
-            write_stdout!(stdout, SetBackgroundColor(COLOR_BG_HILIT))?;
+    let title = |prfl: &Profile, idx: Option<usize>| idx.map(|idx| sanitize(&prfl.get_pairs()[idx].title)).unwrap_or_default();
+    let titles = [old.get_name().as_str(), new_label, PAIR_COLUMN_TITLES[1]];
+    render_column_titles(stdout, &titles, &DIFF_COLUMNS, STATIC_INFO_DIFF_LEN, 0, viewport)?;
+
+    for (line, idx) in viewport.range(rows.len()).enumerate() {
+        let row = &rows[idx];
+        let url = match row.new_idx {
+            Some(new_idx) => &new.get_pairs()[new_idx].url,
+            None => &old.get_pairs()[row.old_idx.unwrap_or_default()].url,
+        };
+        let prefix = format!("{}  ", row.kind.mark());
+        let text = layout_row(
+            &prefix,
+            &[&title(old, row.old_idx), &title(new, row.new_idx), &sanitize(url)],
+            &DIFF_COLUMNS,
+            viewport.get_width()
+        );
+
+        let pos = pos_row
+            .checked_add(line as u16)
+            .ok_or(Errors::CursorPosOverflowError)?;
+        write_stdout!(stdout, MoveTo(0, pos))?;
+
+        match idx == highlight_idx {
+            true => write_stdout!(stdout, SetForegroundColor(COLOR_FG_HILIT), SetBackgroundColor(COLOR_BG_HILIT))?,
+            false => write_stdout!(stdout, SetForegroundColor(match row.kind {
+                DiffKind::Same => COLOR_FG_DEFAULT,
+                DiffKind::Added => COLOR_FG_ADDED,
+                DiffKind::Removed => COLOR_FG_REMOVED,
+                DiffKind::Retitled => COLOR_FG_RETITLED,
+            }))?,
         }
-        
-        write_stdout!(
-            stdout,
-            SetForegroundColor(fg_color),
-            Print(format_pair!(pair)),
-            ResetColor
-        )?;
+        write_stdout!(stdout, Print(text), ResetColor)?;
     }
 
-    Ok(())
+    let indicator_row = pos_row
+        .checked_add(viewport.get_height() as u16)
+        .ok_or(Errors::CursorPosOverflowError)?;
+
+    render_scroll_indicator(stdout, indicator_row, 0, viewport, rows.len(), highlight_idx)
 }
 
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::ScreenBuffer;
+
+    fn viewport(height: usize) -> Viewport {
+        Viewport { offset: 0, height, width: 80 }
+    }
+
+    #[test]
+    fn the_viewport_fits_the_terminal() {
+        let mut view = Viewport::default();
+
+        view.resize(100, 24, 10);
+        assert_eq!(view.get_height(), 24 - 10 - 1 - LIST_FOOTER_LEN as usize);
+        assert_eq!(view.get_width(), 100);
+        // a terminal too small still shows one row
+        view.resize(0, 5, 10);
+        assert_eq!((view.get_width(), view.get_height()), (1, 1));
+    }
+
+    #[test]
+    fn the_viewport_scrolls_as_little_as_possible() {
+        let mut view = viewport(5);
+
+        view.scroll_to(3, 20);
+        assert_eq!(view.range(20), 0..5);
+        view.scroll_to(7, 20);
+        assert_eq!(view.range(20), 3..8);
+        view.scroll_to(2, 20);
+        assert_eq!(view.range(20), 2..7);
+
+        // the list got shorter: no empty rows below the last one
+        view.scroll_to(19, 20);
+        view.scroll_to(5, 6);
+        assert_eq!(view.range(6), 1..6);
+        assert_eq!(viewport(5).range(3), 0..3);
+    }
+
+    #[test]
+    fn the_cursor_wraps_or_stops() {
+        let view = viewport(5);
+
+        assert_eq!(move_cursor(0, 20, CursorMove::Up, &view), 19);
+        assert_eq!(move_cursor(19, 20, CursorMove::Down, &view), 0);
+        assert_eq!(move_cursor(3, 20, CursorMove::PageUp, &view), 0);
+        assert_eq!(move_cursor(3, 20, CursorMove::PageDown, &view), 8);
+        assert_eq!(move_cursor(17, 20, CursorMove::PageDown, &view), 19);
+        assert_eq!(move_cursor(7, 20, CursorMove::Home, &view), 0);
+        assert_eq!(move_cursor(7, 20, CursorMove::End, &view), 19);
+        assert_eq!(move_cursor(0, 0, CursorMove::End, &view), 0);
+    }
+
+    const LEFT: Column = Column { width: ColumnWidth::Flex(1), align: Align::Left, elide: Elide::End };
+
+    #[test]
+    fn truncation_counts_cells_not_chars() {
+        assert_eq!(truncate_end("Firefox", 7), "Firefox");
+        assert_eq!(truncate_end("Firefox", 5), "Fire…");
+        // 2 cells each: a wide char never sticks out of the column
+        assert_eq!(truncate_end("日本語のページ", 6), "日本…");
+        assert_eq!(truncate_end("日本語のページ", 5), "日本…");
+        assert_eq!(truncate_end("🦀🦀🦀", 4), "🦀…");
+        assert!(display_width(&truncate_end("a日本語", 4)) <= 4);
+        // zero width chars stay with what they belong to
+        assert_eq!(truncate_end("e\u{301}tude", 3), "e\u{301}t…");
+
+        assert_eq!(truncate_end("Firefox", 1), "…");
+        assert_eq!(truncate_end("日本", 1), "…");
+        assert_eq!(truncate_end("Firefox", 0), "");
+        assert_eq!(truncate_end("", 0), "");
+    }
+
+    #[test]
+    fn eliding_keeps_both_ends() {
+        assert_eq!(elide_middle("https://example.com/a/b/page.html", 17), "https://…age.html");
+        assert_eq!(elide_middle("https://example.com", 19), "https://example.com");
+        assert_eq!(elide_middle("日本語のページです", 9), "日本…です");
+        assert!(display_width(&elide_middle("日本語のページです", 8)) <= 8);
+
+        assert_eq!(elide_middle("Firefox", 2), "F…");
+        assert_eq!(elide_middle("Firefox", 1), "…");
+        assert_eq!(elide_middle("Firefox", 0), "");
+    }
+
+    #[test]
+    fn columns_share_what_is_left() {
+        let fixed = |width| Column { width: ColumnWidth::Fixed(width), align: Align::Right, elide: Elide::End };
+        let flex = |weight| Column { width: ColumnWidth::Flex(weight), ..LEFT };
+
+        assert_eq!(column_widths(&[flex(1), fixed(5), fixed(16)], 80), vec![59, 5, 16]);
+        // the last flexible column gets the rounding
+        assert_eq!(column_widths(&[flex(2), flex(3)], 11), vec![4, 7]);
+        // fixed columns keep their width even if there is no room
+        assert_eq!(column_widths(&[flex(1), fixed(5)], 3), vec![0, 5]);
+        assert_eq!(column_widths(&[flex(1), flex(1)], 0), vec![0, 0]);
+    }
+
+    #[test]
+    fn rows_are_exactly_as_wide_as_the_screen() {
+        let columns = [LEFT, Column { width: ColumnWidth::Fixed(4), align: Align::Right, elide: Elide::End }];
+
+        assert_eq!(layout_row(ROW_PREFIX, &["News", "12"], &columns, 20), ">> News       |   12");
+        assert_eq!(layout_row(ROW_PREFIX, &["日本語のニュース", "12"], &columns, 20), ">> 日本語の…  |   12");
+        assert_eq!(display_width(&layout_row(ROW_PREFIX, &["日本語のニュース", "12"], &columns, 19)), 19);
+        // tabs and line breaks do not move the cursor
+        assert_eq!(layout_row("", &["a\tb\nc", "1"], &columns, 12), "a b c |    1");
+        assert_eq!(layout_row(ROW_PREFIX, &["News", "12"], &columns, 1), "…");
+    }
+
+    fn profile(name: &str, tabs: usize) -> Profile {
+        let pairs = (0..tabs)
+            .map(|n| URLTitlePair::new(&format!("https://{}.com/{:02}", name.to_lowercase(), n), &format!("Tab {:02}", n)))
+            .collect();
+
+        Profile::builder().add_name(name).add_many_title_url_pairs(pairs).build()
+    }
+
+    #[test]
+    fn renders_the_main_menu() {
+        let mut screen = ScreenBuffer::new(100, 40);
+        let mut view = Viewport::default();
+        view.resize(100, 40, STATIC_INFO_MAINMENU_LEN);
+
+        render_beginning(&mut screen, ProfileSortMode::default()).unwrap();
+        render_list_of_profiles(&mut screen, &[profile("Alpha", 2), profile("Beta", 12)], STATIC_INFO_MAINMENU_LEN, 0, 1, &view, &RowFilter::default()).unwrap();
+
+        assert_eq!(screen.row(0), "=== Firefox Tab Resumer ===");
+        let first = screen.find(">> Alpha ").expect("the profiles are listed");
+        assert!(screen.row(first - 1).starts_with("   Name "));
+        assert!(screen.row(first).contains("|     2 |"));
+        assert!(screen.row(first + 1).starts_with(">> Beta "));
+        assert_eq!(screen.cell(0, first + 1).and_then(|cell| cell.bg), Some(COLOR_BG_HILIT));
+        assert_eq!(screen.row(first + view.get_height() as u16), "-- 2 of 2 --");
+    }
+
+    #[test]
+    fn renders_a_profile_longer_than_the_screen() {
+        let prfl = profile("Alpha", 100);
+        let mut view = Viewport::default();
+        view.resize(100, 30, STATIC_INFO_PROFILE_LEN);
+        let height = view.get_height();
+
+        let mut screen = ScreenBuffer::new(100, 30);
+        render_profile_beginning(&mut screen, &prfl).unwrap();
+        render_one_profile(&mut screen, &prfl, &prfl.rows(&[]), STATIC_INFO_PROFILE_LEN, 0, Some(0), &view, Elide::End, &RowFilter::default()).unwrap();
+
+        assert_eq!(screen.row(0), "=== Profile: Alpha (100 tabs, oldest first) ===");
+        let first = screen.find(">> Tab 00 ").expect("the first tabs are shown");
+        assert!(screen.row(first).ends_with("| https://alpha.com/00"));
+        assert!(screen.row(first + height as u16 - 1).starts_with(&format!(">> Tab {:02} ", height - 1)));
+        assert!(screen.find(&format!("Tab {:02}", height)).is_none());
+        assert_eq!(screen.row(first + height as u16), format!("-- 1 of 100 | v {} more below --", 100 - height));
+
+        view.scroll_to(99, 100);
+        render_profile_beginning(&mut screen, &prfl).unwrap();
+        render_one_profile(&mut screen, &prfl, &prfl.rows(&[]), STATIC_INFO_PROFILE_LEN, 0, Some(99), &view, Elide::End, &RowFilter::default()).unwrap();
+        assert_eq!(screen.find(&format!(">> Tab {:02} ", 100 - height)), Some(first));
+        assert!(screen.find("Tab 00").is_none());
+        assert_eq!(screen.row(first + height as u16), format!("-- ^ {} more above | 100 of 100 --", 100 - height));
+    }
+}
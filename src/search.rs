@@ -0,0 +1,365 @@
+use crate::{query::Query, urls::host_of, Profile, URLTitlePair};
+
+//
+//
+//Looking up tabs and profiles by what the user remembers of them:
+//fuzzy (fzf-like) matching, where the characters of the query have to appear
+//in order but not next to each other, and the best alignment is scored.
+//
+//
+
+
+
+const SCORE_MATCH: i64 = 16;
+/// the first character of a word (after `/`, `.`, `-`, a space, ...)
+const BONUS_BOUNDARY: i64 = 8;
+/// `camelCase` humps and the first digit of a number
+const BONUS_CAMEL: i64 = 7;
+/// for a character right after the previous match
+const BONUS_CONSECUTIVE: i64 = 4;
+/// the first character of the query counts this many times more at a boundary
+const BONUS_FIRST_CHAR_MULTIPLIER: i64 = 2;
+/// a term found in the host is worth more than the same term somewhere in the path
+const BONUS_HOST: i64 = 10;
+
+const PENALTY_GAP_START: i64 = 3;
+const PENALTY_GAP_EXTENSION: i64 = 1;
+
+/// "no alignment ends here"; far enough from [i64::MIN] to subtract from
+const NO_SCORE: i64 = i64::MIN / 2;
+
+
+
+/// How well a query matched a text, see [fuzzy_match]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// higher is better
+    pub score: i64,
+    /// indices of the matched chars (not bytes) of the text, ascending
+    pub positions: Vec<usize>,
+}
+
+
+/// A tab found by [search_profiles]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    pub prfl_idx: usize,
+    pub tab_idx: usize,
+
+    /// higher is better, see [score_pair]
+    pub score: i64,
+    /// matched chars of the title, see [FuzzyMatch::positions]
+    pub title_positions: Vec<usize>,
+    /// matched chars of the URL (host matches included), see [FuzzyMatch::positions]
+    pub url_positions: Vec<usize>,
+}
+
+
+/// How well a query matched a tab, see [score_pair]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PairMatch {
+    pub score: i64,
+    pub title_positions: Vec<usize>,
+    pub url_positions: Vec<usize>,
+}
+
+
+
+
+fn lowercase(c: char) -> char {
+    // one char in, one char out, so that indices stay the same
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn bonus_at(text: &[char], idx: usize) -> i64 {
+    let curr = text[idx];
+    let Some(&prev) = idx.checked_sub(1).and_then(|prev| text.get(prev)) else {
+        return BONUS_BOUNDARY;
+    };
+
+    if !prev.is_alphanumeric() && curr.is_alphanumeric() {
+        BONUS_BOUNDARY
+    } else if (prev.is_lowercase() && curr.is_uppercase()) || (!prev.is_ascii_digit() && curr.is_ascii_digit()) {
+        BONUS_CAMEL
+    } else {
+        0
+    }
+}
+
+
+///
+///Finds the best scoring alignment of `term` (already lowercase) in `text`,
+///with every char of `term` matched in order.
+///
+///`score[i][j]` is the best score of matching `term[..=i]` with `term[i]` at `text[j]`;
+///gaps are tracked while scanning a row, so this is O(term * text).
+///
+fn match_term(text: &[char], term: &[char]) -> Option<FuzzyMatch> {
+    let (n, m) = (text.len(), term.len());
+    if m == 0 {
+        return Some(FuzzyMatch::default());
+    }
+    if m > n {
+        return None;
+    }
+
+    let lower: Vec<char> = text.iter().copied().map(lowercase).collect();
+    let bonus: Vec<i64> = (0..n).map(|j| bonus_at(text, j)).collect();
+
+    let mut score = vec![vec![NO_SCORE; n]; m];
+    let mut from = vec![vec![0usize; n]; m];
+
+    for j in 0..n {
+        if lower[j] == term[0] {
+            score[0][j] = SCORE_MATCH + bonus[j] * BONUS_FIRST_CHAR_MULTIPLIER;
+        }
+    }
+
+    for i in 1..m {
+        // the best alignment of term[..i] ending at least 2 chars before j, minus its gap
+        let mut gap_best = NO_SCORE;
+        let mut gap_from = 0;
+
+        for j in i..n {
+            if j >= 2 {
+                gap_best -= PENALTY_GAP_EXTENSION;
+                let opened = score[i - 1][j - 2] - PENALTY_GAP_START;
+                if opened > gap_best {
+                    gap_best = opened;
+                    gap_from = j - 2;
+                }
+            }
+
+            if lower[j] != term[i] {
+                continue;
+            }
+
+            let consecutive = score[i - 1][j - 1] + BONUS_CONSECUTIVE;
+            let (best, prev) = match consecutive >= gap_best {
+                true => (consecutive, j - 1),
+                false => (gap_best, gap_from),
+            };
+
+            if best > NO_SCORE / 2 {
+                score[i][j] = best + SCORE_MATCH + bonus[j];
+                from[i][j] = prev;
+            }
+        }
+    }
+
+    let (end, &best) = score[m - 1]
+        .iter()
+        .enumerate()
+        .max_by_key(|&(j, score)| (*score, std::cmp::Reverse(j)))?;
+    if best <= NO_SCORE / 2 {
+        return None;
+    }
+
+    let mut positions = vec![end; m];
+    for i in (1..m).rev() {
+        positions[i - 1] = from[i][positions[i]];
+    }
+
+    Some(FuzzyMatch { score: best, positions })
+}
+
+
+fn terms_of(query: &str) -> Vec<Vec<char>> {
+    query
+        .split_whitespace()
+        .map(|term| term.chars().map(lowercase).collect())
+        .collect()
+}
+
+
+///
+///Matches every whitespace separated term of `query` against `text`, ignoring case.
+///
+///Returns [None] if any term does not match; an empty `query` matches everything with score 0.
+///
+pub fn fuzzy_match(text: &str, query: &str) -> Option<FuzzyMatch> {
+    let text: Vec<char> = text.chars().collect();
+
+    let mut total = FuzzyMatch::default();
+    for term in terms_of(query) {
+        let found = match_term(&text, &term)?;
+
+        total.score += found.score;
+        total.positions.extend(found.positions);
+    }
+    total.positions.sort_unstable();
+    total.positions.dedup();
+
+    Some(total)
+}
+
+
+///
+///Matches every term of `query` against the title, URL and host of `pair`,
+///each term where it scores best.
+///
+pub fn score_pair(pair: &URLTitlePair, query: &str) -> Option<PairMatch> {
+    let title: Vec<char> = pair.title.chars().collect();
+    let url: Vec<char> = pair.url.chars().collect();
+
+    // host matches are reported as positions in the URL
+    let host = host_of(&pair.url).and_then(|host| {
+        let byte_idx = pair.url.find(&host)?;
+        Some((host.chars().collect::<Vec<_>>(), pair.url[..byte_idx].chars().count()))
+    });
+
+    let mut total = PairMatch::default();
+    for term in terms_of(query) {
+        let in_title = match_term(&title, &term).map(|found| (found.score, true, found.positions));
+        let in_url = match_term(&url, &term).map(|found| (found.score, false, found.positions));
+        let in_host = host.as_ref().and_then(|(host, offset)| {
+            let found = match_term(host, &term)?;
+            Some((found.score + BONUS_HOST, false, found.positions.iter().map(|pos| pos + offset).collect()))
+        });
+
+        let (score, is_title, positions) = [in_title, in_url, in_host]
+            .into_iter()
+            .flatten()
+            .max_by_key(|(score, ..)| *score)?;
+
+        total.score += score;
+        match is_title {
+            true => total.title_positions.extend(positions),
+            false => total.url_positions.extend(positions),
+        }
+    }
+
+    for positions in [&mut total.title_positions, &mut total.url_positions] {
+        positions.sort_unstable();
+        positions.dedup();
+    }
+
+    Some(total)
+}
+
+/// Whether `query` matches `pair`, see [score_pair]
+pub fn pair_matches(pair: &URLTitlePair, query: &str) -> bool {
+    score_pair(pair, query).is_some()
+}
+
+
+///
+///Every tab of every profile matching `query`,
+///best first (and in the order they are stored if equally good).
+///
+pub fn search_profiles(prfls: &[Profile], query: &Query) -> Vec<SearchHit> {
+    let mut hits: Vec<SearchHit> = prfls
+        .iter()
+        .enumerate()
+        .flat_map(|(prfl_idx, prfl)| {
+            prfl.get_pairs()
+                .iter()
+                .enumerate()
+                .filter_map(move |(tab_idx, pair)| {
+                    let found = query.score(prfl, pair)?;
+
+                    Some(SearchHit {
+                        prfl_idx,
+                        tab_idx,
+                        score: found.score,
+                        title_positions: found.title_positions,
+                        url_positions: found.url_positions,
+                    })
+                })
+        })
+        .collect();
+
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.score));
+
+    hits
+}
+
+
+/// The chars of `text` to mark for `query`, see [fuzzy_match]
+pub fn fuzzy_positions(text: &str, query: &str) -> Vec<usize> {
+    fuzzy_match(text, query)
+        .map(|found| found.positions)
+        .unwrap_or_default()
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    #[test]
+    fn terms_match_in_order() {
+        let found = match_term(&chars("Firefox Tab Resumer"), &chars("ftr")).unwrap();
+        assert_eq!(found.positions, vec![0, 8, 12]);
+
+        assert!(match_term(&chars("Firefox"), &chars("xf")).is_none());
+        assert!(match_term(&chars("Tab"), &chars("tabs")).is_none());
+        assert_eq!(match_term(&chars("Tab"), &[]), Some(FuzzyMatch::default()));
+    }
+
+    #[test]
+    fn boundaries_and_runs_score_higher() {
+        let score = |text: &str, term: &str| match_term(&chars(text), &chars(term)).unwrap().score;
+
+        // "tr" at the start of two words beats "tr" in the middle of one
+        assert!(score("Tab Resumer", "tr") > score("Patron", "tr"));
+        // a run of chars beats the same chars spread out
+        assert!(score("rust", "rust") > score("roust", "rust"));
+        // the best alignment is kept, not the first one
+        let found = match_term(&chars("bar foo_bar"), &chars("fb")).unwrap();
+        assert_eq!(found.positions, vec![4, 8]);
+    }
+
+    #[test]
+    fn pairs_match_in_the_title_and_url() {
+        let pair = URLTitlePair::new("https://www.rust-lang.org/learn", "The Book");
+
+        let found = score_pair(&pair, "book learn").unwrap();
+        assert_eq!(found.title_positions, vec![4, 5, 6, 7]);
+        assert_eq!(found.url_positions, vec![26, 27, 28, 29, 30]);
+
+        assert!(score_pair(&pair, "book python").is_none());
+        assert!(pair_matches(&pair, "BOOK"));
+    }
+
+    #[test]
+    fn hosts_score_higher_than_paths() {
+        let in_host = URLTitlePair::new("https://github.com/rust-lang", "Repositories");
+        let in_path = URLTitlePair::new("https://example.com/github", "Repositories");
+
+        assert!(score_pair(&in_host, "github").unwrap().score > score_pair(&in_path, "github").unwrap().score);
+        // host matches are marked in the URL
+        assert_eq!(score_pair(&in_host, "hub").unwrap().url_positions, vec![11, 12, 13]);
+    }
+
+    #[test]
+    fn the_best_hits_come_first() {
+        let prfls = vec![
+            Profile::builder()
+                .add_name("Work")
+                .add_many_title_url_pairs(vec![
+                    URLTitlePair::new("https://a.com/adhoc_results", "Mail"),
+                    URLTitlePair::new("https://b.com", "Team Docs"),
+                ])
+                .build(),
+            Profile::builder()
+                .add_name("Docs")
+                .add_many_title_url_pairs(vec![URLTitlePair::new("https://c.com", "Spreadsheets")])
+                .build(),
+        ];
+
+        let hits: Vec<(usize, usize)> = search_profiles(&prfls, &Query::parse("docs").unwrap())
+            .iter()
+            .map(|hit| (hit.prfl_idx, hit.tab_idx))
+            .collect();
+        assert_eq!(hits, vec![(0, 1), (0, 0)]);
+
+        assert_eq!(fuzzy_positions("Team Docs", "tdo"), vec![0, 5, 6]);
+    }
+}
@@ -0,0 +1,264 @@
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use url::Url;
+
+use crate::Errors;
+
+//
+//
+//Small helpers for checking and comparing the URLs we store
+//
+//
+
+
+
+const DEFAULT_SCHEME: &str = "https://";
+
+/// The placeholder filled with today's date (`2024-01-31`) unless it is given a value
+pub const DATE_PLACEHOLDER: &str = "date";
+
+/// What a placeholder value is encoded with in a URL: everything but the unreserved characters of RFC 3986
+const PLACEHOLDER_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+
+
+///
+///Checks that `url` is something a browser can open,
+///and returns it in the form it should be saved in.
+///
+///A missing scheme (`example.com/page`) gets `https://` prepended,
+///everything else is returned as typed by the user.
+///
+pub fn validate_url(url: &str) -> Result<String, Errors> {
+    let url = url.trim();
+    let invalid = || Errors::URLParseError { url: url.to_string(), line: None };
+
+    if url.is_empty() || url.contains(char::is_whitespace) {
+        return Err(invalid());
+    }
+
+    match Url::parse(url) {
+        Ok(parsed) => match parsed.scheme() {
+            "http" | "https" | "ftp" | "ws" | "wss" if parsed.host_str().is_none() => Err(invalid()),
+            _ => Ok(url.to_string()),
+        },
+        Err(url::ParseError::RelativeUrlWithoutBase) => {
+            let with_scheme = format!("{}{}", DEFAULT_SCHEME, url);
+            let parsed = Url::parse(&with_scheme).map_err(|_| invalid())?;
+
+            // "foo" is not a host anyone means to visit, "foo.com" or "localhost:8080" is
+            match parsed.host_str() {
+                Some(host) if host.contains('.') || parsed.port().is_some() => Ok(with_scheme),
+                _ => Err(invalid()),
+            }
+        },
+        Err(_) => Err(invalid()),
+    }
+}
+
+
+///
+///Returns the form of `url` used to decide whether two tabs point to the same page:
+///lowercase scheme & host, no default port, no fragment, no trailing slash.
+///
+///URLs that cannot be parsed are only trimmed.
+///
+pub fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+
+    match Url::parse(url) {
+        Ok(mut parsed) => {
+            parsed.set_fragment(None);
+
+            let mut normalized = parsed.to_string();
+            if normalized.ends_with('/') {
+                normalized.pop();
+            }
+            normalized
+        },
+        Err(_) => url.trim_end_matches('/').to_string(),
+    }
+}
+
+
+/// The host part of `url`, without a leading `www.`
+pub fn host_of(url: &str) -> Option<String> {
+    let parsed = Url::parse(url.trim()).ok()?;
+    let host = parsed.host_str()?;
+
+    Some(host.strip_prefix("www.").unwrap_or(host).to_string())
+}
+
+
+/// The pieces of a URL shown to the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct URLParts {
+    pub host: Option<String>,
+    pub path: String,
+    /// decoded `key=value` pairs, in order
+    pub query: Vec<(String, String)>,
+}
+
+/// Splits `url` into host, path and query parameters, if it can be parsed
+pub fn url_parts(url: &str) -> Option<URLParts> {
+    let parsed = Url::parse(url.trim()).ok()?;
+
+    Some(URLParts {
+        host: parsed.host_str().map(str::to_string),
+        path: parsed.path().to_string(),
+        query: parsed.query_pairs().into_owned().collect(),
+    })
+}
+
+
+///
+///The names of the placeholders in `text`, like `q` in `https://www.youtube.com/results?search_query={q}`,
+///in the order they first appear. A name is made of letters, digits, `_` and `-`.
+///
+pub fn placeholders(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+
+    for (_, name) in placeholder_spans(text) {
+        if !names.iter().any(|other| other == name) {
+            names.push(name.to_string());
+        }
+    }
+
+    names
+}
+
+///
+///`text` with every placeholder that has a value in `values` replaced by it,
+///and [DATE_PLACEHOLDER] by today's date if it has none. The others are left as they are.
+///
+pub fn fill_placeholders(text: &str, values: &[(String, String)]) -> String {
+    fill(text, values, |value| value.to_string())
+}
+
+/// Like [fill_placeholders], with the values percent-encoded to fit anywhere in the URL `url`
+pub fn fill_url_placeholders(url: &str, values: &[(String, String)]) -> String {
+    fill(url, values, |value| utf8_percent_encode(value, PLACEHOLDER_ENCODE_SET).to_string())
+}
+
+
+/// The byte range of every `{name}` in `text`, with the name
+fn placeholder_spans(text: &str) -> Vec<(std::ops::Range<usize>, &str)> {
+    let is_name = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    let mut spans = Vec::new();
+
+    let mut start = 0;
+    while let Some(open) = text[start..].find('{').map(|pos| start + pos) {
+        let name_len = text[open + 1..].find(|c: char| !is_name(c)).unwrap_or(text.len() - open - 1);
+        let close = open + 1 + name_len;
+
+        if name_len > 0 && text[close..].starts_with('}') {
+            spans.push((open..close + 1, &text[open + 1..close]));
+            start = close + 1;
+        } else {
+            start = open + 1;
+        }
+    }
+
+    spans
+}
+
+fn fill(text: &str, values: &[(String, String)], encode: impl Fn(&str) -> String) -> String {
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let mut filled = String::with_capacity(text.len());
+
+    let mut last = 0;
+    for (span, name) in placeholder_spans(text) {
+        let value = values
+            .iter()
+            .find(|(other, _)| other == name)
+            .map(|(_, value)| value.as_str())
+            .or((name == DATE_PLACEHOLDER).then_some(today.as_str()));
+
+        if let Some(value) = value {
+            filled.push_str(&text[last..span.start]);
+            filled.push_str(&encode(value));
+            last = span.end;
+        }
+    }
+    filled.push_str(&text[last..]);
+
+    filled
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn placeholders_are_names_in_braces() {
+        assert_eq!(placeholders("https://a.com/{q}?page={page_2}&lang={größe}"), ["q", "page_2", "größe"]);
+        // repeated ones are named once, in the order they first appear
+        assert_eq!(placeholders("{b}/{a}/{b}"), ["b", "a"]);
+        // only the innermost of nested braces, nothing unclosed or with other characters
+        assert_eq!(placeholders("{{q}}"), ["q"]);
+        assert_eq!(placeholders("{q{r}"), ["r"]);
+        assert!(placeholders("{q").is_empty());
+        assert!(placeholders("{} {a b} {a.b}").is_empty());
+    }
+
+    #[test]
+    fn spans_are_byte_ranges() {
+        assert_eq!(placeholder_spans("ä{q}"), vec![(2..5, "q")]);
+        assert_eq!(placeholder_spans("{x}{y}"), vec![(0..3, "x"), (3..6, "y")]);
+    }
+
+    #[test]
+    fn filling_replaces_what_has_a_value() {
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+        assert_eq!(fill_placeholders("{q} and {q}, {other}", &values(&[("q", "rust")])), "rust and rust, {other}");
+        assert_eq!(fill_placeholders("{{q}}", &values(&[("q", "x")])), "{x}");
+        assert_eq!(fill_placeholders("News of {date}", &[]), format!("News of {}", today));
+        assert_eq!(fill_placeholders("{date}", &values(&[("date", "2024-01-31")])), "2024-01-31");
+        // values are put in as they are, placeholders in them are not filled again
+        assert_eq!(fill_placeholders("{a}", &values(&[("a", "{b}"), ("b", "no")])), "{b}");
+    }
+
+    #[test]
+    fn url_values_are_percent_encoded() {
+        let url = "https://www.youtube.com/results?search_query={q}";
+
+        assert_eq!(fill_url_placeholders(url, &values(&[("q", "c++")])), "https://www.youtube.com/results?search_query=c%2B%2B");
+        assert_eq!(
+            fill_url_placeholders("https://a.com/{path}", &values(&[("path", "a b/é?&=#")])),
+            "https://a.com/a%20b%2F%C3%A9%3F%26%3D%23"
+        );
+        assert_eq!(fill_url_placeholders("https://a.com/{v}", &values(&[("v", "A-z.0_9~")])), "https://a.com/A-z.0_9~");
+        // the rest of the URL is left alone
+        assert_eq!(fill_url_placeholders("https://a.com/%20{v}", &[]), "https://a.com/%20{v}");
+    }
+
+    #[test]
+    fn normalized_urls_compare_equal() {
+        assert_eq!(normalize_url(" HTTPS://Example.COM:443/Path/#top "), "https://example.com/Path");
+        assert_eq!(normalize_url("https://a.com"), normalize_url("https://a.com/"));
+        assert_ne!(normalize_url("https://a.com/?q=1"), normalize_url("https://a.com/?q=2"));
+        assert_eq!(normalize_url("not a url//"), "not a url");
+    }
+
+    #[test]
+    fn valid_urls_get_a_scheme_if_they_need_one() {
+        assert_eq!(validate_url(" example.com/page ").unwrap(), "https://example.com/page");
+        assert_eq!(validate_url("http://a.com").unwrap(), "http://a.com");
+        assert_eq!(validate_url("about:blank").unwrap(), "about:blank");
+        assert_eq!(
+            validate_url("https://www.youtube.com/results?search_query={q}").unwrap(),
+            "https://www.youtube.com/results?search_query={q}"
+        );
+
+        for url in ["", "foo", "http://", "https://a .com"] {
+            assert!(matches!(validate_url(url), Err(Errors::URLParseError { line: None, .. })), "{}", url);
+        }
+    }
+}